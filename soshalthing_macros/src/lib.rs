@@ -67,6 +67,7 @@ pub fn service(args: TokenStream, input: TokenStream) -> TokenStream {
 	}
 
 	let service_name = &args.name;
+	let service_ident = &item_struct.ident;
 	let gen = quote! {
 		use crate::services::ServiceInfo;
 
@@ -75,6 +76,23 @@ pub fn service(args: TokenStream, input: TokenStream) -> TokenStream {
 		};
 
 		#item_struct
+
+		impl #service_ident {
+			fn collect_garbage(&mut self, cap: Option<usize>) -> (crate::services::garbage_collector::ServiceMemoryStats, Vec<crate::articles::ArticleWeak>) {
+				crate::services::garbage_collector::collect_articles(&mut self.articles, cap)
+			}
+
+			/// Collects the articles when asked to, then forgets them in the endpoints and reports the stats
+			fn handle_garbage_collector_response(&mut self, response: crate::services::garbage_collector::GarbageCollectorResponse) {
+				if let crate::services::garbage_collector::GarbageCollectorResponse::Collect(cap) = response {
+					let (stats, forgotten) = self.collect_garbage(cap);
+					if !forgotten.is_empty() {
+						self.endpoint_agent.send(crate::services::endpoint_agent::EndpointRequest::ForgetArticles(forgotten));
+					}
+					self.garbage_collector.send(crate::services::garbage_collector::GarbageCollectorRequest::ReportStats(SERVICE_INFO.name, stats));
+				}
+			}
+		}
 	};
 
 	gen.into()
@@ -236,9 +236,12 @@ impl Component for ArticleComponent {
 		);
 
 		//For some reason, the view needs at least a wrapper otherwise when changing article_view, the container draws everything in reverse order...
+		let title = ctx.props().article_struct.rejected_by.as_ref().map(|filter| format!("Filtered by {}", filter));
+
 		let article_html = html! {
-			<article {class} articleId={ctx.props().article_struct.boxed.id()} style={ctx.props().style.clone()} ref={self.component_ref.clone()}>
+			<article {class} {title} articleId={ctx.props().article_struct.boxed.id()} style={ctx.props().style.clone()} ref={self.component_ref.clone()}>
 				{ view_html }
+				{ self.view_also_posted_on(ctx) }
 			</article>
 		};
 
@@ -266,6 +269,24 @@ impl Component for ArticleComponent {
 	}*/
 }
 
+impl ArticleComponent {
+	fn view_also_posted_on(&self, ctx: &Context<Self>) -> Html {
+		let also_posted_on = &ctx.props().article_struct.also_posted_on;
+		if also_posted_on.is_empty() {
+			return html! {};
+		}
+
+		html! {
+			<div class="alsoPostedOn tags">
+				<span class="tag is-dark">{"Also posted on"}</span>
+				{ for also_posted_on.iter().map(|(service, url)| html! {
+					<a class="tag" href={url.clone()} target="_blank" rel="noopener noreferrer">{ service }</a>
+				}) }
+			</div>
+		}
+	}
+}
+
 fn pause_video(video_ref: &NodeRef) {
 	if let Some(video) = video_ref.cast::<web_sys::HtmlVideoElement>() {
 		video.set_muted(true);
@@ -39,6 +39,16 @@ pub trait ArticleData : Debug {
 	fn referenced_articles(&self) -> Vec<ArticleRefType> { Vec::new() }
 	fn actual_article_index(&self) -> Option<usize> { None }
 	fn actual_article(&self) -> Option<ArticleWeak> { None }
+	/// Without the leading #
+	fn hashtags(&self) -> Vec<String> {
+		self.text().split_whitespace()
+			.filter_map(|word| word.strip_prefix('#'))
+			.map(|hashtag| hashtag.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_').to_owned())
+			.filter(|hashtag| !hashtag.is_empty())
+			.collect()
+	}
+	/// Expanded urls linked in the text
+	fn links(&self) -> Vec<String> { Vec::new() }
 	fn url(&self) -> String;
 	fn marked_as_read(&self) -> bool;
 	fn set_marked_as_read(&mut self, value: bool);
@@ -83,6 +93,54 @@ pub enum UnfetchedArticleRef {
 	ReplyToUser(String),
 }
 
+/// Response JSON an article was parsed from, left as text when it comes from the fetch worker
+#[derive(Clone, Debug, PartialEq)]
+pub enum RawJson {
+	Value(serde_json::Value),
+	Text(String),
+}
+
+impl RawJson {
+	pub fn value(&self) -> serde_json::Value {
+		match self {
+			RawJson::Value(value) => value.clone(),
+			RawJson::Text(text) => serde_json::from_str(text).unwrap_or_default(),
+		}
+	}
+
+	pub fn is_null(&self) -> bool {
+		matches!(self, RawJson::Value(serde_json::Value::Null))
+	}
+}
+
+impl Default for RawJson {
+	fn default() -> Self {
+		RawJson::Value(serde_json::Value::Null)
+	}
+}
+
+impl From<serde_json::Value> for RawJson {
+	fn from(value: serde_json::Value) -> Self {
+		RawJson::Value(value)
+	}
+}
+
+//Carried as a string, bincode can't deserialize a serde_json::Value
+impl Serialize for RawJson {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+		match self {
+			RawJson::Value(value) => serializer.serialize_str(&value.to_string()),
+			RawJson::Text(text) => serializer.serialize_str(text),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for RawJson {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+		String::deserialize(deserializer).map(RawJson::Text)
+	}
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ValidRatio(f32);
 
@@ -129,7 +187,7 @@ pub struct ArticleMedia {
 	pub media_type: MediaType,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum MediaType {
 	Image,
 	Video,
@@ -113,6 +113,10 @@ impl Component for SocialArticle {
 								false => html! {<p class="articleParagraph">{ actual_article.view_text() }</p>},
 								true => html! {},
 							} }
+							{ match (self.is_minimized(ctx), &ctx.props().article_struct.rejected_by) {
+								(true, Some(filter)) => html! {<p class="help">{ format!("Filtered by {}", filter) }</p>},
+								_ => html! {},
+							} }
 						</div>
 						{ quoted_post }
 						{ self.view_nav(ctx, &actual_article, actual_weak, false) }
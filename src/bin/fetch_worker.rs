@@ -0,0 +1,8 @@
+use yew_agent::Threaded;
+use soshalthing::services::fetch_worker::FetchWorker;
+
+fn main() {
+	wasm_logger::init(wasm_logger::Config::new(log::Level::Trace));
+
+	FetchWorker::register();
+}
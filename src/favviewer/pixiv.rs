@@ -11,6 +11,7 @@ use crate::services::{
 	endpoint_agent::{EndpointRequest, TimelineCreationRequest},
 	pixiv::endpoints::*,
 	storages::get_or_init_favviewer_settings,
+	migrations::migrate_storage,
 };
 use crate::services::endpoint_agent::BatchEndpointAddClosure;
 
@@ -102,6 +103,10 @@ pub fn setup(href: &str) -> bool {
 			}, document_head.into(),
 		));
 
+		//The settings are read before Model migrates the storage, which notifies if this failed
+		if let Err(err) = migrate_storage() {
+			log::error!("{}", &err);
+		}
 		let display_mode = get_or_init_favviewer_settings(DisplayMode::Single {
 			container: Container::Masonry,
 			column_count: 5,
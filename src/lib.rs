@@ -20,7 +20,7 @@ use components::{FA, IconSize};
 use error::Result;
 use favviewer::PageInfo;
 use settings::{AppSettings, ArticleFilteredMode, OnMediaClick, SettingsModal, SettingsAgent, SettingsRequest, SettingsResponse};
-use notifications::{NotificationAgent, NotificationRequest, NotificationResponse};
+use notifications::{Notification, NotificationAgent, NotificationRequest, NotificationResponse};
 use services::{
 	Endpoint,
 	endpoint_agent::{EndpointId, EndpointAgent, TimelineEndpointWrapper, EndpointRequest},
@@ -29,6 +29,7 @@ use services::{
 	twitter::{endpoints::*, TwitterAgent, TwitterRequest, TwitterResponse, SERVICE_INFO as TwitterServiceInfo},
 	youtube::{YouTubeAgent, YouTubeRequest, YouTubeResponse, SERVICE_INFO as YouTubeServiceInfo},
 	storages::SoshalLocalStorage,
+	migrations::migrate_storage,
 };
 use sidebar::Sidebar;
 use timeline::{
@@ -95,6 +96,12 @@ impl Component for Model {
 		let mut _notification_agent = NotificationAgent::bridge(ctx.link().callback(Msg::NotificationResponse));
 		_notification_agent.send(NotificationRequest::RegisterTimelineContainer);
 
+		//Before any agent reads the storage
+		if let Err(err) = migrate_storage() {
+			log::error!("{}", &err);
+			_notification_agent.send(NotificationRequest::Notify(Some("StorageMigration".to_owned()), Notification::Error(err)));
+		}
+
 		let mut twitter = TwitterAgent::bridge(ctx.link().callback(Msg::TwitterResponse));
 		twitter.send(TwitterRequest::Sidebar);
 		let _pixiv = PixivAgent::dispatcher();
@@ -255,7 +262,7 @@ impl Component for Model {
 	fn view(&self, ctx: &Context<Self>) -> Html {
 		html! {
 			<>
-				<SettingsModal app_settings={self.app_settings}/>
+				<SettingsModal app_settings={self.app_settings} favviewer={ctx.props().favviewer}/>
 				<div id="soshalNotifications">
 					{ for self.notifications.iter().cloned() }
 				</div>
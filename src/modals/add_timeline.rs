@@ -20,6 +20,7 @@ pub struct AddTimelineModal {
 	endpoints: Rc<RefCell<Vec<TimelineEndpointWrapper>>>,
 	_agent: Box<dyn Bridge<TimelineAgent>>,
 	filters: FilterCollection,
+	filter_preset: Option<String>,
 	set_as_main_timeline: bool,
 }
 
@@ -52,6 +53,7 @@ impl Component for AddTimelineModal {
 			title_ref: NodeRef::default(),
 			endpoints: Rc::new(RefCell::new(Vec::new())),
 			filters: FilterCollection::default(),
+			filter_preset: None,
 			_agent,
 			set_as_main_timeline: false,
 		}
@@ -66,6 +68,7 @@ impl Component for AddTimelineModal {
 				};
 				let endpoints = self.endpoints.borrow().clone();
 				let filters = self.filters.clone();
+				let filter_preset = self.filter_preset.take();
 				let set_as_main_timeline = self.set_as_main_timeline;
 				ctx.props().add_timeline_callback.emit((Box::new(|id| {
 					yew::props! { TimelineProps {
@@ -73,6 +76,7 @@ impl Component for AddTimelineModal {
 						id,
 						endpoints,
 						filters,
+						filter_preset,
 					}}
 				}), set_as_main_timeline));
 
@@ -91,6 +95,7 @@ impl Component for AddTimelineModal {
 					}
 					self.endpoints.borrow_mut().clear();
 					self.filters = FilterCollection::default();
+					self.filter_preset = None;
 
 					self.enabled = true;
 					true
@@ -101,6 +106,7 @@ impl Component for AddTimelineModal {
 					}
 					self.endpoints.borrow_mut().clear();
 					self.filters = FilterCollection::default();
+					self.filter_preset = None;
 
 					self.enabled = true;
 					true
@@ -111,7 +117,12 @@ impl Component for AddTimelineModal {
 				self.enabled = value;
 				true
 			}
-			Msg::FilterMsg(msg) => self.filters.update(msg),
+			Msg::FilterMsg(msg) => {
+				if let FilterMsg::LoadPreset(name, _) = &msg {
+					self.filter_preset = Some(name.clone());
+				}
+				self.filters.update(msg)
+			}
 			Msg::ToggleSetMainTimeline => {
 				self.set_as_main_timeline = !self.set_as_main_timeline;
 				false
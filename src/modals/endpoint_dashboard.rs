@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use yew::prelude::*;
+use yew_agent::{Bridge, Bridged};
+use gloo_timers::callback::Interval;
+use wasm_bindgen::JsCast;
+
+use super::ModalCard;
+use crate::modals::modal_agent::{ModalAgent, ModalRequest, ModalType};
+use crate::services::endpoint_agent::{EndpointAgent, EndpointId, EndpointRequest, EndpointResponse, EndpointView, RefreshTime};
+use crate::services::garbage_collector::{GarbageCollectorAgent, GarbageCollectorRequest, GarbageCollectorResponse, ServiceMemoryStats};
+use crate::timeline::TimelineId;
+
+pub struct EndpointDashboardModal {
+	enabled: bool,
+	endpoints: Vec<EndpointView>,
+	_modal_agent: Box<dyn Bridge<ModalAgent>>,
+	endpoint_agent: Box<dyn Bridge<EndpointAgent>>,
+	memory_stats: HashMap<&'static str, ServiceMemoryStats>,
+	article_caps: HashMap<String, usize>,
+	garbage_collector: Box<dyn Bridge<GarbageCollectorAgent>>,
+	/// Keeps the countdowns updated while shown
+	ticker: Option<Interval>,
+}
+
+pub enum EndpointDashboardMsg {
+	SetEnabled(bool),
+	EndpointResponse(EndpointResponse),
+	Refresh(EndpointId),
+	Pause(EndpointId),
+	Resume(EndpointId),
+	Remove(EndpointId),
+	Tick,
+	GarbageCollectorResponse(GarbageCollectorResponse),
+	SetArticleCap(String, Option<usize>),
+	CollectGarbage,
+}
+
+type Msg = EndpointDashboardMsg;
+
+#[derive(Properties, PartialEq)]
+pub struct EndpointDashboardProps {
+	pub timeline_ids: Vec<(TimelineId, String)>,
+}
+
+impl Component for EndpointDashboardModal {
+	type Message = Msg;
+	type Properties = EndpointDashboardProps;
+
+	fn create(ctx: &Context<Self>) -> Self {
+		let mut _modal_agent = ModalAgent::bridge(ctx.link().callback(|_| Msg::SetEnabled(true)));
+		_modal_agent.send(ModalRequest::Register(ModalType::EndpointDashboard));
+
+		let mut endpoint_agent = EndpointAgent::bridge(ctx.link().callback(Msg::EndpointResponse));
+		endpoint_agent.send(EndpointRequest::GetState);
+
+		let mut garbage_collector = GarbageCollectorAgent::bridge(ctx.link().callback(Msg::GarbageCollectorResponse));
+		garbage_collector.send(GarbageCollectorRequest::GetStats);
+
+		Self {
+			enabled: false,
+			endpoints: Vec::new(),
+			_modal_agent,
+			endpoint_agent,
+			memory_stats: HashMap::new(),
+			article_caps: HashMap::new(),
+			garbage_collector,
+			ticker: None,
+		}
+	}
+
+	fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+		match msg {
+			Msg::SetEnabled(enabled) => {
+				self.enabled = enabled;
+				self.ticker = if enabled {
+					let link = ctx.link().clone();
+					Some(Interval::new(1_000, move || link.send_message(Msg::Tick)))
+				} else {
+					None
+				};
+				true
+			}
+			Msg::EndpointResponse(response) => match response {
+				EndpointResponse::UpdatedState(_services, mut endpoints) => {
+					endpoints.sort_by_key(|e| e.id);
+					self.endpoints = endpoints;
+					self.enabled
+				}
+				_ => false,
+			}
+			Msg::Refresh(endpoint_id) => {
+				self.endpoint_agent.send(EndpointRequest::RefreshEndpoint(endpoint_id, RefreshTime::OnRefresh));
+				false
+			}
+			Msg::Pause(endpoint_id) => {
+				self.endpoint_agent.send(EndpointRequest::StopAutoRefresh(endpoint_id));
+				false
+			}
+			Msg::Resume(endpoint_id) => {
+				self.endpoint_agent.send(EndpointRequest::StartAutoRefresh(endpoint_id));
+				false
+			}
+			Msg::Remove(endpoint_id) => {
+				self.endpoint_agent.send(EndpointRequest::RemoveEndpoint(endpoint_id));
+				false
+			}
+			Msg::Tick => self.enabled,
+			Msg::GarbageCollectorResponse(response) => match response {
+				GarbageCollectorResponse::UpdatedStats(stats, caps) => {
+					self.memory_stats = stats;
+					self.article_caps = caps;
+					self.enabled
+				}
+				_ => false,
+			}
+			Msg::SetArticleCap(service, cap) => {
+				self.garbage_collector.send(GarbageCollectorRequest::SetCap(service, cap));
+				false
+			}
+			Msg::CollectGarbage => {
+				self.garbage_collector.send(GarbageCollectorRequest::Collect);
+				false
+			}
+		}
+	}
+
+	fn view(&self, ctx: &Context<Self>) -> Html {
+		let footer = html! {
+			<button class="button card-footer-item" onclick={ctx.link().callback(|_| Msg::CollectGarbage)}>
+				{"Collect garbage"}
+			</button>
+		};
+
+		html! {
+			<ModalCard enabled={self.enabled} modal_title="Endpoints" close_modal_callback={ctx.link().callback(|_| Msg::SetEnabled(false))} {footer}>
+				<table class="table is-fullwidth is-narrow">
+					<thead>
+						<tr>
+							<th>{"Endpoint"}</th>
+							<th>{"Last refresh"}</th>
+							<th>{"Articles"}</th>
+							<th>{"Rate limit"}</th>
+							<th>{"Auto refresh"}</th>
+							<th>{"Timelines"}</th>
+							<th/>
+						</tr>
+					</thead>
+					<tbody>
+						{ for self.endpoints.iter().map(|endpoint| self.view_endpoint(ctx, endpoint)) }
+					</tbody>
+				</table>
+				{ self.view_memory(ctx) }
+			</ModalCard>
+		}
+	}
+}
+
+impl EndpointDashboardModal {
+	fn view_endpoint(&self, ctx: &Context<Self>, endpoint: &EndpointView) -> Html {
+		let now = js_sys::Date::now();
+		let id = endpoint.id;
+		let timeline_names: Vec<String> = endpoint.timelines.iter()
+			.map(|timeline_id| ctx.props().timeline_ids.iter()
+				.find(|(id, _)| id == timeline_id)
+				.map(|(_, name)| name.clone())
+				.unwrap_or_else(|| timeline_id.to_string())
+			)
+			.collect();
+
+		html! {
+			<tr key={id}>
+				<td>
+					{ endpoint.name.clone() }
+					{ match &endpoint.last_error {
+						Some(error) => html! { <p class="help is-danger">{ error.clone() }</p> },
+						None => html! {},
+					} }
+				</td>
+				<td>
+					{ match endpoint.last_refresh {
+						Some(last_refresh) => format_duration(now - last_refresh) + " ago",
+						None => "Never".to_owned(),
+					} }
+					{ match endpoint.pending_since {
+						Some(pending_since) => html! {
+							<p class="help" title={format!("Given up on after {}", format_duration(endpoint.refresh_timeout as f64))}>
+								{ format!("Refreshing for {}", format_duration(now - pending_since)) }
+							</p>
+						},
+						None => html! {},
+					} }
+				</td>
+				<td>{ endpoint.article_count }</td>
+				<td>
+					{ match &endpoint.ratelimit {
+						Some(ratelimit) => format!("{}/{}, resets in {}", ratelimit.remaining, ratelimit.limit, format_duration(ratelimit.reset_time() - now)),
+						None => "-".to_owned(),
+					} }
+				</td>
+				<td>
+					{ if endpoint.auto_refresh_suspended {
+						"Suspended".to_owned()
+					} else if endpoint.is_autorefreshing {
+						match endpoint.refresh_schedule.current_interval(endpoint.autorefresh_interval) {
+							Some(interval) => format_duration(interval as f64),
+							None => "Quiet hours".to_owned(),
+						}
+					} else {
+						"Off".to_owned()
+					} }
+				</td>
+				<td>{ timeline_names.join(", ") }</td>
+				<td>
+					<div class="buttons are-small">
+						<button class="button" onclick={ctx.link().callback(move |_| Msg::Refresh(id))}>{"Refresh"}</button>
+						{ if endpoint.is_autorefreshing {
+							html! { <button class="button" onclick={ctx.link().callback(move |_| Msg::Pause(id))}>{"Pause"}</button> }
+						} else {
+							html! { <button class="button" onclick={ctx.link().callback(move |_| Msg::Resume(id))}>{"Resume"}</button> }
+						} }
+						{ if endpoint.timelines.is_empty() && endpoint.virtual_endpoints.is_empty() {
+							html! { <button class="button is-danger" onclick={ctx.link().callback(move |_| Msg::Remove(id))}>{"Delete"}</button> }
+						} else {
+							html! {}
+						} }
+					</div>
+				</td>
+			</tr>
+		}
+	}
+
+	fn view_memory(&self, ctx: &Context<Self>) -> Html {
+		let mut services: Vec<(&&'static str, &ServiceMemoryStats)> = self.memory_stats.iter().collect();
+		services.sort_by_key(|(service, _)| **service);
+
+		html! {
+			<table class="table is-fullwidth is-narrow">
+				<thead>
+					<tr>
+						<th>{"Service"}</th>
+						<th>{"Articles"}</th>
+						<th>{"Evicted"}</th>
+						<th>{"Article cap"}</th>
+					</tr>
+				</thead>
+				<tbody>
+					{ for services.into_iter().map(|(service, stats)| {
+						let service = *service;
+						let oninput = ctx.link().callback(move |e: InputEvent| {
+							let cap = e.target()
+								.and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+								.and_then(|i| i.value().parse::<usize>().ok());
+							Msg::SetArticleCap(service.to_owned(), cap)
+						});
+						let cap = self.article_caps.get(service).map(|cap| cap.to_string()).unwrap_or_default();
+
+						html! {
+							<tr key={service}>
+								<td>{ service }</td>
+								<td>{ stats.article_count }</td>
+								<td>{ stats.evicted }</td>
+								<td>
+									<input class="input is-small" type="number" min=0 placeholder="None" value={cap} {oninput}/>
+								</td>
+							</tr>
+						}
+					}) }
+				</tbody>
+			</table>
+		}
+	}
+}
+
+fn format_duration(ms: f64) -> String {
+	let seconds = (ms / 1000.0).max(0.0).round() as u64;
+	if seconds < 60 {
+		format!("{}s", seconds)
+	} else if seconds < 3600 {
+		format!("{}m{}s", seconds / 60, seconds % 60)
+	} else {
+		format!("{}h{}m", seconds / 3600, (seconds % 3600) / 60)
+	}
+}
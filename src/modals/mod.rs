@@ -3,6 +3,8 @@ use yew::prelude::*;
 pub mod modal_agent;
 pub mod add_timeline;
 pub mod batch_action;
+pub mod endpoint_dashboard;
+pub mod mute_list;
 
 pub use add_timeline::AddTimelineModal;
 use crate::components::FA;
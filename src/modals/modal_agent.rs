@@ -43,4 +43,6 @@ impl Agent for ModalAgent {
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ModalType {
 	BatchAction,
+	EndpointDashboard,
+	MuteList,
 }
\ No newline at end of file
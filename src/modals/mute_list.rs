@@ -0,0 +1,246 @@
+use yew::prelude::*;
+use yew_agent::{Bridge, Bridged};
+use wasm_bindgen::JsCast;
+
+use super::ModalCard;
+use crate::components::{Dropdown, DropdownLabel};
+use crate::modals::modal_agent::{ModalAgent, ModalRequest, ModalType};
+use crate::services::endpoint_agent::{EndpointAgent, EndpointRequest, EndpointResponse};
+use crate::services::mute::{MuteList, MuteRule, MuteTarget};
+
+const TARGET_KINDS: [&str; 5] = ["User", "Keyword", "Regex", "Hashtag", "Domain"];
+
+const EXPIRIES: [(&str, Option<f64>); 5] = [
+	("Never", None),
+	("1 hour", Some(3_600_000.0)),
+	("1 day", Some(86_400_000.0)),
+	("1 week", Some(604_800_000.0)),
+	("30 days", Some(2_592_000_000.0)),
+];
+
+pub struct MuteListModal {
+	enabled: bool,
+	mute_list: MuteList,
+	muted_count: usize,
+	services: Vec<&'static str>,
+	kind: usize,
+	service: Option<&'static str>,
+	value: String,
+	expiry: usize,
+	_modal_agent: Box<dyn Bridge<ModalAgent>>,
+	endpoint_agent: Box<dyn Bridge<EndpointAgent>>,
+}
+
+pub enum MuteListMsg {
+	SetEnabled(bool),
+	EndpointResponse(EndpointResponse),
+	SetKind(usize),
+	SetService(&'static str),
+	SetValue(String),
+	SetExpiry(usize),
+	AddRule,
+	RemoveRule(usize),
+}
+
+type Msg = MuteListMsg;
+
+impl Component for MuteListModal {
+	type Message = Msg;
+	type Properties = ();
+
+	fn create(ctx: &Context<Self>) -> Self {
+		let mut _modal_agent = ModalAgent::bridge(ctx.link().callback(|_| Msg::SetEnabled(true)));
+		_modal_agent.send(ModalRequest::Register(ModalType::MuteList));
+
+		let mut endpoint_agent = EndpointAgent::bridge(ctx.link().callback(Msg::EndpointResponse));
+		endpoint_agent.send(EndpointRequest::GetState);
+		endpoint_agent.send(EndpointRequest::GetMuteList);
+
+		Self {
+			enabled: false,
+			mute_list: MuteList::default(),
+			muted_count: 0,
+			services: Vec::new(),
+			kind: 0,
+			service: None,
+			value: String::new(),
+			expiry: 0,
+			_modal_agent,
+			endpoint_agent,
+		}
+	}
+
+	fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+		match msg {
+			Msg::SetEnabled(enabled) => {
+				self.enabled = enabled;
+				if enabled {
+					self.endpoint_agent.send(EndpointRequest::GetMuteList);
+				}
+				true
+			}
+			Msg::EndpointResponse(response) => match response {
+				EndpointResponse::UpdatedState(services, _endpoints) => {
+					let mut services: Vec<&'static str> = services.keys().copied().collect();
+					services.sort_unstable();
+					if self.service.is_none() {
+						self.service = services.first().copied();
+					}
+					self.services = services;
+					self.enabled
+				}
+				EndpointResponse::UpdatedMuteList(mute_list, muted_count) => {
+					self.mute_list = mute_list;
+					self.muted_count = muted_count;
+					self.enabled
+				}
+				_ => false,
+			}
+			Msg::SetKind(kind) => {
+				self.kind = kind;
+				true
+			}
+			Msg::SetService(service) => {
+				self.service = Some(service);
+				true
+			}
+			Msg::SetValue(value) => {
+				self.value = value;
+				false
+			}
+			Msg::SetExpiry(expiry) => {
+				self.expiry = expiry;
+				true
+			}
+			Msg::AddRule => match self.target() {
+				Some(target) => {
+					let expiry = EXPIRIES[self.expiry].1.map(|duration| js_sys::Date::now() + duration);
+					self.endpoint_agent.send(EndpointRequest::AddMuteRule(MuteRule { target, expiry }));
+					self.value.clear();
+					true
+				}
+				None => false,
+			}
+			Msg::RemoveRule(index) => {
+				self.endpoint_agent.send(EndpointRequest::RemoveMuteRule(index));
+				false
+			}
+		}
+	}
+
+	fn view(&self, ctx: &Context<Self>) -> Html {
+		let footer = html! {
+			<button class="button card-footer-item" onclick={ctx.link().callback(|_| Msg::SetEnabled(false))}>
+				{"Close"}
+			</button>
+		};
+
+		html! {
+			<ModalCard enabled={self.enabled} modal_title="Mute list" close_modal_callback={ctx.link().callback(|_| Msg::SetEnabled(false))} {footer}>
+				<p class="block">{ format!("{} loaded articles muted", self.muted_count) }</p>
+				<table class="table is-fullwidth is-narrow">
+					<thead>
+						<tr>
+							<th>{"Type"}</th>
+							<th>{"Muted"}</th>
+							<th>{"Expires"}</th>
+							<th/>
+						</tr>
+					</thead>
+					<tbody>
+						{ for self.mute_list.rules.iter().enumerate().map(|(i, rule)| html! {
+							<tr key={i}>
+								<td>{ rule.target.kind_name() }</td>
+								<td>{ rule.target.value() }</td>
+								<td>
+									{ match rule.expiry {
+										Some(expiry) => String::from(js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(expiry)).to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED)),
+										None => "Never".to_owned(),
+									} }
+								</td>
+								<td>
+									<button class="button is-small" onclick={ctx.link().callback(move |_| Msg::RemoveRule(i))}>{"Remove"}</button>
+								</td>
+							</tr>
+						}) }
+					</tbody>
+				</table>
+				{ self.view_new_rule(ctx) }
+			</ModalCard>
+		}
+	}
+}
+
+impl MuteListModal {
+	fn target(&self) -> Option<MuteTarget> {
+		let value = self.value.trim();
+		if value.is_empty() {
+			return None;
+		}
+
+		Some(match self.kind {
+			0 => MuteTarget::User {
+				service: self.service?.to_owned(),
+				username: value.trim_start_matches('@').to_owned(),
+			},
+			1 => MuteTarget::Keyword(value.to_owned()),
+			2 => MuteTarget::Regex(value.to_owned()),
+			3 => MuteTarget::Hashtag(value.trim_start_matches('#').to_owned()),
+			_ => MuteTarget::Domain(value.to_owned()),
+		})
+	}
+
+	fn view_new_rule(&self, ctx: &Context<Self>) -> Html {
+		let oninput = ctx.link().callback(|e: InputEvent| {
+			let input: web_sys::HtmlInputElement = e.target().unwrap().dyn_into().unwrap();
+			Msg::SetValue(input.value())
+		});
+
+		html! {
+			<div class="field has-addons">
+				<div class="control">
+					<Dropdown current_label={DropdownLabel::Text(TARGET_KINDS[self.kind].to_owned())}>
+						{ for TARGET_KINDS.iter().enumerate().map(|(i, kind)| html! {
+							<a class="dropdown-item" onclick={ctx.link().callback(move |_| Msg::SetKind(i))}>
+								{ kind }
+							</a>
+						}) }
+					</Dropdown>
+				</div>
+				{ if self.kind == 0 {
+					html! {
+						<div class="control">
+							<Dropdown current_label={DropdownLabel::Text(self.service.unwrap_or("Service").to_owned())}>
+								{ for self.services.iter().map(|service| {
+									let service = *service;
+									html! {
+										<a class="dropdown-item" onclick={ctx.link().callback(move |_| Msg::SetService(service))}>
+											{ service }
+										</a>
+									}
+								}) }
+							</Dropdown>
+						</div>
+					}
+				} else {
+					html! {}
+				} }
+				<div class="control is-expanded">
+					<input class="input" type="text" value={self.value.clone()} {oninput}/>
+				</div>
+				<div class="control">
+					<Dropdown current_label={DropdownLabel::Text(EXPIRIES[self.expiry].0.to_owned())}>
+						{ for EXPIRIES.iter().enumerate().map(|(i, (label, _))| html! {
+							<a class="dropdown-item" onclick={ctx.link().callback(move |_| Msg::SetExpiry(i))}>
+								{ label }
+							</a>
+						}) }
+					</Dropdown>
+				</div>
+				<div class="control">
+					<button class="button" onclick={ctx.link().callback(|_| Msg::AddRule)}>{"Mute"}</button>
+				</div>
+			</div>
+		}
+	}
+}
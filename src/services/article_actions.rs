@@ -1,12 +1,13 @@
 use yew::prelude::*;
-use yew_agent::{Agent, AgentLink, HandlerId, Context};
+use yew_agent::{Agent, AgentLink, HandlerId, Context, Dispatched, Dispatcher};
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use web_sys::console;
 use wasm_bindgen::JsValue;
 
 use crate::articles::ArticleWeak;
-use crate::services::storages::{hide_article, mark_article_as_read};
+use crate::services::endpoint_agent::{EndpointAgent, EndpointRequest};
+use crate::services::storages::{hide_article, mark_articles_as_read, set_read_watermark};
 
 //TODO Use Action
 pub struct ServiceActions {
@@ -19,6 +20,12 @@ pub struct ArticleActionsAgent {
 	link: AgentLink<Self>,
 	services: HashMap<&'static str, ServiceActions>,
 	subscribers: HashSet<HandlerId>,
+	endpoint_agent: Dispatcher<EndpointAgent>,
+}
+
+pub enum ArticleActionsMsg {
+	/// Loaded articles to mark as read, for the service and watermark
+	MarkAsReadBefore(String, f64, Vec<ArticleWeak>),
 }
 
 pub enum ArticleActionsRequest {
@@ -26,6 +33,8 @@ pub enum ArticleActionsRequest {
 	//Callback(Vec<ArticleWeak>),
 	Action(Action, Vec<ArticleWeak>),
 	RedrawTimelines(Vec<ArticleWeak>),
+	/// Marks every article of the service created before the timestamp as read
+	MarkAsReadBefore(String, f64),
 }
 
 pub enum ArticleActionsResponse {
@@ -33,12 +42,13 @@ pub enum ArticleActionsResponse {
 	RedrawTimelines(Vec<ArticleWeak>),
 }
 
+type Msg = ArticleActionsMsg;
 type Request = ArticleActionsRequest;
 type Response = ArticleActionsResponse;
 
 impl Agent for ArticleActionsAgent {
 	type Reach = Context<Self>;
-	type Message = ();
+	type Message = Msg;
 	type Input = Request;
 	type Output = Response;
 
@@ -47,10 +57,33 @@ impl Agent for ArticleActionsAgent {
 			link,
 			subscribers: HashSet::new(),
 			services: HashMap::new(),
+			endpoint_agent: EndpointAgent::dispatcher(),
 		}
 	}
 
-	fn update(&mut self, _msg: Self::Message) {}
+	fn update(&mut self, msg: Self::Message) {
+		match msg {
+			Msg::MarkAsReadBefore(service, watermark, articles) => {
+				let marked: Vec<ArticleWeak> = articles.into_iter()
+					.filter(|a| match a.upgrade() {
+						Some(strong) => {
+							let mut borrow = strong.borrow_mut();
+							let covered = borrow.service() == service && !borrow.marked_as_read() && borrow.creation_time().get_time() < watermark;
+							if covered {
+								borrow.set_marked_as_read(true);
+							}
+							covered
+						}
+						None => false,
+					})
+					.collect();
+
+				if !marked.is_empty() {
+					self.redraw_timelines(marked);
+				}
+			}
+		}
+	}
 
 	fn connected(&mut self, id: HandlerId) {
 		self.subscribers.insert(id);
@@ -62,7 +95,13 @@ impl Agent for ArticleActionsAgent {
 				self.services.insert(service, actions);
 			}
 			Request::RedrawTimelines(articles) => self.redraw_timelines(articles),
+			Request::MarkAsReadBefore(service, watermark) => {
+				set_read_watermark(&service, Some(watermark));
+				let callback = self.link.callback(move |articles| Msg::MarkAsReadBefore(service.clone(), watermark, articles));
+				self.endpoint_agent.send(EndpointRequest::GetArticles(callback));
+			}
 			Request::Action(action, articles) => {
+				let mut read_marks: HashMap<&'static str, Vec<(String, bool)>> = HashMap::new();
 				for article in &articles {
 					let strong = article.upgrade().unwrap();
 					let mut borrow = strong.borrow_mut();
@@ -82,7 +121,7 @@ impl Agent for ArticleActionsAgent {
 							let new_marked_as_read = !borrow.marked_as_read();
 							borrow.set_marked_as_read(new_marked_as_read);
 
-							mark_article_as_read(borrow.service(), borrow.id(), new_marked_as_read);
+							read_marks.entry(borrow.service()).or_default().push((borrow.id(), new_marked_as_read));
 						}
 						Action::Hide => {
 							let new_hidden = !borrow.hidden();
@@ -113,6 +152,9 @@ impl Agent for ArticleActionsAgent {
 					};
 				}
 
+				for (service, marks) in read_marks {
+					mark_articles_as_read(service, marks);
+				}
 				self.redraw_timelines(articles);
 			}
 		};
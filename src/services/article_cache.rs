@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use yew_agent::{Agent, AgentLink, Context, HandlerId};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use js_sys::{Array, Promise};
+use web_sys::{DomException, IdbDatabase, IdbObjectStoreParameters, IdbRequest, IdbTransaction, IdbTransactionMode};
+
+use crate::error::Result;
+use crate::log_error;
+
+const DB_NAME: &str = "SoshalThingYew";
+const DB_VERSION: u32 = 1;
+const STORE: &str = "articles";
+/// Cached articles above which the oldest are evicted
+const MAX_CACHED_ARTICLES: f64 = 10_000.0;
+/// Share of the cached articles evicted at once
+const EVICTION_RATIO: f64 = 0.2;
+
+/// Reads the cached articles of a service by id, skipping the ones not cached
+pub async fn cached_articles(service: &str, ids: impl IntoIterator<Item = String>) -> HashMap<String, serde_json::Value> {
+	match read_articles(service, ids).await {
+		Ok(articles) => articles,
+		Err(err) => {
+			log::warn!("{}", err.with_message("Failed to read the article cache"));
+			HashMap::new()
+		}
+	}
+}
+
+async fn read_articles(service: &str, ids: impl IntoIterator<Item = String>) -> Result<HashMap<String, serde_json::Value>> {
+	let db = open_database().await?;
+	let transaction = db.transaction_with_str(STORE)?;
+	let store = transaction.object_store(STORE)?;
+
+	let mut articles = HashMap::new();
+	for id in ids {
+		let record = request_result(&store.get(&JsValue::from_str(&format!("{}/{}", service, &id)))?).await?;
+		if !record.is_undefined() {
+			let article: CachedArticle = from_js(&record)?;
+			articles.insert(id, article.data);
+		}
+	}
+	Ok(articles)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CachedArticle {
+	/// `service/id`, the key of the store
+	key: String,
+	pub service: String,
+	pub id: String,
+	/// Timestamp used to evict the oldest articles first
+	pub creation_time: f64,
+	pub data: serde_json::Value,
+}
+
+impl CachedArticle {
+	pub fn new(service: &str, id: String, creation_time: f64, data: serde_json::Value) -> Self {
+		Self {
+			key: format!("{}/{}", service, &id),
+			service: service.to_owned(),
+			id,
+			creation_time,
+			data,
+		}
+	}
+}
+
+/// Persists service articles in IndexedDB, read back with `cached_articles`
+pub struct ArticleCacheAgent {
+	link: AgentLink<Self>,
+	db: Option<IdbDatabase>,
+	loaded: bool,
+	/// Articles received before the database was opened
+	pending: Vec<CachedArticle>,
+}
+
+pub enum ArticleCacheMsg {
+	Opened(Result<IdbDatabase>),
+	Written(Result<usize>),
+}
+
+pub enum ArticleCacheRequest {
+	Cache(Vec<CachedArticle>),
+}
+
+type Msg = ArticleCacheMsg;
+type Request = ArticleCacheRequest;
+
+impl Agent for ArticleCacheAgent {
+	type Reach = Context<Self>;
+	type Message = Msg;
+	type Input = Request;
+	type Output = ();
+
+	fn create(link: AgentLink<Self>) -> Self {
+		link.send_future(async {
+			Msg::Opened(open_database().await)
+		});
+
+		Self {
+			link,
+			db: None,
+			loaded: false,
+			pending: Vec::new(),
+		}
+	}
+
+	fn update(&mut self, msg: Self::Message) {
+		match msg {
+			Msg::Opened(result) => {
+				match result {
+					Ok(db) => {
+						self.db = Some(db);
+
+						let pending = std::mem::take(&mut self.pending);
+						if !pending.is_empty() {
+							self.write(pending);
+						}
+					}
+					Err(err) => log::error!("{}", err.with_message("Failed to open the article cache")),
+				}
+
+				self.loaded = true;
+			}
+			Msg::Written(Ok(evicted)) => if evicted > 0 {
+				log::debug!("Evicted {} cached articles", evicted);
+			}
+			Msg::Written(Err(err)) => log::warn!("{}", err.with_message("Failed to cache articles")),
+		}
+	}
+
+	fn handle_input(&mut self, msg: Self::Input, _id: HandlerId) {
+		match msg {
+			Request::Cache(articles) => {
+				if self.db.is_some() {
+					self.write(articles);
+				} else if !self.loaded {
+					self.pending.extend(articles);
+				}
+			}
+		}
+	}
+}
+
+impl ArticleCacheAgent {
+	fn write(&self, articles: Vec<CachedArticle>) {
+		if let Some(db) = self.db.clone() {
+			self.link.send_future(async move {
+				Msg::Written(cache(&db, &articles).await)
+			});
+		}
+	}
+}
+
+async fn open_database() -> Result<IdbDatabase> {
+	let factory = gloo_utils::window().indexed_db()?
+		.ok_or("IndexedDB isn't available")?;
+	let request = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+
+	let upgrade_request = request.clone();
+	let onupgradeneeded = Closure::once(move || {
+		if let Err(err) = upgrade_request.result().and_then(|db| create_store(&db.unchecked_into())) {
+			log_error!("Failed to create the article cache store", err);
+		}
+	});
+	request.set_onupgradeneeded(Some(onupgradeneeded.as_ref().unchecked_ref()));
+
+	Ok(request_result(&request).await?.unchecked_into())
+}
+
+fn create_store(db: &IdbDatabase) -> std::result::Result<(), JsValue> {
+	let mut parameters = IdbObjectStoreParameters::new();
+	parameters.key_path(Some(&JsValue::from_str("key")));
+	let store = db.create_object_store_with_optional_parameters(STORE, &parameters)?;
+	store.create_index_with_str("creation_time", "creation_time")?;
+	store.create_index_with_str("service", "service")?;
+	Ok(())
+}
+
+/// Writes the articles, evicting the oldest ones when running out of quota or above the cap, and returns the evicted count
+async fn cache(db: &IdbDatabase, articles: &[CachedArticle]) -> Result<usize> {
+	let mut evicted = 0;
+	if let Err(err) = put_articles(db, articles).await {
+		let quota_exceeded = err.dyn_ref::<DomException>()
+			.map(|err| err.name() == "QuotaExceededError")
+			.unwrap_or_default();
+		if !quota_exceeded {
+			return Err(err.into());
+		}
+
+		evicted += evict_oldest(db, 0.0).await?;
+		put_articles(db, articles).await?;
+	}
+
+	evicted += evict_oldest(db, MAX_CACHED_ARTICLES).await?;
+	Ok(evicted)
+}
+
+async fn put_articles(db: &IdbDatabase, articles: &[CachedArticle]) -> std::result::Result<(), JsValue> {
+	let transaction = db.transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)?;
+	let store = transaction.object_store(STORE)?;
+	for article in articles {
+		let value = serde_json::to_string(article)
+			.map_err(|err| JsValue::from_str(&err.to_string()))
+			.and_then(|json| js_sys::JSON::parse(&json))?;
+		store.put(&value)?;
+	}
+	transaction_done(&transaction).await
+}
+
+/// Evicts a share of the oldest articles if the store holds more than `cap`
+async fn evict_oldest(db: &IdbDatabase, cap: f64) -> std::result::Result<usize, JsValue> {
+	let transaction = db.transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)?;
+	let store = transaction.object_store(STORE)?;
+	let count = request_result(&store.count()?).await?.as_f64().unwrap_or_default();
+	if count <= cap {
+		return Ok(0);
+	}
+	let limit = ((count * EVICTION_RATIO).ceil() as u32).max(1);
+
+	let keys: Array = request_result(&store.index("creation_time")?.get_all_keys_with_key_and_limit(&JsValue::NULL, limit)?).await?
+		.unchecked_into();
+	for key in keys.iter() {
+		store.delete(&key)?;
+	}
+	transaction_done(&transaction).await?;
+
+	Ok(keys.length() as usize)
+}
+
+async fn request_result(request: &IdbRequest) -> std::result::Result<JsValue, JsValue> {
+	let promise = Promise::new(&mut |resolve, reject| {
+		request.set_onsuccess(Some(&resolve));
+		request.set_onerror(Some(&reject));
+	});
+	if let Err(err) = JsFuture::from(promise).await {
+		return Err(request.error().ok().flatten().map(JsValue::from).unwrap_or(err));
+	}
+	request.result()
+}
+
+async fn transaction_done(transaction: &IdbTransaction) -> std::result::Result<(), JsValue> {
+	let promise = Promise::new(&mut |resolve, reject| {
+		transaction.set_oncomplete(Some(&resolve));
+		transaction.set_onerror(Some(&reject));
+		transaction.set_onabort(Some(&reject));
+	});
+	JsFuture::from(promise).await
+		.map(|_| ())
+		.map_err(|err| transaction.error().map(JsValue::from).unwrap_or(err))
+}
+
+fn from_js<T: DeserializeOwned>(value: &JsValue) -> Result<T> {
+	let json: String = js_sys::JSON::stringify(value)?.into();
+	Ok(serde_json::from_str(&json)?)
+}
@@ -2,28 +2,31 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
 use js_sys::Date;
-use yew_agent::{Agent, AgentLink, Context, Dispatcher, Dispatched, HandlerId};
+use yew_agent::{Agent, AgentLink, Context, Bridge, Bridged, Dispatcher, Dispatched, HandlerId};
 
 use crate::articles::{ArticleBox, ArticleData, ArticleMedia, ArticleRc, ArticleWeak};
 use crate::{Endpoint, EndpointId, EndpointRequest};
 use crate::services::{
 	service,
 	EndpointSerialized,
-	RefreshTime,
+	RefreshRequest,
 	endpoint_agent::{EndpointAgent, EndpointConstructor, EndpointConstructorCollection},
 	article_actions::{ArticleActionsAgent, ServiceActions, ArticleActionsRequest},
+	garbage_collector::{GarbageCollectorAgent, GarbageCollectorRequest, GarbageCollectorResponse},
 };
 
 #[service("Dummy", DummyArticleData, u32)]
 pub struct DummyServiceAgent {
 	//link: AgentLink<Self>,
-	_endpoint_agent: Dispatcher<EndpointAgent>,
+	endpoint_agent: Dispatcher<EndpointAgent>,
 	actions_agent: Dispatcher<ArticleActionsAgent>,
+	garbage_collector: Box<dyn Bridge<GarbageCollectorAgent>>,
 }
 
 pub enum DummyServiceMsg {
 	Like(HandlerId, ArticleWeak),
 	Repost(HandlerId, ArticleWeak),
+	GarbageCollectorResponse(GarbageCollectorResponse),
 }
 
 type Msg = DummyServiceMsg;
@@ -36,27 +39,15 @@ impl Agent for DummyServiceAgent {
 
 	fn create(link: AgentLink<Self>) -> Self {
 		let articles = HashMap::from([
-			(0, Rc::new(RefCell::new(DummyArticleData {
-				id: 0,
-				creation_time: js_sys::Date::new_0(),
-				text: "Text".to_string(),
-				author_name: "Author Name".to_string(),
-				author_avatar_url: "".to_string(),
-				author_url: "".to_string(),
-				url: "".to_string(),
-				marked_as_read: false,
-				hidden: false,
-				liked: false,
-				reposted: false,
-			})))
+			(0, Rc::new(RefCell::new(DummyArticleData::new(0, js_sys::Date::new_0(), "Text".to_string()))))
 		]);
 
 		let weak_articles: Vec<ArticleWeak> = articles.values()
 			.map(|a| Rc::downgrade(a) as ArticleWeak)
 			.collect();
 
-		let mut _endpoint_agent = EndpointAgent::dispatcher();
-		_endpoint_agent.send(EndpointRequest::InitService(
+		let mut endpoint_agent = EndpointAgent::dispatcher();
+		endpoint_agent.send(EndpointRequest::InitService(
 			SERVICE_INFO.name,
 			EndpointConstructorCollection {
 				constructors: vec![
@@ -73,6 +64,9 @@ impl Agent for DummyServiceAgent {
 			},
 		));
 
+		let mut garbage_collector = GarbageCollectorAgent::bridge(link.callback(Msg::GarbageCollectorResponse));
+		garbage_collector.send(GarbageCollectorRequest::RegisterService(SERVICE_INFO.name));
+
 		let mut actions_agent = ArticleActionsAgent::dispatcher();
 		actions_agent.send(ArticleActionsRequest::Init(SERVICE_INFO.name, ServiceActions {
 			like: Some(link.callback(|(id, article)| Msg::Like(id, article))),
@@ -82,14 +76,16 @@ impl Agent for DummyServiceAgent {
 
 		Self {
 			//link,
-			_endpoint_agent,
+			endpoint_agent,
 			actions_agent,
 			articles,
+			garbage_collector,
 		}
 	}
 
 	fn update(&mut self, msg: Self::Message) {
 		match msg {
+			Msg::GarbageCollectorResponse(response) => self.handle_garbage_collector_response(response),
 			Msg::Like(_id, article) => {
 				let strong = article.upgrade().unwrap();
 
@@ -130,6 +126,24 @@ pub struct DummyArticleData {
 	reposted: bool,
 }
 
+impl DummyArticleData {
+	pub fn new(id: u32, creation_time: Date, text: String) -> Self {
+		Self {
+			id,
+			creation_time,
+			text,
+			author_name: "Author Name".to_string(),
+			author_avatar_url: "".to_string(),
+			author_url: "".to_string(),
+			url: "".to_string(),
+			marked_as_read: false,
+			hidden: false,
+			liked: false,
+			reposted: false,
+		}
+	}
+}
+
 impl ArticleData for DummyArticleData {
 	//type Id = u32;
 
@@ -221,9 +235,9 @@ impl Endpoint for DummyEndpoint {
 		&mut self.articles
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		self.endpoint_agent.send(EndpointRequest::AddArticles(
-			refresh_time,
+			refresh,
 			self.id,
 			self.articles.iter()
 				.map(|a| a.upgrade().unwrap())
@@ -235,4 +249,8 @@ impl Endpoint for DummyEndpoint {
 		storage.service == SERVICE_INFO.name &&
 			storage.endpoint_type == 0
 	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 0, serde_json::json!({})))
+	}
 }
\ No newline at end of file
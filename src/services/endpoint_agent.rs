@@ -4,16 +4,23 @@ use yew::prelude::*;
 use yew_agent::{Agent, Context as AgentContext, AgentLink, HandlerId, Dispatcher, Dispatched};
 use std::cell::RefCell;
 use serde_json::json;
-use gloo_timers::callback::Interval;
+use gloo_timers::callback::{Interval, Timeout};
+use rand::{thread_rng, Rng};
+use wasm_bindgen::{closure::Closure, JsCast};
 
-use super::{Endpoint, EndpointSerialized, RateLimit};
+use super::{Endpoint, EndpointSerialized, RateLimit, Gap, RefreshSchedule};
+use super::virtual_endpoint::{self, VirtualEndpoint};
+use super::mute::{MuteList, MuteRule};
+use super::storages::{get_mute_list, notify_storage_error, set_mute_list};
+use super::garbage_collector::forget_articles;
 use crate::error::{Result, Error, RatelimitedResult};
 use crate::articles::{ArticleRc, ArticleWeak};
 use crate::choose_endpoints::EndpointForm;
 use crate::timeline::{
 	TimelineId,
 	timeline_container::{TimelineCreationMode, TimelinePropsEndpointsClosure},
-	filters::FilterCollection
+	filters::FilterCollection,
+	agent::update_stored_endpoints,
 };
 use crate::notifications::{NotificationAgent, NotificationRequest, Notification};
 
@@ -26,14 +33,28 @@ pub struct EndpointAgent {
 	subscribers: HashSet<HandlerId>,
 	timeline_container: Option<HandlerId>,
 	notification_agent: Dispatcher<NotificationAgent>,
+	/// Next time an automatic refresh is allowed, per rate limit bucket
+	bucket_slots: HashMap<String, f64>,
+	_visibility_listener: Closure<dyn Fn()>,
+	mute_list: MuteList,
+	/// Unset when the stored list couldn't be read, so it isn't overwritten
+	mute_list_writable: bool,
+	/// Service and id of the articles muted this session
+	muted_articles: HashSet<(&'static str, String)>,
+	/// Fires when the next mute rule expires
+	mute_expiry_timeout: Option<Timeout>,
 }
 
 pub enum EndpointAgentMsg {
-	Refreshed(RefreshTime, EndpointId, (Vec<ArticleRc>, Option<RateLimit>)),
-	RefreshFail(EndpointId, Error),
+	Refreshed(RefreshRequest, EndpointId, (Vec<ArticleRc>, Option<RateLimit>)),
+	RefreshFail(RefreshRequest, EndpointId, Error),
 	UpdatedState,
 	AutoRefreshEndpoint(EndpointId),
 	ResetAutoRefresh(EndpointId),
+	RetryRefresh(EndpointId),
+	DeferredRefresh(EndpointId, RefreshTime, RefreshKind),
+	VisibilityChanged,
+	MuteRuleExpired,
 }
 
 pub enum EndpointRequest {
@@ -43,8 +64,11 @@ pub enum EndpointRequest {
 	LoadBottom(Weak<RefCell<Vec<TimelineEndpointWrapper>>>),
 	LoadTop(Weak<RefCell<Vec<TimelineEndpointWrapper>>>),
 	RefreshEndpoint(EndpointId, RefreshTime),
-	EndpointFetchResponse(RefreshTime, EndpointId, RatelimitedResult<Vec<ArticleRc>>),
-	AddArticles(RefreshTime, EndpointId, Vec<ArticleRc>),
+	FillGap(EndpointId, Gap),
+	EndpointFetchResponse(RefreshRequest, EndpointId, RatelimitedResult<Vec<ArticleRc>>),
+	AddArticles(RefreshRequest, EndpointId, Vec<ArticleRc>),
+	/// Drops articles from every endpoint, so services can evict them
+	ForgetArticles(Vec<ArticleWeak>),
 	AddEndpoint {
 		id_to_endpoint: Box<dyn FnOnce(EndpointId) -> Box<dyn Endpoint>>,
 		shared: bool,
@@ -60,17 +84,34 @@ pub enum EndpointRequest {
 	InitService(&'static str, EndpointConstructorCollection),
 	UpdateRateLimit(EndpointId, RateLimit),
 	BatchNewEndpoints(Vec<(Vec<EndpointSerialized>, TimelinePropsEndpointsClosure)>),
+	/// Serialized endpoints of each timeline, in the same order
+	SerializeTimelineEndpoints(Vec<TimelineId>, Callback<Vec<Vec<EndpointSerialized>>>),
 	RegisterTimelineContainer,
+	/// Every article loaded by the endpoints, once
+	GetArticles(Callback<Vec<ArticleWeak>>),
 	GetState,
 	StartAutoRefresh(EndpointId),
 	StopAutoRefresh(EndpointId),
+	/// Only removes endpoints no timeline uses
+	RemoveEndpoint(EndpointId),
 	SetAutoRefreshInterval(EndpointId, u32),
+	SetRetryPolicy(EndpointId, RetryPolicy),
+	SetRefreshSchedule(EndpointId, RefreshSchedule),
+	GetMuteList,
+	AddMuteRule(MuteRule),
+	RemoveMuteRule(usize),
 }
 
 pub enum EndpointResponse {
 	UpdatedState(HashMap<&'static str, EndpointConstructorCollection>, Vec<EndpointView>),
 	BatchRequestResponse(Vec<(Vec<TimelineEndpointWrapper>, TimelinePropsEndpointsClosure)>),
 	AddTimeline(TimelineCreationMode, bool),
+	/// With the number of loaded articles muted
+	UpdatedMuteList(MuteList, usize),
+	/// Articles over their service's cap, to stop referencing
+	ForgotArticles(Vec<ArticleWeak>),
+	/// Articles muted by a new rule, to remove from timelines
+	MutedArticles(Vec<ArticleWeak>),
 }
 
 type Msg = EndpointAgentMsg;
@@ -84,7 +125,24 @@ impl Agent for EndpointAgent {
 	type Output = Response;
 
 	fn create(link: AgentLink<Self>) -> Self {
-		Self {
+		let callback = link.callback(|_| Msg::VisibilityChanged);
+		let _visibility_listener = Closure::wrap(Box::new(move || callback.emit(())) as Box<dyn Fn()>);
+		gloo_utils::document()
+			.add_event_listener_with_callback("visibilitychange", _visibility_listener.as_ref().unchecked_ref())
+			.unwrap();
+
+		let (mut mute_list, mute_list_writable) = match get_mute_list() {
+			Ok(mute_list) => (mute_list, true),
+			Err(err) => {
+				notify_storage_error(err);
+				(MuteList::default(), false)
+			}
+		};
+		if mute_list.remove_expired(js_sys::Date::now()) && mute_list_writable {
+			set_mute_list(&mute_list);
+		}
+
+		let mut agent = Self {
 			link,
 			endpoint_counter: i32::MIN,
 			endpoints: HashMap::new(),
@@ -93,55 +151,106 @@ impl Agent for EndpointAgent {
 			subscribers: HashSet::new(),
 			timeline_container: None,
 			notification_agent: NotificationAgent::dispatcher(),
-		}
+			bucket_slots: HashMap::new(),
+			_visibility_listener,
+			mute_list,
+			mute_list_writable,
+			muted_articles: HashSet::new(),
+			mute_expiry_timeout: None,
+		};
+		agent.schedule_mute_expiry();
+		agent
 	}
 
 	fn update(&mut self, msg: Self::Message) {
 		match msg {
-			Msg::Refreshed(refresh_time, endpoint_id, response) => {
-				log::trace!("{} articles for {}", &response.0.len(), self.endpoints[&endpoint_id].endpoint.name());
-				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
-				info.endpoint.add_articles(response.0.iter().map(|article| Rc::downgrade(&article)).collect());
+			Msg::Refreshed(refresh, endpoint_id, response) => {
+				//The endpoint might have been removed while the refresh was ongoing
+				let info = match self.endpoints.get_mut(&endpoint_id) {
+					Some(info) => info,
+					None => return,
+				};
+				log::trace!("{} articles for {}", &response.0.len(), info.endpoint.name());
 				if let Some(ratelimit) = response.1 {
-					info.endpoint.update_ratelimit(ratelimit);
+					self.update_ratelimit(endpoint_id, ratelimit);
 				}
 
-				for (_timeline_id, timeline) in &self.timelines {
-					let timeline_strong = timeline.0.upgrade().unwrap();
-					let borrow = timeline_strong.borrow();
-					let endpoints: Vec<&TimelineEndpointWrapper> = borrow.iter().filter(|e| match refresh_time {
-						RefreshTime::Start => e.on_start,
-						RefreshTime::OnRefresh => e.on_refresh,
-					}).collect();
-
-					if let Some(endpoint_wrapper) = endpoints.iter().find(|e| e.id == endpoint_id) {
-						timeline.1.emit(response.0.iter()
-							.map(|article| Rc::downgrade(&article))
-							.filter(|article|
-								endpoint_wrapper.filters.iter().all(|instance|
-									instance.enabled && {
-										let strong = article.upgrade();
-										if let Some(a) = strong {
-											instance.filter.filter(&a.borrow()) != instance.inverted
-										}else {
-											false
-										}
-									}
-								)
-							)
-							.collect());
+				let articles: Vec<ArticleWeak> = response.0.iter().map(|article| Rc::downgrade(&article)).collect();
+				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
+				let pending = info.complete_pending_refresh(refresh);
+				let mut filling_gap = None;
+				if let Some(pagination) = info.endpoint.get_mut_pagination() {
+					pagination.start_loading(refresh.kind);
+					if refresh.kind == RefreshKind::FillGap {
+						filling_gap = pagination.filling_gap().cloned();
 					}
 				}
+				info.endpoint.add_articles(articles.clone());
+				info.article_count = info.endpoint.articles().len();
+				info.last_refresh = Some(js_sys::Date::now());
+				//Keep going until the gap is filled
+				let remaining_gap = filling_gap.and_then(|filled| info.endpoint.pagination()
+					.and_then(|p| p.gaps.iter().find(|gap| gap.older == filled.older).cloned())
+				);
+				let late_requesters = pending
+					.map(|pending| pending.late_requesters)
+					.unwrap_or_default();
+
+				if info.backoff.succeed() {
+					log::debug!("Resuming auto refresh for {}", info.endpoint.name());
+					self.start_interval(endpoint_id);
+				}
+
+				self.send_to_timelines(endpoint_id, refresh.time, &articles, &late_requesters);
+				self.update_virtual_endpoints(endpoint_id, refresh.time, &articles, &late_requesters);
+
+				if let Some(gap) = remaining_gap {
+					self.fill_gap(endpoint_id, gap);
+				}
 
 				self.link.send_message(Msg::UpdatedState);
 			}
-			Msg::RefreshFail(endpoint_id, err) => {
+			Msg::RefreshFail(refresh, endpoint_id, err) => {
 				//TODO macrofy → log_error(err)
 				log::error!("{}", &err);
+				if let Error::RatelimitedArticleFetch { ratelimit, .. } = &err {
+					self.update_ratelimit(endpoint_id, *ratelimit);
+				}
+				let error_message = err.to_string();
 				self.notification_agent.send(NotificationRequest::Notify(
 					Some(format!("Endpoint{}RefreshFail", endpoint_id)),
 					Notification::Error(err),
 				));
+
+				if let Some(info) = self.endpoints.get_mut(&endpoint_id) {
+					info.complete_pending_refresh(refresh);
+					info.last_error = Some(error_message);
+
+					let action = info.backoff.fail(info.interval_id.is_some(), &info.retry_policy, info.interval);
+					if action.is_some() {
+						if let Some(interval_id) = info.interval_id.take() {
+							Interval::cancel(interval_id);
+						}
+						if let Some(retry_timeout) = info.retry_timeout.take() {
+							Timeout::cancel(retry_timeout);
+						}
+					}
+
+					match action {
+						Some(BackoffAction::Suspend) => {
+							log::warn!("Suspending auto refresh for {} after {} failures.", info.endpoint.name(), &info.backoff.failure_streak);
+						}
+						Some(BackoffAction::Retry(delay)) => {
+							log::debug!("Retrying {} in {}ms", info.endpoint.name(), &delay);
+
+							let callback = self.link.callback(move |_| Msg::RetryRefresh(endpoint_id));
+							info.retry_timeout = Some(Timeout::new(delay, move || callback.emit(())));
+						}
+						None => {}
+					}
+
+					self.link.send_message(Msg::UpdatedState);
+				}
 			}
 			Msg::UpdatedState => {
 				for sub in &self.subscribers {
@@ -150,13 +259,71 @@ impl Agent for EndpointAgent {
 					}
 				}
 			}
-			Msg::AutoRefreshEndpoint(endpoint_id) => self.endpoints.get_mut(&endpoint_id).unwrap().endpoint.refresh(RefreshTime::OnRefresh),
+			Msg::AutoRefreshEndpoint(endpoint_id) => {
+				let info = match self.endpoints.get_mut(&endpoint_id) {
+					Some(info) => info,
+					None => return,
+				};
+
+				let scheduled = info.schedule.current_interval(info.interval);
+				if scheduled.unwrap_or(info.interval) != info.running_interval {
+					log::debug!("Rescheduling auto refresh for {}", info.endpoint.name());
+					self.start_interval(endpoint_id);
+					self.link.send_message(Msg::UpdatedState);
+				}
+
+				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
+				if scheduled.is_none() {
+					log::trace!("Skipping auto refresh of {} during quiet hours", info.endpoint.name());
+				} else if info.schedule.pause_when_hidden && gloo_utils::document().hidden() {
+					log::trace!("Skipping auto refresh of {} while hidden", info.endpoint.name());
+					info.missed_refresh = true;
+				} else {
+					self.schedule_refresh(endpoint_id, RefreshTime::OnRefresh, RefreshKind::Refresh, true, Vec::new());
+				}
+			}
+			Msg::VisibilityChanged => {
+				if !gloo_utils::document().hidden() {
+					let missed: Vec<EndpointId> = self.endpoints.iter_mut()
+						.filter_map(|(id, info)| std::mem::take(&mut info.missed_refresh).then(|| *id))
+						.collect();
+
+					for endpoint_id in missed {
+						log::debug!("Catching up on auto refresh for {}", &endpoint_id);
+						self.schedule_refresh(endpoint_id, RefreshTime::OnRefresh, RefreshKind::Refresh, true, Vec::new());
+					}
+				}
+			}
+			Msg::DeferredRefresh(endpoint_id, refresh_time, kind) => {
+				if let Some(info) = self.endpoints.get_mut(&endpoint_id) {
+					let requesters = info.deferred_refresh.take()
+						.map(|deferred| deferred.requesters)
+						.unwrap_or_default();
+					self.schedule_refresh(endpoint_id, refresh_time, kind, false, requesters);
+					self.link.send_message(Msg::UpdatedState);
+				}
+			}
+			Msg::MuteRuleExpired => {
+				if self.mute_list.remove_expired(js_sys::Date::now()) {
+					self.update_mute_list(true);
+				} else {
+					self.schedule_mute_expiry();
+				}
+			}
+			Msg::RetryRefresh(endpoint_id) => {
+				if let Some(info) = self.endpoints.get_mut(&endpoint_id) {
+					info.retry_timeout = None;
+					log::trace!("Retrying {}", &endpoint_id);
+					self.schedule_refresh(endpoint_id, RefreshTime::OnRefresh, RefreshKind::Refresh, true, Vec::new());
+				}
+			}
 			Msg::ResetAutoRefresh(endpoint_id) => {
 				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
 				if info.interval_id.is_some() {
 					let id_c = endpoint_id;
 					let callback = self.link.callback(move |_| Msg::AutoRefreshEndpoint(id_c));
-					let new_interval = Interval::new(info.interval, move || {
+					info.running_interval = info.interval_period();
+					let new_interval = Interval::new(info.running_interval, move || {
 						log::trace!("Refreshing {}", &id_c);
 						callback.emit(());
 					});
@@ -179,12 +346,7 @@ impl Agent for EndpointAgent {
 				self.timelines.insert(timeline_id, (Rc::downgrade(&endpoints), callback));
 
 				for timeline_endpoint in endpoints.borrow().iter().filter(|e| e.on_start) {
-					let info = self.endpoints.get_mut(&timeline_endpoint.id).unwrap();
-					if info.endpoint.get_mut_ratelimit().map(|r| r.can_refresh()).unwrap_or(true) {
-						info.endpoint.refresh(RefreshTime::Start);
-					}else {
-						log::warn!("Can't refresh {}", &info.endpoint.name());
-					}
+					self.schedule_refresh(timeline_endpoint.id, RefreshTime::Start, RefreshKind::Refresh, false, vec![timeline_id]);
 				}
 			},
 			Request::RemoveTimeline(id) => {
@@ -206,59 +368,41 @@ impl Agent for EndpointAgent {
 					self.link.send_message(Msg::UpdatedState);
 				}
 			}
-			Request::Refresh(endpoints_weak) => {
-				let endpoints = endpoints_weak.upgrade().unwrap();
-				for timeline_endpoint in endpoints.borrow().iter().filter(|e| e.on_refresh) {
-					let info = self.endpoints.get_mut(&timeline_endpoint.id).unwrap();
-					if info.endpoint.get_mut_ratelimit().map(|r| r.can_refresh()).unwrap_or(true) {
-						info.endpoint.refresh(RefreshTime::OnRefresh);
-						self.link.send_message(Msg::ResetAutoRefresh(*info.endpoint.id()));
-					}else {
-						log::warn!("Can't refresh {}", &info.endpoint.name());
-					}
-				}
-			}
-			Request::LoadBottom(endpoints_weak) => {
-				let endpoints = endpoints_weak.upgrade().unwrap();
-				for timeline_endpoint in endpoints.borrow().iter().filter(|e| e.on_refresh) {
-					let info = self.endpoints.get_mut(&timeline_endpoint.id).unwrap();
-					if info.endpoint.get_mut_ratelimit().map(|r| r.can_refresh()).unwrap_or(true) {
-						info.endpoint.load_bottom(RefreshTime::OnRefresh);
-						self.link.send_message(Msg::ResetAutoRefresh(*info.endpoint.id()));
-					}else {
-						log::warn!("Can't refresh {}", &info.endpoint.name());
-					}
-				}
-			}
-			Request::LoadTop(endpoints_weak) => {
-				let endpoints = endpoints_weak.upgrade().unwrap();
-				for timeline_endpoint in endpoints.borrow().iter().filter(|e| e.on_refresh) {
-					let info = self.endpoints.get_mut(&timeline_endpoint.id).unwrap();
-					if info.endpoint.get_mut_ratelimit().map(|r| r.can_refresh()).unwrap_or(true) {
-						info.endpoint.load_top(RefreshTime::OnRefresh);
-						self.link.send_message(Msg::ResetAutoRefresh(*info.endpoint.id()));
-					}else {
-						log::warn!("Can't refresh {}", &info.endpoint.name());
-					}
-				}
-			}
+			Request::Refresh(endpoints_weak) => self.refresh_timeline_endpoints(endpoints_weak, RefreshKind::Refresh),
+			Request::LoadBottom(endpoints_weak) => self.refresh_timeline_endpoints(endpoints_weak, RefreshKind::LoadBottom),
+			Request::LoadTop(endpoints_weak) => self.refresh_timeline_endpoints(endpoints_weak, RefreshKind::LoadTop),
 			Request::RefreshEndpoint(endpoint_id, refresh_time) => {
-				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
-				if info.endpoint.get_mut_ratelimit().map(|r| r.can_refresh()).unwrap_or(true) {
-					info.endpoint.refresh(refresh_time);
-					self.link.send_message(Msg::ResetAutoRefresh(*info.endpoint.id()));
-				}else {
-					log::warn!("Can't refresh {}", &info.endpoint.name());
+				//Explicit refreshes replace the pending one, since services use it to retry
+				if let Some(info) = self.endpoints.get_mut(&endpoint_id) {
+					info.pending_refreshes.retain(|p| p.refresh.time != refresh_time || !p.refresh.kind.overlaps(RefreshKind::Refresh));
+				}
+
+				if self.schedule_refresh(endpoint_id, refresh_time, RefreshKind::Refresh, false, Vec::new()) {
+					self.link.send_message(Msg::ResetAutoRefresh(endpoint_id));
 				}
 			}
-			Request::EndpointFetchResponse(refresh_time, endpoint_id, response) => {
+			Request::FillGap(endpoint_id, gap) => self.fill_gap(endpoint_id, gap),
+			Request::EndpointFetchResponse(refresh, endpoint_id, response) => {
 				match response {
-					Ok(response) => self.link.send_message(Msg::Refreshed(refresh_time, endpoint_id, response)),
-					Err(err) => self.link.send_message(Msg::RefreshFail(endpoint_id, err)),
+					Ok(response) => self.link.send_message(Msg::Refreshed(refresh, endpoint_id, response)),
+					Err(err) => self.link.send_message(Msg::RefreshFail(refresh, endpoint_id, err)),
 				};
 			}
-			Request::AddArticles(refresh_time, endpoint_id, articles) =>
-				self.link.send_message(Msg::Refreshed(refresh_time, endpoint_id, (articles, None))),
+			Request::AddArticles(refresh, endpoint_id, articles) =>
+				self.link.send_message(Msg::Refreshed(refresh, endpoint_id, (articles, None))),
+			Request::ForgetArticles(articles) => {
+				for info in self.endpoints.values_mut() {
+					forget_articles(info.endpoint.articles(), &articles);
+					info.article_count = info.endpoint.articles().len();
+				}
+
+				for sub in &self.subscribers {
+					if sub.is_respondable() {
+						self.link.respond(*sub, Response::ForgotArticles(articles.clone()));
+					}
+				}
+				self.link.send_message(Msg::UpdatedState);
+			}
 			Request::AddEndpoint { id_to_endpoint, shared } => {
 				self.endpoints.insert(self.endpoint_counter, EndpointInfo::new(id_to_endpoint(self.endpoint_counter), shared));
 				self.endpoint_counter += 1;
@@ -327,7 +471,8 @@ impl Agent for EndpointAgent {
 				self.link.send_message(Msg::UpdatedState);
 			},
 			Request::UpdateRateLimit(endpoint_id, ratelimit) => {
-				self.endpoints.get_mut(&endpoint_id).unwrap().endpoint.update_ratelimit(ratelimit)
+				self.update_ratelimit(endpoint_id, ratelimit);
+				self.link.send_message(Msg::UpdatedState);
 			},
 			Request::BatchNewEndpoints(endpoints) => {
 				let endpoints: Vec<(Vec<TimelineEndpointWrapper>, TimelinePropsEndpointsClosure)> = endpoints.into_iter().map(|(constructor, callback)| {
@@ -351,27 +496,58 @@ impl Agent for EndpointAgent {
 					self.link.respond(timeline_container, Response::BatchRequestResponse(endpoints));
 				}
 			},
+			Request::SerializeTimelineEndpoints(timeline_ids, callback) => callback.emit(timeline_ids.into_iter()
+				.map(|timeline_id| self.serialize_timeline_endpoints(timeline_id))
+				.collect()),
 			Request::RegisterTimelineContainer => self.timeline_container = Some(id),
+			Request::GetArticles(callback) => {
+				let mut seen = HashSet::new();
+				let articles = self.endpoints.values_mut()
+					.flat_map(|info| info.endpoint.articles().clone())
+					.filter(|article| seen.insert(article.as_ptr() as *const ()))
+					.collect();
+				callback.emit(articles);
+			}
 			Request::GetState => self.send_state(&id),
 			Request::StartAutoRefresh(endpoint_id) => {
 				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
-				if let None = info.interval_id {
-					let id_c = endpoint_id.clone();
-					let id_c_2 = endpoint_id.clone();
-					let callback = self.link.callback(move |_| Msg::AutoRefreshEndpoint(id_c));
-					info.interval_id = Some(Interval::new(info.interval, move || {
-						log::trace!("Refreshing {}", &id_c_2);
-						callback.emit(());
-					}));
+				if !info.is_autorefreshing() {
+					self.start_interval(endpoint_id);
 					self.link.send_message(Msg::UpdatedState);
 				}else {
 					log::trace!("Auto refresh for {} is already on.", &endpoint_id);
 				}
 			},
+			Request::RemoveEndpoint(endpoint_id) => {
+				if !self.virtual_endpoints(endpoint_id).is_empty() {
+					log::warn!("Endpoint {} is still used by a virtual endpoint.", &endpoint_id);
+				} else if self.endpoint_timelines(endpoint_id).is_empty() {
+					if let Some(mut info) = self.endpoints.remove(&endpoint_id) {
+						if let Some(interval_id) = info.interval_id.take() {
+							Interval::cancel(interval_id);
+						}
+						if let Some(retry_timeout) = info.retry_timeout.take() {
+							Timeout::cancel(retry_timeout);
+						}
+						if let Some(deferred) = info.deferred_refresh.take() {
+							Timeout::cancel(deferred.timeout);
+						}
+					}
+					self.link.send_message(Msg::UpdatedState);
+				} else {
+					log::warn!("Endpoint {} is still used by a timeline.", &endpoint_id);
+				}
+			}
 			Request::StopAutoRefresh(endpoint_id) => {
 				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
-				if info.interval_id.is_some() {
-					Interval::cancel(std::mem::replace(&mut info.interval_id, None).unwrap());
+				if info.is_autorefreshing() {
+					if let Some(interval_id) = info.interval_id.take() {
+						Interval::cancel(interval_id);
+					}
+					if let Some(retry_timeout) = info.retry_timeout.take() {
+						Timeout::cancel(retry_timeout);
+					}
+					info.backoff.stop();
 					self.link.send_message(Msg::UpdatedState);
 				}else {
 					log::warn!("Auto refresh for {} is not on.", &endpoint_id);
@@ -382,6 +558,42 @@ impl Agent for EndpointAgent {
 				info.interval = interval;
 				self.link.send_message(Msg::UpdatedState);
 			}
+			Request::SetRetryPolicy(endpoint_id, retry_policy) => {
+				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
+				info.retry_policy = retry_policy;
+				self.link.send_message(Msg::UpdatedState);
+			}
+			Request::SetRefreshSchedule(endpoint_id, schedule) => {
+				let info = self.endpoints.get_mut(&endpoint_id).unwrap();
+				update_stored_endpoints(|serialized| {
+					let matches = info.endpoint.eq_storage(serialized) && serialized.refresh_schedule != schedule;
+					if matches {
+						serialized.refresh_schedule = schedule.clone();
+					}
+					matches
+				});
+				info.schedule = schedule;
+				if info.interval_id.is_some() {
+					self.start_interval(endpoint_id);
+				}
+				self.link.send_message(Msg::UpdatedState);
+			}
+			Request::GetMuteList => {
+				if self.mute_list.remove_expired(js_sys::Date::now()) {
+					self.update_mute_list(true);
+				}
+				self.link.respond(id, Response::UpdatedMuteList(self.mute_list.clone(), self.muted_articles.len()));
+			}
+			Request::AddMuteRule(rule) => {
+				self.mute_list.rules.push(rule);
+				self.update_mute_list(false);
+			}
+			Request::RemoveMuteRule(index) => {
+				if index < self.mute_list.rules.len() {
+					self.mute_list.rules.remove(index);
+					self.update_mute_list(true);
+				}
+			}
 		}
 	}
 
@@ -395,6 +607,48 @@ impl Agent for EndpointAgent {
 }
 
 impl EndpointAgent {
+	fn serialize_timeline_endpoints(&self, timeline_id: TimelineId) -> Vec<EndpointSerialized> {
+		let endpoints = match self.timelines.get(&timeline_id).and_then(|(endpoints, _)| endpoints.upgrade()) {
+			Some(endpoints) => endpoints,
+			None => return Vec::new(),
+		};
+		let endpoints = endpoints.borrow();
+		endpoints.iter()
+			.filter_map(|wrapper| {
+				let mut serialized = self.serialize_endpoint(wrapper.id)?;
+				serialized.filters = wrapper.filters.clone();
+				serialized.on_start = wrapper.on_start;
+				serialized.on_refresh = wrapper.on_refresh;
+				Some(serialized)
+			})
+			.collect()
+	}
+
+	/// Inverse of `find_endpoint_or_create`, virtual endpoints embedding their sources
+	fn serialize_endpoint(&self, id: EndpointId) -> Option<EndpointSerialized> {
+		let info = self.endpoints.get(&id)?;
+		let mut serialized = match info.endpoint.composition() {
+			Some(composition) => EndpointSerialized::new(virtual_endpoint::SERVICE_INFO.name, 0, json!({
+				"name": info.endpoint.name(),
+				"mode": composition.mode,
+				"filters": composition.filters,
+				"sources": composition.sources.iter()
+					.filter_map(|source| self.serialize_endpoint(*source))
+					.collect::<Vec<EndpointSerialized>>(),
+			})),
+			None => match info.endpoint.serialize() {
+				Some(serialized) => serialized,
+				None => {
+					log::warn!("Couldn't serialize endpoint \"{}\"", info.endpoint.name());
+					return None;
+				}
+			}
+		};
+		serialized.auto_refresh = info.interval_id.is_some();
+		serialized.refresh_schedule = info.schedule.clone();
+		Some(serialized)
+	}
+
 	fn endpoint_from_constructor(&self, storage: &EndpointSerialized) -> Option<EndpointId> {
 		self.endpoints.iter().find_map(|(id, endpoint)| match endpoint.endpoint.eq_storage(storage) {
 			true => Some(id.clone()),
@@ -403,8 +657,23 @@ impl EndpointAgent {
 	}
 
 	fn find_endpoint_or_create(&mut self, serialized: &EndpointSerialized, on_start: bool, on_refresh: bool) -> Result<TimelineEndpointWrapper> {
+		let resolved;
+		let serialized = if serialized.service == virtual_endpoint::SERVICE_INFO.name {
+			resolved = self.resolve_virtual_sources(serialized)?;
+			&resolved
+		} else {
+			serialized
+		};
+
 		match self.endpoint_from_constructor(serialized) {
 			Some(id) => Ok(id),
+			None if serialized.service == virtual_endpoint::SERVICE_INFO.name => {
+				let id = self.endpoint_counter;
+				self.endpoints.insert(id, EndpointInfo::new(Box::new(VirtualEndpoint::from_json(id, serialized.params.clone())), true));
+				self.endpoint_counter += 1;
+
+				Ok(id)
+			}
 			None => {
 				match self.services.get(&serialized.service.as_str()) {
 					None => Err(format!("{} isn't registered as a service. Available: {:?}", &serialized.service, self.services.keys()).into()),
@@ -422,6 +691,12 @@ impl EndpointAgent {
 			}
 		}
 			.map(|id| {
+				if !serialized.refresh_schedule.is_default() {
+					if let Some(info) = self.endpoints.get_mut(&id) {
+						info.schedule = serialized.refresh_schedule.clone();
+					}
+				}
+
 				if serialized.auto_refresh {
 					self.link.send_input(Request::StartAutoRefresh(id))
 				}
@@ -430,14 +705,375 @@ impl EndpointAgent {
 			})
 	}
 
+	/// Finds or creates the serialized sources of a virtual endpoint, replacing them with their ids
+	fn resolve_virtual_sources(&mut self, serialized: &EndpointSerialized) -> Result<EndpointSerialized> {
+		let sources: Vec<EndpointSerialized> = serde_json::from_value(serialized.params["sources"].clone())?;
+		let source_ids = sources.iter()
+			.map(|source| self.find_endpoint_or_create(source, false, false).map(|wrapper| wrapper.id))
+			.collect::<Result<Vec<EndpointId>>>()?;
+
+		let mut params = serialized.params.clone();
+		if let Some(params) = params.as_object_mut() {
+			params.remove("sources");
+			params.insert("source_ids".to_owned(), json!(source_ids));
+		}
+
+		Ok(EndpointSerialized {
+			service: serialized.service.clone(),
+			endpoint_type: serialized.endpoint_type,
+			params,
+			filters: serialized.filters.clone(),
+			auto_refresh: serialized.auto_refresh,
+			on_start: serialized.on_start,
+			on_refresh: serialized.on_refresh,
+			refresh_schedule: serialized.refresh_schedule.clone(),
+		})
+	}
+
+	fn refresh_timeline_endpoints(&mut self, endpoints_weak: Weak<RefCell<Vec<TimelineEndpointWrapper>>>, kind: RefreshKind) {
+		let endpoints = endpoints_weak.upgrade().unwrap();
+		let endpoint_ids: Vec<EndpointId> = endpoints.borrow().iter()
+			.filter(|e| e.on_refresh)
+			.map(|e| e.id)
+			.collect();
+		let requester = self.timelines.iter()
+			.find(|(_, (timeline_endpoints, _))| timeline_endpoints.ptr_eq(&endpoints_weak))
+			.map(|(timeline_id, _)| *timeline_id);
+
+		for endpoint_id in endpoint_ids {
+			if self.schedule_refresh(endpoint_id, RefreshTime::OnRefresh, kind, false, requester.into_iter().collect()) {
+				self.link.send_message(Msg::ResetAutoRefresh(endpoint_id));
+			}
+		}
+	}
+
+	fn fill_gap(&mut self, endpoint_id: EndpointId, gap: Gap) {
+		if let Some(pagination) = self.endpoints.get_mut(&endpoint_id).and_then(|info| info.endpoint.get_mut_pagination()) {
+			pagination.start_filling_gap(gap);
+		}
+
+		self.schedule_refresh(endpoint_id, RefreshTime::OnRefresh, RefreshKind::FillGap, false, Vec::new());
+	}
+
+	/// Refreshes the endpoint right away if its rate limit allows it, otherwise defers it until the reset.
+	/// With `spread`, refreshes sharing a rate limit bucket are also spaced out.
+	/// If an overlapping refresh is already in flight or deferred, the requesters get attached to it instead.
+	/// Returns whether the endpoint was refreshed right away.
+	fn schedule_refresh(&mut self, endpoint_id: EndpointId, refresh_time: RefreshTime, kind: RefreshKind, spread: bool, mut requesters: Vec<TimelineId>) -> bool {
+		let now = js_sys::Date::now();
+		let info = match self.endpoints.get_mut(&endpoint_id) {
+			Some(info) => info,
+			None => {
+				log::warn!("Endpoint {} doesn't exist anymore.", &endpoint_id);
+				return false;
+			}
+		};
+
+		if let Some(composition) = info.endpoint.composition() {
+			let sources = composition.sources.clone();
+			let mut refreshed = false;
+			for source in sources {
+				refreshed |= self.schedule_refresh(source, refresh_time, kind, spread, requesters.clone());
+			}
+			return refreshed;
+		}
+
+		if kind == RefreshKind::LoadBottom && info.endpoint.pagination().map(|p| p.exhausted).unwrap_or_default() {
+			log::debug!("{} has no more articles to load.", info.endpoint.name());
+			return false;
+		}
+
+		let timeout = info.endpoint.refresh_timeout();
+		if let Some(pending) = info.pending_refreshes.iter_mut().find(|p| p.refresh.kind.overlaps(kind) && !p.is_stale(now, timeout)) {
+			log::trace!("Coalescing {:?} of {} with pending {:?}", kind, info.endpoint.name(), pending.refresh.kind);
+			add_requesters(&mut pending.late_requesters, requesters);
+			return false;
+		}
+
+		if info.deferred_refresh.is_some() {
+			//Explicit refreshes go through while the rate limit allows it
+			let can_refresh = !spread && info.endpoint.get_mut_ratelimit()
+				.map(|ratelimit| ratelimit.can_refresh())
+				.unwrap_or(true);
+			let deferred = info.deferred_refresh.as_mut().unwrap();
+			if !can_refresh {
+				log::trace!("{} already has a deferred refresh.", info.endpoint.name());
+				add_requesters(&mut deferred.requesters, requesters);
+				return false;
+			}
+
+			if deferred.refresh.time == refresh_time && deferred.refresh.kind.overlaps(kind) {
+				log::trace!("Replacing the deferred {:?} of {} with {:?}", deferred.refresh.kind, info.endpoint.name(), kind);
+				if let Some(deferred) = info.deferred_refresh.take() {
+					add_requesters(&mut requesters, deferred.requesters);
+				}
+			}
+		}
+
+		let bucket = info.endpoint.ratelimit_bucket();
+		let wait = match info.endpoint.get_mut_ratelimit() {
+			Some(ratelimit) if !ratelimit.can_refresh() => Some(ratelimit.reset_time() - now + RATELIMIT_RESET_MARGIN),
+			_ => None,
+		};
+		let spacing = bucket_spacing(info.endpoint.ratelimit(), now);
+
+		let wait = match (wait, spread, &bucket) {
+			(Some(wait), _, _) => {
+				log::warn!("{} is rate limited, deferring refresh by {}s", info.endpoint.name(), (wait / 1000.0).ceil());
+				Some(wait)
+			}
+			(None, true, Some(bucket)) => {
+				let next_slot = self.bucket_slots.entry(bucket.clone()).or_insert(now);
+				let slot = next_slot.max(now);
+				*next_slot = slot + spacing;
+
+				if slot > now {
+					Some(slot - now)
+				} else {
+					None
+				}
+			}
+			_ => None,
+		};
+
+		match wait {
+			Some(wait) => {
+				let callback = self.link.callback(move |_| Msg::DeferredRefresh(endpoint_id, refresh_time, kind));
+				info.deferred_refresh = Some(DeferredRefresh {
+					timeout: Timeout::new(wait as u32, move || callback.emit(())),
+					refresh: RefreshRequest { time: refresh_time, kind },
+					requesters,
+				});
+				self.link.send_message(Msg::UpdatedState);
+				false
+			}
+			None => {
+				info.pending_refreshes.retain(|p| !p.is_stale(now, timeout));
+				let refresh = RefreshRequest { time: refresh_time, kind };
+				info.pending_refreshes.push(PendingRefresh {
+					refresh,
+					started: now,
+					late_requesters: requesters,
+				});
+				self.link.send_message(Msg::UpdatedState);
+
+				match kind {
+					RefreshKind::Refresh => info.endpoint.refresh(refresh),
+					RefreshKind::LoadTop => info.endpoint.load_top(refresh),
+					RefreshKind::LoadBottom => info.endpoint.load_bottom(refresh),
+					RefreshKind::FillGap => info.endpoint.fill_gap(refresh),
+				}
+				true
+			}
+		}
+	}
+
+	/// Updates the rate limit of every endpoint sharing the same bucket
+	fn update_ratelimit(&mut self, endpoint_id: EndpointId, ratelimit: RateLimit) {
+		let bucket = match self.endpoints.get(&endpoint_id) {
+			Some(info) => info.endpoint.ratelimit_bucket(),
+			None => return,
+		};
+
+		for (id, info) in self.endpoints.iter_mut() {
+			let same_bucket = bucket.is_some() && info.endpoint.ratelimit_bucket() == bucket;
+			if *id == endpoint_id || same_bucket {
+				info.endpoint.update_ratelimit(ratelimit);
+			}
+		}
+	}
+
+	/// (Re)starts the regular auto refresh interval, dropping any pending retry
+	fn start_interval(&mut self, endpoint_id: EndpointId) {
+		let info = self.endpoints.get_mut(&endpoint_id).unwrap();
+		let callback = self.link.callback(move |_| Msg::AutoRefreshEndpoint(endpoint_id));
+		info.running_interval = info.interval_period();
+		let new_interval = Interval::new(info.running_interval, move || {
+			log::trace!("Refreshing {}", &endpoint_id);
+			callback.emit(());
+		});
+
+		if let Some(old_interval) = info.interval_id.replace(new_interval) {
+			Interval::cancel(old_interval);
+		}
+		if let Some(retry_timeout) = info.retry_timeout.take() {
+			Timeout::cancel(retry_timeout);
+		}
+		info.backoff.stop();
+	}
+
+	/// Sends the articles to the timelines using the endpoint at this refresh time, minus the muted ones
+	fn send_to_timelines(&mut self, endpoint_id: EndpointId, refresh_time: RefreshTime, articles: &[ArticleWeak], late_requesters: &[TimelineId]) {
+		let now = js_sys::Date::now();
+		let muted_count = self.muted_articles.len();
+		let mut articles = articles.to_vec();
+		articles.retain(|article| match article.upgrade() {
+			Some(strong) => {
+				let borrow = strong.borrow();
+				let muted = self.mute_list.is_muted(&borrow, now);
+				if muted {
+					self.muted_articles.insert((borrow.service(), borrow.id()));
+				}
+				!muted
+			}
+			None => true,
+		});
+		if self.muted_articles.len() != muted_count {
+			self.send_mute_list();
+		}
+
+		for (timeline_id, timeline) in &self.timelines {
+			let timeline_strong = timeline.0.upgrade().unwrap();
+			let borrow = timeline_strong.borrow();
+			let is_late_requester = late_requesters.contains(timeline_id);
+			let endpoint_wrapper = borrow.iter().find(|e| e.id == endpoint_id && (is_late_requester || match refresh_time {
+				RefreshTime::Start => e.on_start,
+				RefreshTime::OnRefresh => e.on_refresh,
+			}));
+
+			if let Some(endpoint_wrapper) = endpoint_wrapper {
+				timeline.1.emit(articles.iter()
+					.cloned()
+					.filter(|article| match article.upgrade() {
+						Some(strong) => endpoint_wrapper.filters.rejecting_index(&strong.borrow()).is_none(),
+						None => false,
+					})
+					.collect());
+			}
+		}
+	}
+
+	/// Recombines the virtual endpoints built on this one, passing on the newly refreshed articles they kept
+	fn update_virtual_endpoints(&mut self, source_id: EndpointId, refresh_time: RefreshTime, articles: &[ArticleWeak], late_requesters: &[TimelineId]) {
+		for virtual_id in self.virtual_endpoints(source_id) {
+			let sources = self.endpoints[&virtual_id].endpoint.composition().unwrap().sources.clone();
+			let source_articles = sources.iter()
+				.map(|id| self.endpoints.get_mut(id).map(|info| info.endpoint.articles().clone()).unwrap_or_default())
+				.collect();
+
+			let info = self.endpoints.get_mut(&virtual_id).unwrap();
+			let combined = info.endpoint.composition().unwrap().combine(source_articles);
+			let kept: Vec<ArticleWeak> = articles.iter()
+				.filter(|article| combined.iter().any(|a| Weak::ptr_eq(a, article)))
+				.cloned()
+				.collect();
+
+			*info.endpoint.articles() = combined;
+			info.article_count = info.endpoint.articles().len();
+			info.last_refresh = Some(js_sys::Date::now());
+
+			self.send_to_timelines(virtual_id, refresh_time, &kept, late_requesters);
+			self.update_virtual_endpoints(virtual_id, refresh_time, &kept, late_requesters);
+		}
+	}
+
+	/// Saves the rules and applies them to the articles already in timelines,
+	/// sending articles back if rules were removed
+	fn update_mute_list(&mut self, removed_rules: bool) {
+		let now = js_sys::Date::now();
+		self.mute_list.remove_expired(now);
+		if self.mute_list_writable {
+			set_mute_list(&self.mute_list);
+		}
+
+		let mut muted = Vec::new();
+		self.muted_articles.clear();
+		for info in self.endpoints.values_mut() {
+			for article in info.endpoint.articles().iter() {
+				if let Some(strong) = article.upgrade() {
+					let borrow = strong.borrow();
+					if self.mute_list.is_muted(&borrow, now) {
+						self.muted_articles.insert((borrow.service(), borrow.id()));
+						muted.push(article.clone());
+					}
+				}
+			}
+		}
+
+		if !muted.is_empty() {
+			for sub in &self.subscribers {
+				if sub.is_respondable() {
+					self.link.respond(*sub, Response::MutedArticles(muted.clone()));
+				}
+			}
+		}
+
+		if removed_rules {
+			let endpoints: Vec<(EndpointId, Vec<ArticleWeak>)> = self.endpoints.iter_mut()
+				.map(|(id, info)| (*id, info.endpoint.articles().clone()))
+				.collect();
+			for (endpoint_id, articles) in endpoints {
+				let timelines = self.endpoint_timelines(endpoint_id);
+				self.send_to_timelines(endpoint_id, RefreshTime::OnRefresh, &articles, &timelines);
+			}
+		}
+
+		self.schedule_mute_expiry();
+		self.send_mute_list();
+	}
+
+	/// Sets a timer for the earliest expiring rule, replacing the previous one
+	fn schedule_mute_expiry(&mut self) {
+		let next_expiry = self.mute_list.rules.iter()
+			.filter_map(|rule| rule.expiry)
+			.fold(None, |next: Option<f64>, expiry| Some(next.map_or(expiry, |next| next.min(expiry))));
+
+		self.mute_expiry_timeout = next_expiry.map(|expiry| {
+			//setTimeout fires right away past i32::MAX ms, so long delays are rescheduled on expiry
+			let delay = (expiry - js_sys::Date::now()).clamp(0.0, i32::MAX as f64) as u32;
+			let callback = self.link.callback(|_| Msg::MuteRuleExpired);
+			Timeout::new(delay, move || callback.emit(()))
+		});
+	}
+
+	fn send_mute_list(&self) {
+		for sub in &self.subscribers {
+			if sub.is_respondable() {
+				self.link.respond(*sub, Response::UpdatedMuteList(self.mute_list.clone(), self.muted_articles.len()));
+			}
+		}
+	}
+
+	fn endpoint_timelines(&self, endpoint_id: EndpointId) -> Vec<TimelineId> {
+		self.timelines.iter()
+			.filter(|(_, (endpoints, _))| endpoints.upgrade()
+				.map(|endpoints| endpoints.borrow().iter().any(|e| e.id == endpoint_id))
+				.unwrap_or_default()
+			)
+			.map(|(timeline_id, _)| *timeline_id)
+			.collect()
+	}
+
+	/// Virtual endpoints composed from this one
+	fn virtual_endpoints(&self, source_id: EndpointId) -> Vec<EndpointId> {
+		self.endpoints.iter()
+			.filter(|(_, info)| info.endpoint.composition().map(|c| c.sources.contains(&source_id)).unwrap_or_default())
+			.map(|(id, _)| *id)
+			.collect()
+	}
+
 	fn send_state(&self, id: &HandlerId) {
 		self.link.respond(*id, Response::UpdatedState(self.services.clone(), self.endpoints.iter().map(|(id, e)| EndpointView {
 			id: id.clone(),
 			name: e.endpoint.name(),
 			ratelimit: e.endpoint.ratelimit().cloned(),
-			is_autorefreshing: e.interval_id.is_some(),
+			is_autorefreshing: e.is_autorefreshing(),
 			autorefresh_interval: e.interval,
+			refresh_schedule: e.schedule.clone(),
 			shared: e.shared,
+			failure_streak: e.backoff.failure_streak,
+			auto_refresh_suspended: e.backoff.suspended,
+			refresh_deferred: e.deferred_refresh.is_some(),
+			pending_since: e.pending_since(),
+			refresh_timeout: e.endpoint.refresh_timeout(),
+			paginated: e.endpoint.pagination().is_some(),
+			exhausted: e.endpoint.pagination().map(|p| p.exhausted).unwrap_or_default(),
+			gaps: e.endpoint.pagination().map(|p| p.gaps.clone()).unwrap_or_default(),
+			last_refresh: e.last_refresh,
+			last_error: e.last_error.clone(),
+			article_count: e.article_count,
+			timelines: self.endpoint_timelines(*id),
+			virtual_endpoints: self.virtual_endpoints(*id),
 		}).collect()));
 	}
 }
@@ -470,12 +1106,62 @@ impl TimelineEndpointWrapper {
 }
 
 //TODO Replace with per_timeline boolean?
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum RefreshTime {
 	Start,
 	OnRefresh,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RefreshKind {
+	Refresh,
+	LoadTop,
+	LoadBottom,
+	FillGap,
+}
+
+impl RefreshKind {
+	/// Whether both would fetch the same range of articles
+	pub fn overlaps(&self, other: RefreshKind) -> bool {
+		match (self, other) {
+			(RefreshKind::LoadBottom, RefreshKind::LoadBottom) | (RefreshKind::FillGap, RefreshKind::FillGap) => true,
+			(RefreshKind::LoadBottom | RefreshKind::FillGap, _) | (_, RefreshKind::LoadBottom | RefreshKind::FillGap) => false,
+			_ => true,
+		}
+	}
+}
+
+/// A refresh asked of an endpoint, which its service hands back with the response
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RefreshRequest {
+	pub time: RefreshTime,
+	pub kind: RefreshKind,
+}
+
+/// Minimum time between two automatic refreshes sharing a rate limit bucket
+const MIN_BUCKET_SPACING: f64 = 5_000.0;
+/// Extra time waited after a rate limit reset, in case of clock drift
+const RATELIMIT_RESET_MARGIN: f64 = 1_000.0;
+
+fn add_requesters(requesters: &mut Vec<TimelineId>, new_requesters: Vec<TimelineId>) {
+	for requester in new_requesters {
+		if !requesters.contains(&requester) {
+			requesters.push(requester);
+		}
+	}
+}
+
+/// Spreads the remaining requests evenly over what's left of the rate limit window
+fn bucket_spacing(ratelimit: Option<&RateLimit>, now: f64) -> f64 {
+	match ratelimit {
+		Some(ratelimit) if ratelimit.remaining > 0 => {
+			let window_left = ratelimit.reset_time() - now;
+			(window_left / ratelimit.remaining as f64).max(MIN_BUCKET_SPACING)
+		}
+		_ => MIN_BUCKET_SPACING,
+	}
+}
+
 #[derive(Clone)]
 pub struct EndpointConstructor {
 	pub name: &'static str,
@@ -508,7 +1194,30 @@ pub struct EndpointView {
 	pub ratelimit: Option<RateLimit>,
 	pub is_autorefreshing: bool,
 	pub autorefresh_interval: u32,
+	pub refresh_schedule: RefreshSchedule,
 	pub shared: bool,
+	/// Number of consecutive failed refreshes
+	pub failure_streak: u32,
+	pub auto_refresh_suspended: bool,
+	/// Whether a refresh is waiting on the rate limit
+	pub refresh_deferred: bool,
+	/// Start of the oldest refresh still waiting on the service
+	pub pending_since: Option<f64>,
+	/// Time after which a pending refresh is given up on
+	pub refresh_timeout: u32,
+	/// Whether the endpoint tracks a `Pagination`
+	pub paginated: bool,
+	/// Whether there are no more articles to load at the bottom
+	pub exhausted: bool,
+	pub gaps: Vec<Gap>,
+	/// Timestamp of the last successful refresh
+	pub last_refresh: Option<f64>,
+	pub last_error: Option<String>,
+	pub article_count: usize,
+	/// Timelines using this endpoint
+	pub timelines: Vec<TimelineId>,
+	/// Virtual endpoints using this endpoint as a source
+	pub virtual_endpoints: Vec<EndpointId>,
 }
 
 /// Additional data common to all endpoints
@@ -517,6 +1226,19 @@ pub struct EndpointInfo {
 	shared: bool,
 	interval_id: Option<Interval>,
 	interval: u32,
+	schedule: RefreshSchedule,
+	/// Period of the current auto refresh interval
+	running_interval: u32,
+	/// Whether an auto refresh was skipped while the tab was hidden
+	missed_refresh: bool,
+	retry_policy: RetryPolicy,
+	backoff: Backoff,
+	retry_timeout: Option<Timeout>,
+	deferred_refresh: Option<DeferredRefresh>,
+	pending_refreshes: Vec<PendingRefresh>,
+	last_refresh: Option<f64>,
+	last_error: Option<String>,
+	article_count: usize,
 }
 
 impl EndpointInfo {
@@ -524,10 +1246,164 @@ impl EndpointInfo {
 		Self {
 			interval: endpoint.default_interval(),
 			interval_id: None,
+			schedule: RefreshSchedule::default(),
+			running_interval: 0,
+			missed_refresh: false,
+			retry_policy: endpoint.default_retry_policy(),
+			backoff: Backoff::default(),
+			retry_timeout: None,
+			deferred_refresh: None,
+			pending_refreshes: Vec::new(),
+			last_refresh: None,
+			last_error: None,
+			article_count: 0,
 			endpoint,
 			shared,
 		}
 	}
+
+	/// Whether auto refresh is on, even if currently backing off or suspended
+	fn is_autorefreshing(&self) -> bool {
+		self.interval_id.is_some() || self.retry_timeout.is_some() || self.backoff.is_active()
+	}
+
+	/// Period of the auto refresh interval, which keeps ticking during quiet hours to notice their end
+	fn interval_period(&self) -> u32 {
+		self.schedule.current_interval(self.interval).unwrap_or(self.interval)
+	}
+
+	fn pending_since(&self) -> Option<f64> {
+		let now = js_sys::Date::now();
+		let timeout = self.endpoint.refresh_timeout();
+		self.pending_refreshes.iter()
+			.filter(|p| !p.is_stale(now, timeout))
+			.map(|p| p.started)
+			.reduce(f64::min)
+	}
+
+	/// Removes the oldest in-flight refresh this response is for
+	fn complete_pending_refresh(&mut self, refresh: RefreshRequest) -> Option<PendingRefresh> {
+		self.pending_refreshes.iter()
+			.position(|p| p.refresh == refresh)
+			.map(|index| self.pending_refreshes.remove(index))
+	}
+}
+
+/// A refresh waiting on the rate limit, or for its turn in the rate limit bucket
+struct DeferredRefresh {
+	timeout: Timeout,
+	refresh: RefreshRequest,
+	/// Timelines that asked for it, to send them the articles
+	requesters: Vec<TimelineId>,
+}
+
+/// A refresh sent to the service, waiting for its response
+struct PendingRefresh {
+	refresh: RefreshRequest,
+	started: f64,
+	/// Timelines that asked for this refresh or an overlapping one while it was in flight or deferred
+	late_requesters: Vec<TimelineId>,
+}
+
+impl PendingRefresh {
+	/// `timeout` after which the service is assumed to never respond
+	fn is_stale(&self, now: f64, timeout: u32) -> bool {
+		now - self.started > timeout as f64
+	}
+}
+
+/// How auto refresh backs off after failed refreshes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RetryPolicy {
+	/// Consecutive failures before auto refresh gets suspended
+	pub max_attempts: u32,
+	pub multiplier: f64,
+	/// Fraction of the delay randomly added or removed
+	pub jitter: f64,
+	/// Maximum delay in ms
+	pub cap: u32,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self {
+			max_attempts: 5,
+			multiplier: 2.0,
+			jitter: 0.1,
+			cap: 900_000,
+		}
+	}
+}
+
+impl RetryPolicy {
+	/// Delay before the next retry, without jitter
+	pub fn backoff(&self, interval: u32, failure_streak: u32) -> u32 {
+		let delay = interval as f64 * self.multiplier.powi(failure_streak as i32);
+		delay.min(self.cap as f64) as u32
+	}
+
+	pub fn delay(&self, interval: u32, failure_streak: u32) -> u32 {
+		let backoff = self.backoff(interval, failure_streak) as f64;
+		let jitter = if self.jitter > 0.0 {
+			thread_rng().gen_range(-self.jitter..=self.jitter)
+		} else {
+			0.0
+		};
+
+		(backoff * (1.0 + jitter)).max(0.0) as u32
+	}
+}
+
+/// Failures of an endpoint, followed across retries until it succeeds or gets suspended
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Backoff {
+	pub failure_streak: u32,
+	/// Auto refresh was replaced by retries
+	pub retrying: bool,
+	pub suspended: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BackoffAction {
+	/// Retries after this delay in ms
+	Retry(u32),
+	Suspend,
+}
+
+impl Backoff {
+	/// Whether auto refresh is on while its interval is stopped
+	pub fn is_active(&self) -> bool {
+		self.retrying || self.suspended
+	}
+
+	/// `auto_refreshing` being whether the interval was running, manual refreshes don't back off
+	pub fn fail(&mut self, auto_refreshing: bool, policy: &RetryPolicy, interval: u32) -> Option<BackoffAction> {
+		self.failure_streak += 1;
+		if !auto_refreshing && !self.is_active() {
+			return None;
+		}
+
+		if self.failure_streak >= policy.max_attempts {
+			self.retrying = false;
+			self.suspended = true;
+			Some(BackoffAction::Suspend)
+		} else {
+			self.retrying = true;
+			Some(BackoffAction::Retry(policy.delay(interval, self.failure_streak)))
+		}
+	}
+
+	/// Returns whether auto refresh should resume
+	pub fn succeed(&mut self) -> bool {
+		let resume = self.is_active();
+		*self = Self::default();
+		resume
+	}
+
+	pub fn stop(&mut self) {
+		self.retrying = false;
+		self.suspended = false;
+	}
 }
 
 pub enum TimelineCreationRequest {
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use yew::Callback;
+use yew_agent::{Agent, AgentLink, Bridge, Bridged, HandlerId, Public};
+
+use crate::articles::RawJson;
+use crate::error::{Error, RatelimitedResult};
+use crate::services::RateLimit;
+use crate::services::twitter::{fetch_parsed_tweets, article::ParsedTweet};
+use crate::services::pixiv::{fetch_parsed_post, fetch_parsed_posts, endpoints::{FollowAPIIllust, FullPostAPI}};
+use crate::services::youtube::{fetch_parsed_videos, PlaylistItem};
+
+/// How the worker should parse the response
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum FetchKind {
+	Tweets,
+	PixivPosts,
+	PixivPost,
+	YouTubeVideos,
+}
+
+/// Article data for the services to build their articles with
+#[derive(Serialize, Deserialize)]
+pub enum ParsedArticles {
+	Tweets(Vec<ParsedTweet>),
+	PixivPosts(Vec<(FollowAPIIllust, RawJson)>),
+	PixivPost(FullPostAPI, RawJson),
+	YouTubeVideos(Vec<(PlaylistItem, RawJson)>),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FetchWorkerRequest {
+	pub request_id: u32,
+	pub url: String,
+	pub kind: FetchKind,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FetchWorkerError {
+	pub unauthorized: bool,
+	pub message: String,
+}
+
+impl From<Error> for FetchWorkerError {
+	fn from(err: Error) -> Self {
+		Self {
+			unauthorized: matches!(err, Error::UnauthorizedFetch { .. }),
+			message: err.to_string(),
+		}
+	}
+}
+
+impl FetchWorkerError {
+	pub fn into_error(self) -> Error {
+		if self.unauthorized {
+			Error::UnauthorizedFetch {
+				message: None,
+				error: self.message.into(),
+				article_ids: vec![],
+			}
+		} else {
+			Error::from(self.message)
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FetchWorkerResponse {
+	pub request_id: u32,
+	pub result: Result<(ParsedArticles, Option<RateLimit>), FetchWorkerError>,
+}
+
+/// Fetches and parses articles off the UI thread, the services then build them
+pub struct FetchWorker {
+	link: AgentLink<Self>,
+}
+
+pub enum FetchWorkerMsg {
+	Fetched(HandlerId, u32, Result<(ParsedArticles, Option<RateLimit>), FetchWorkerError>),
+}
+
+type Msg = FetchWorkerMsg;
+
+impl Agent for FetchWorker {
+	type Reach = Public<Self>;
+	type Message = Msg;
+	type Input = FetchWorkerRequest;
+	type Output = FetchWorkerResponse;
+
+	fn create(link: AgentLink<Self>) -> Self {
+		Self {
+			link,
+		}
+	}
+
+	fn update(&mut self, msg: Self::Message) {
+		match msg {
+			Msg::Fetched(id, request_id, result) => {
+				if id.is_respondable() {
+					self.link.respond(id, FetchWorkerResponse { request_id, result });
+				}
+			}
+		}
+	}
+
+	fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+		let FetchWorkerRequest { request_id, url, kind } = msg;
+		self.link.send_future(async move {
+			Msg::Fetched(id, request_id, fetch_parsed(&url, kind).await.map_err(FetchWorkerError::from))
+		});
+	}
+
+	fn name_of_resource() -> &'static str {
+		"fetch_worker.js"
+	}
+}
+
+async fn fetch_parsed(url: &str, kind: FetchKind) -> RatelimitedResult<ParsedArticles> {
+	Ok(match kind {
+		FetchKind::Tweets => {
+			let (tweets, ratelimit) = fetch_parsed_tweets(url).await?;
+			(ParsedArticles::Tweets(tweets), ratelimit)
+		}
+		FetchKind::PixivPosts => (ParsedArticles::PixivPosts(fetch_parsed_posts(url).await?), None),
+		FetchKind::PixivPost => {
+			let (post, raw_json) = fetch_parsed_post(url).await?;
+			(ParsedArticles::PixivPost(post, raw_json), None)
+		}
+		FetchKind::YouTubeVideos => (ParsedArticles::YouTubeVideos(fetch_parsed_videos(url).await?), None),
+	})
+}
+
+/// Sends fetches to the worker, spawned on first use, and remembers what each was for
+pub struct WorkerFetcher<C> {
+	worker: Option<Box<dyn Bridge<FetchWorker>>>,
+	callback: Callback<FetchWorkerResponse>,
+	pending: HashMap<u32, C>,
+	next_id: u32,
+}
+
+impl<C> WorkerFetcher<C> {
+	pub fn new(callback: Callback<FetchWorkerResponse>) -> Self {
+		Self {
+			worker: None,
+			callback,
+			pending: HashMap::new(),
+			next_id: 0,
+		}
+	}
+
+	pub fn fetch(&mut self, url: String, kind: FetchKind, context: C) {
+		let request_id = self.next_id;
+		self.next_id = self.next_id.wrapping_add(1);
+		self.pending.insert(request_id, context);
+
+		let callback = &self.callback;
+		self.worker.get_or_insert_with(|| FetchWorker::bridge(callback.clone()))
+			.send(FetchWorkerRequest { request_id, url, kind });
+	}
+
+	pub fn take(&mut self, response: FetchWorkerResponse) -> Option<(C, RatelimitedResult<ParsedArticles>)> {
+		self.pending.remove(&response.request_id)
+			.map(|context| (context, response.result.map_err(FetchWorkerError::into_error)))
+	}
+}
+
+/// For a response the worker parsed as something else than what was asked
+pub fn unexpected_articles() -> Error {
+	Error::from("The fetch worker parsed unexpected articles")
+}
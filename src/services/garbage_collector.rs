@@ -0,0 +1,183 @@
+use std::rc::Rc;
+use std::hash::Hash;
+use std::collections::{HashMap, HashSet};
+use yew_agent::{Agent, AgentLink, Context, HandlerId};
+use gloo_timers::callback::Interval;
+
+use crate::articles::{ArticleData, ArticleRc, ArticleWeak};
+use crate::services::storages::{get_article_caps, notify_storage_error, set_article_caps};
+use crate::timeline::sort_methods::sort_by_id;
+
+/// Time between two automatic collections
+const COLLECTION_INTERVAL: u32 = 300_000;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct ServiceMemoryStats {
+	pub article_count: usize,
+	/// Articles dropped during the last collection
+	pub evicted: usize,
+	/// Articles over the cap, forgotten by endpoints to be evicted next collection
+	pub forgotten: usize,
+}
+
+/// Drops articles nothing references anymore, including through `ArticleRefType`.
+/// Past the cap, the oldest articles are returned so endpoints can forget them.
+pub fn collect_articles<Id, A>(articles: &mut HashMap<Id, ArticleRc<A>>, cap: Option<usize>) -> (ServiceMemoryStats, Vec<ArticleWeak>)
+	where Id: Eq + Hash, A: ArticleData + 'static
+{
+	let before = articles.len();
+	//Dropping an article can free the ones it referenced
+	loop {
+		let len = articles.len();
+		articles.retain(|_, article| Rc::weak_count(article) > 0);
+		if articles.len() == len {
+			break;
+		}
+	}
+
+	let forgotten = match cap {
+		Some(cap) if articles.len() > cap => {
+			let mut sorted: Vec<ArticleWeak> = articles.values()
+				.map(|article| Rc::downgrade(article) as ArticleWeak)
+				.collect();
+			sorted.sort_by(sort_by_id);
+			sorted.split_off(cap)
+		}
+		_ => Vec::new(),
+	};
+
+	(ServiceMemoryStats {
+		article_count: articles.len(),
+		evicted: before - articles.len(),
+		forgotten: forgotten.len(),
+	}, forgotten)
+}
+
+/// Drops forgotten and already dropped articles from a list of references,
+/// so they stop keeping them from being collected
+pub fn forget_articles(articles: &mut Vec<ArticleWeak>, forgotten: &[ArticleWeak]) -> bool {
+	let forgotten: HashSet<*const ()> = forgotten.iter()
+		.map(|article| article.as_ptr() as *const ())
+		.collect();
+	let len = articles.len();
+	articles.retain(|article| article.strong_count() > 0 && !forgotten.contains(&(article.as_ptr() as *const ())));
+	articles.len() != len
+}
+
+pub struct GarbageCollectorAgent {
+	link: AgentLink<Self>,
+	services: HashMap<&'static str, HandlerId>,
+	subscribers: HashSet<HandlerId>,
+	stats: HashMap<&'static str, ServiceMemoryStats>,
+	caps: HashMap<String, usize>,
+	/// Unset when the stored caps couldn't be read, so they aren't overwritten
+	caps_writable: bool,
+	_interval: Interval,
+}
+
+pub enum GarbageCollectorMsg {
+	Collect,
+}
+
+pub enum GarbageCollectorRequest {
+	RegisterService(&'static str),
+	Collect,
+	ReportStats(&'static str, ServiceMemoryStats),
+	SetCap(String, Option<usize>),
+	GetStats,
+}
+
+pub enum GarbageCollectorResponse {
+	/// Sent to services, with their article cap
+	Collect(Option<usize>),
+	UpdatedStats(HashMap<&'static str, ServiceMemoryStats>, HashMap<String, usize>),
+}
+
+type Msg = GarbageCollectorMsg;
+type Request = GarbageCollectorRequest;
+type Response = GarbageCollectorResponse;
+
+impl Agent for GarbageCollectorAgent {
+	type Reach = Context<Self>;
+	type Message = Msg;
+	type Input = Request;
+	type Output = Response;
+
+	fn create(link: AgentLink<Self>) -> Self {
+		let callback = link.callback(|_| Msg::Collect);
+		let (caps, caps_writable) = match get_article_caps() {
+			Ok(caps) => (caps, true),
+			Err(err) => {
+				notify_storage_error(err);
+				(HashMap::new(), false)
+			}
+		};
+
+		Self {
+			link,
+			services: HashMap::new(),
+			subscribers: HashSet::new(),
+			stats: HashMap::new(),
+			caps,
+			caps_writable,
+			_interval: Interval::new(COLLECTION_INTERVAL, move || callback.emit(())),
+		}
+	}
+
+	fn update(&mut self, msg: Self::Message) {
+		match msg {
+			Msg::Collect => self.collect(),
+		}
+	}
+
+	fn connected(&mut self, id: HandlerId) {
+		self.subscribers.insert(id);
+	}
+
+	fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+		match msg {
+			Request::RegisterService(service) => {
+				self.subscribers.remove(&id);
+				self.services.insert(service, id);
+			}
+			Request::Collect => self.collect(),
+			Request::ReportStats(service, stats) => {
+				log::debug!("{}: {} articles, {} evicted, {} forgotten", service, stats.article_count, stats.evicted, stats.forgotten);
+				self.stats.insert(service, stats);
+				self.send_stats();
+			}
+			Request::SetCap(service, cap) => {
+				match cap {
+					Some(cap) => self.caps.insert(service, cap),
+					None => self.caps.remove(&service),
+				};
+				if self.caps_writable {
+					set_article_caps(&self.caps);
+				}
+				self.send_stats();
+			}
+			Request::GetStats => self.send_stats(),
+		}
+	}
+
+	fn disconnected(&mut self, id: HandlerId) {
+		self.subscribers.remove(&id);
+		self.services.retain(|_, service| *service != id);
+	}
+}
+
+impl GarbageCollectorAgent {
+	fn collect(&mut self) {
+		for (service, id) in &self.services {
+			self.link.respond(*id, Response::Collect(self.caps.get(*service).copied()));
+		}
+	}
+
+	fn send_stats(&self) {
+		for sub in &self.subscribers {
+			if sub.is_respondable() {
+				self.link.respond(*sub, Response::UpdatedStats(self.stats.clone(), self.caps.clone()));
+			}
+		}
+	}
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use gloo_storage::Storage;
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+
+const VERSION_KEY: &str = "SoshalThingYew Schema Version";
+/// Keys migrated together, as a single versioned schema
+const LOCAL_KEYS: [&str; 4] = [
+	"SoshalThingYew",
+	"SoshalThingYew Timelines",
+	"SoshalThingYew FavViewer",
+	"SoshalThingYew Workspaces",
+];
+/// Session storage of older versions, only read by migrations
+pub const SESSION_KEY: &str = "SoshalThingYew Session";
+
+/// Raw storage values by key, `Value::Null` when missing
+pub type StorageBlobs = HashMap<String, Value>;
+
+type Migration = fn(&mut StorageBlobs, f64) -> std::result::Result<(), &'static str>;
+
+/// Migration to version N is at index N - 1
+const MIGRATIONS: [Migration; 2] = [
+	add_missing_fields,
+	persist_session_read_marks,
+];
+
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Runs the migrations needed by the stored data, backing it up first
+pub fn migrate_storage() -> Result<()> {
+	let version: u32 = gloo_storage::LocalStorage::get(VERSION_KEY).unwrap_or_default();
+	if version > SCHEMA_VERSION {
+		log::warn!("Stored data is from a newer schema version ({} > {})", version, SCHEMA_VERSION);
+		return Ok(());
+	} else if version == SCHEMA_VERSION {
+		return Ok(());
+	}
+
+	let local = gloo_storage::LocalStorage::raw();
+	let raw: HashMap<String, String> = LOCAL_KEYS.iter()
+		.filter_map(|key| local.get_item(key).ok().flatten().map(|value| (key.to_string(), value)))
+		.collect();
+
+	if !raw.is_empty() {
+		let backup_key = format!("SoshalThingYew Backup v{}", version);
+		gloo_storage::LocalStorage::set(&backup_key, &raw)
+			.map_err(|err| Error::from(err).with_message("Couldn't back up stored data before migrating it"))?;
+
+		let mut blobs = StorageBlobs::new();
+		for (key, value) in &raw {
+			let value = serde_json::from_str(value)
+				.map_err(|err| Error::from(err).with_message(&format!("Couldn't parse \"{}\", a backup was kept in \"{}\"", key, &backup_key)))?;
+			blobs.insert(key.clone(), value);
+		}
+		if let Ok(Some(session)) = gloo_storage::SessionStorage::raw().get_item("SoshalThingYew") {
+			blobs.insert(SESSION_KEY.to_owned(), serde_json::from_str(&session).unwrap_or_default());
+		}
+
+		migrate(&mut blobs, version, js_sys::Date::now())
+			.map_err(|err| {
+				let message = format!("{}, a backup was kept in \"{}\"", err.message(), &backup_key);
+				err.with_message(&message)
+			})?;
+
+		for key in LOCAL_KEYS {
+			if let Some(value) = blobs.get(key).filter(|value| !value.is_null()) {
+				local.set_item(key, &value.to_string())
+					.map_err(|err| Error::from(err).with_message("Couldn't write migrated data"))?;
+			}
+		}
+		log::info!("Migrated stored data from version {} to {}", version, SCHEMA_VERSION);
+	}
+
+	gloo_storage::LocalStorage::set(VERSION_KEY, SCHEMA_VERSION)?;
+	Ok(())
+}
+
+/// Applies the migrations after `from` in order
+pub fn migrate(blobs: &mut StorageBlobs, from: u32, now: f64) -> Result<()> {
+	for (index, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+		migration(blobs, now)
+			.map_err(|err| Error::from(err).with_message(&format!("Couldn't migrate stored data to version {}: {}", index + 1, err)))?;
+	}
+	Ok(())
+}
+
+/// Fields that were required before defaults existed
+fn add_missing_fields(blobs: &mut StorageBlobs, _now: f64) -> std::result::Result<(), &'static str> {
+	if let Some(local) = blobs.get_mut("SoshalThingYew").filter(|value| !value.is_null()) {
+		let local = local.as_object_mut().ok_or("local storage isn't an object")?;
+		local.entry("display_mode").or_insert_with(|| json!({"type": "Default"}));
+
+		let services = local.entry("services").or_insert_with(|| json!({}));
+		for service in services.as_object_mut().ok_or("services isn't an object")?.values_mut() {
+			if let Some(service) = service.as_object_mut() {
+				service.entry("hidden_articles").or_insert_with(|| json!([]));
+			}
+		}
+	}
+
+	if let Some(timelines) = blobs.get_mut("SoshalThingYew Timelines").filter(|value| !value.is_null()) {
+		add_missing_timeline_fields(timelines)?;
+	}
+
+	if let Some(workspaces) = blobs.get_mut("SoshalThingYew Workspaces").filter(|value| !value.is_null()) {
+		for workspace in workspaces.as_array_mut().ok_or("workspaces aren't a list")? {
+			if let Some(timelines) = workspace.get_mut("timelines").filter(|value| !value.is_null()) {
+				add_missing_timeline_fields(timelines)?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+fn add_missing_timeline_fields(timelines: &mut Value) -> std::result::Result<(), &'static str> {
+	for (i, timeline) in timelines.as_array_mut().ok_or("timelines aren't a list")?.iter_mut().enumerate() {
+		if let Some(timeline) = timeline.as_object_mut() {
+			timeline.entry("title").or_insert_with(|| json!(format!("Timeline {}", i + 1)));
+		}
+	}
+	Ok(())
+}
+
+/// Read marks moved from session storage to local storage
+fn persist_session_read_marks(blobs: &mut StorageBlobs, now: f64) -> std::result::Result<(), &'static str> {
+	let marks: Vec<(String, Vec<String>)> = match blobs.get(SESSION_KEY).and_then(|session| session["services"].as_object()) {
+		Some(services) => services.iter()
+			.map(|(service, storage)| (
+				service.clone(),
+				storage["articles_marked_as_read"].as_array()
+					.map(|ids| ids.iter().filter_map(|id| id.as_str().map(str::to_owned)).collect())
+					.unwrap_or_default(),
+			))
+			.filter(|(_, ids): &(String, Vec<String>)| !ids.is_empty())
+			.collect(),
+		None => return Ok(()),
+	};
+
+	let local = blobs.entry("SoshalThingYew".to_owned()).or_insert(Value::Null);
+	if local.is_null() {
+		*local = json!({"services": {}, "display_mode": {"type": "Default"}});
+	}
+	let services = local.as_object_mut()
+		.ok_or("local storage isn't an object")?
+		.entry("services")
+		.or_insert_with(|| json!({}))
+		.as_object_mut()
+		.ok_or("services isn't an object")?;
+
+	for (service, ids) in marks {
+		let storage = services.entry(service).or_insert_with(|| json!({"hidden_articles": []}));
+		let read_articles = storage.as_object_mut()
+			.ok_or("service storage isn't an object")?
+			.entry("read_articles")
+			.or_insert_with(|| json!({}))
+			.as_object_mut()
+			.ok_or("read articles aren't an object")?;
+		for id in ids {
+			read_articles.entry(id).or_insert_with(|| json!({"marked": now}));
+		}
+	}
+
+	Ok(())
+}
@@ -6,6 +6,14 @@ pub use soshalthing_macros::service;
 
 pub mod endpoint_agent;
 pub mod article_actions;
+pub mod garbage_collector;
+pub mod fetch_worker;
+pub mod virtual_endpoint;
+pub mod refresh_schedule;
+pub mod mute;
+pub mod article_cache;
+pub mod migrations;
+pub mod workspace;
 
 pub mod twitter;
 pub mod pixiv;
@@ -14,17 +22,19 @@ pub mod storages;
 //#[cfg(feature = "dummy_service")]
 pub mod dummy_service;
 
-pub use endpoint_agent::{EndpointId, EndpointAgent, RefreshTime};
+pub use endpoint_agent::{EndpointId, EndpointAgent, RefreshTime, RefreshKind, RefreshRequest, RetryPolicy, Backoff, BackoffAction};
+pub use refresh_schedule::{RefreshSchedule, ScheduleRule};
 use crate::error::Error;
 use crate::articles::ArticleWeak;
 use crate::timeline::sort_methods::sort_by_id;
 use crate::timeline::filters::FilterCollection;
+use virtual_endpoint::Composition;
 
 pub struct ServiceInfo {
 	pub name: &'static str
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct RateLimit {
 	pub limit: i32,
 	pub remaining: i32,
@@ -65,16 +75,121 @@ impl TryFrom<&HeaderMap> for RateLimit {
 
 impl RateLimit {
 	pub fn can_refresh(&mut self) -> bool {
-		if (self.reset as f64) < js_sys::Date::now() {
+		self.can_refresh_at(js_sys::Date::now())
+	}
+
+	/// `now` in milliseconds, like `Date::now()`
+	pub fn can_refresh_at(&mut self, now: f64) -> bool {
+		if self.reset_time() < now {
 			self.remaining = self.limit;
 			true
 		}else {
 			self.remaining > 0
 		}
 	}
+
+	/// Reset timestamp in milliseconds, `reset` being in seconds
+	pub fn reset_time(&self) -> f64 {
+		self.reset as f64 * 1000.0
+	}
 }
 
-#[derive(Serialize, Deserialize)]
+/// Where an endpoint is in its feed, updated as articles get added
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pagination {
+	/// Id of the newest article, to load above it
+	pub top: Option<String>,
+	/// Id of the oldest article, to load below it
+	pub bottom: Option<String>,
+	/// Last loaded page, for page based endpoints
+	pub page: u16,
+	/// Whether loading the bottom returned nothing, until the next refresh
+	pub exhausted: bool,
+	/// Ranges skipped by refreshes, newest first
+	pub gaps: Vec<Gap>,
+	track_gaps: bool,
+	loading: Option<RefreshKind>,
+	filling_gap: Option<Gap>,
+}
+
+/// Articles missing between two refresh batches
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gap {
+	/// Id of the oldest article above the gap
+	pub newer: String,
+	/// Id of the newest article below the gap
+	pub older: String,
+}
+
+impl Pagination {
+	pub fn with_page(page: u16) -> Self {
+		Self {
+			page,
+			..Default::default()
+		}
+	}
+
+	/// For endpoints able to load between two ids
+	pub fn with_gaps() -> Self {
+		Self {
+			track_gaps: true,
+			..Default::default()
+		}
+	}
+
+	/// Marks the next added articles as coming from this kind of refresh
+	pub fn start_loading(&mut self, kind: RefreshKind) {
+		self.loading = Some(kind);
+	}
+
+	pub fn start_filling_gap(&mut self, gap: Gap) {
+		self.filling_gap = Some(gap);
+	}
+
+	pub fn filling_gap(&self) -> Option<&Gap> {
+		self.filling_gap.as_ref()
+	}
+
+	/// `batch_oldest` being the oldest of the `added` articles out of `batch_len`, and `batch_bottom` the oldest of all of them
+	fn update(&mut self, top: Option<String>, bottom: Option<String>, batch_len: usize, added: usize, batch_oldest: Option<String>, batch_bottom: Option<String>) {
+		match self.loading.take() {
+			Some(RefreshKind::LoadBottom) => {
+				//A page of articles already loaded isn't the end of the feed
+				if batch_len == 0 {
+					self.exhausted = true;
+				} else {
+					self.page += 1;
+				}
+			}
+			Some(RefreshKind::FillGap) => {
+				let gap = self.filling_gap.take();
+				if let Some(index) = gap.and_then(|gap| self.gaps.iter().position(|g| g == &gap)) {
+					//Fills include the bounds, so reaching the older one closes the gap
+					let reached_older = batch_bottom.as_ref() == Some(&self.gaps[index].older);
+					match batch_oldest {
+						Some(oldest) if added > 0 && !reached_older => self.gaps[index].newer = oldest,
+						_ => { self.gaps.remove(index); }
+					}
+				}
+			}
+			_ => {
+				self.exhausted = false;
+
+				//No overlap with what was already loaded
+				if self.track_gaps && added > 0 && added == batch_len {
+					if let (Some(older), Some(newer)) = (self.top.clone(), batch_oldest) {
+						self.gaps.insert(0, Gap { newer, older });
+					}
+				}
+			}
+		}
+
+		self.top = top;
+		self.bottom = bottom;
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct EndpointSerialized {
 	pub service: String,
 	pub endpoint_type: usize,
@@ -87,6 +202,24 @@ pub struct EndpointSerialized {
 	pub on_start: bool,
 	#[serde(default)]
 	pub on_refresh: bool,
+	#[serde(default)]
+	pub refresh_schedule: RefreshSchedule,
+}
+
+impl EndpointSerialized {
+	/// Without filters nor refresh options
+	pub fn new(service: &str, endpoint_type: usize, params: serde_json::Value) -> Self {
+		Self {
+			service: service.to_owned(),
+			endpoint_type,
+			params,
+			filters: FilterCollection::default(),
+			auto_refresh: false,
+			on_start: false,
+			on_refresh: false,
+			refresh_schedule: RefreshSchedule::default(),
+		}
+	}
 }
 
 pub trait Endpoint {
@@ -97,37 +230,75 @@ pub trait Endpoint {
 	fn articles(&mut self) -> &mut Vec<ArticleWeak>;
 
 	fn add_articles(&mut self, articles: Vec<ArticleWeak>)  {
+		let batch_len = articles.len();
+		let article_id = |a: Option<&ArticleWeak>| a.and_then(|a| a.upgrade()).map(|a| a.borrow().id());
+		let batch_bottom = article_id(articles.iter().max_by(|a, b| sort_by_id(a, b)));
+		let mut added = Vec::new();
 		for a in articles {
 			if !self.articles().iter().any(|existing| Weak::ptr_eq(&existing, &a)) {
-				self.articles().push(a);
+				self.articles().push(a.clone());
+				added.push(a);
 			}
 		}
-		self.articles().sort_by(sort_by_id)
+		self.articles().sort_by(sort_by_id);
+
+		let top = article_id(self.articles().first());
+		let bottom = article_id(self.articles().last());
+		let batch_oldest = article_id(self.articles().iter().rev().find(|a| added.iter().any(|added| Weak::ptr_eq(a, added))));
+		if let Some(pagination) = self.get_mut_pagination() {
+			pagination.update(top, bottom, batch_len, added.len(), batch_oldest, batch_bottom);
+		}
 	}
 
+	fn pagination(&self) -> Option<&Pagination> { None }
+
+	fn get_mut_pagination(&mut self) -> Option<&mut Pagination> { None }
+
+	/// For virtual endpoints, built out of other endpoints
+	fn composition(&self) -> Option<&Composition> { None }
+
 	fn ratelimit(&self) -> Option<&RateLimit> { None }
 
 	fn get_mut_ratelimit(&mut self) -> Option<&mut RateLimit> { None }
 
 	fn update_ratelimit(&mut self, _ratelimit: RateLimit) {}
 
+	/// Endpoints returning the same bucket share their upstream rate limit
+	fn ratelimit_bucket(&self) -> Option<String> { None }
+
 	fn can_refresh(&self) -> bool { true }
 
-	fn refresh(&mut self, refresh_time: RefreshTime);
+	/// Time in ms after which a refresh without response is given up on, letting new refreshes through
+	fn refresh_timeout(&self) -> u32 { 60_000 }
 
-	fn load_top(&mut self, refresh_time: RefreshTime) {
+	fn refresh(&mut self, refresh: RefreshRequest);
+
+	fn load_top(&mut self, refresh: RefreshRequest) {
 		log::debug!("{} doesn't implement load_top()", self.name());
-		self.refresh(refresh_time)
+		self.refresh(refresh)
 	}
 
-	fn load_bottom(&mut self, refresh_time: RefreshTime) {
+	fn load_bottom(&mut self, refresh: RefreshRequest) {
 		log::debug!("{} doesn't implement load_bottom()", self.name());
-		self.refresh(refresh_time)
+		self.refresh(refresh)
+	}
+
+	/// Loads the articles of `Pagination::filling_gap`
+	fn fill_gap(&mut self, refresh: RefreshRequest) {
+		log::debug!("{} doesn't implement fill_gap()", self.name());
+		self.refresh(refresh)
 	}
 
 	fn eq_storage(&self, storage: &EndpointSerialized) -> bool;
 
+	/// Storage recreating the endpoint through its service's constructors, None if it can't be
+	fn serialize(&self) -> Option<EndpointSerialized> { None }
+
 	fn default_interval(&self) -> u32 {
 		90_000
 	}
+
+	fn default_retry_policy(&self) -> RetryPolicy {
+		RetryPolicy::default()
+	}
 }
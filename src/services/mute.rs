@@ -0,0 +1,115 @@
+use std::cell::Ref;
+use serde::{Serialize, Deserialize};
+
+use crate::articles::{ArticleData, ArticleRefType};
+use crate::timeline::filters::cached_regexp;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MuteTarget {
+	User {
+		service: String,
+		username: String,
+	},
+	/// Case-insensitive
+	Keyword(String),
+	/// Case-insensitive JS regular expression, invalid patterns muting nothing
+	Regex(String),
+	/// Without the leading #
+	Hashtag(String),
+	/// Also mutes subdomains
+	Domain(String),
+}
+
+impl MuteTarget {
+	pub fn kind_name(&self) -> &'static str {
+		match self {
+			MuteTarget::User { .. } => "User",
+			MuteTarget::Keyword(_) => "Keyword",
+			MuteTarget::Regex(_) => "Regex",
+			MuteTarget::Hashtag(_) => "Hashtag",
+			MuteTarget::Domain(_) => "Domain",
+		}
+	}
+
+	pub fn value(&self) -> String {
+		match self {
+			MuteTarget::User { service, username } => format!("{}: @{}", service, username),
+			MuteTarget::Keyword(value) | MuteTarget::Regex(value) | MuteTarget::Domain(value) => value.clone(),
+			MuteTarget::Hashtag(hashtag) => format!("#{}", hashtag),
+		}
+	}
+
+	fn mutes(&self, article: &Ref<dyn ArticleData>) -> bool {
+		match self {
+			MuteTarget::User { service, username } =>
+				article.service() == service && article.author_username().eq_ignore_ascii_case(username),
+			MuteTarget::Keyword(keyword) => article.text().to_lowercase().contains(&keyword.to_lowercase()),
+			MuteTarget::Regex(pattern) => cached_regexp(pattern)
+				.map(|regex| regex.test(&article.text()))
+				.unwrap_or_default(),
+			MuteTarget::Hashtag(hashtag) => article.hashtags().iter().any(|h| h.eq_ignore_ascii_case(hashtag)),
+			MuteTarget::Domain(domain) => article.links().iter()
+				.filter_map(|link| reqwest::Url::parse(link).ok())
+				.any(|url| url.host_str().map(|host| is_subdomain(host, domain)).unwrap_or_default()),
+		}
+	}
+}
+
+fn mutes_any(rules: &[&MuteRule], article: &Ref<dyn ArticleData>) -> bool {
+	rules.iter().any(|rule| rule.target.mutes(article))
+}
+
+fn is_subdomain(host: &str, domain: &str) -> bool {
+	let host = host.to_lowercase();
+	let domain = domain.trim_start_matches("www.").to_lowercase();
+	host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MuteRule {
+	pub target: MuteTarget,
+	/// Timestamp after which the rule is dropped, None to mute forever
+	#[serde(default)]
+	pub expiry: Option<f64>,
+}
+
+impl MuteRule {
+	pub fn is_expired(&self, now: f64) -> bool {
+		self.expiry.map(|expiry| expiry <= now).unwrap_or_default()
+	}
+}
+
+/// Global rules hiding articles before they reach any timeline
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MuteList {
+	#[serde(default)]
+	pub rules: Vec<MuteRule>,
+}
+
+impl MuteList {
+	/// Also checks the reposted and quoted articles
+	pub fn is_muted(&self, article: &Ref<dyn ArticleData>, now: f64) -> bool {
+		let active: Vec<&MuteRule> = self.rules.iter().filter(|rule| !rule.is_expired(now)).collect();
+		if active.is_empty() {
+			return false;
+		}
+
+		mutes_any(&active, article) || article.referenced_articles().into_iter().any(|ref_article| {
+			let referenced = match ref_article {
+				ArticleRefType::Reposted(a) | ArticleRefType::Quote(a) => vec![a],
+				ArticleRefType::RepostedQuote(a, q) => vec![a, q],
+				ArticleRefType::Reply(_) => vec![],
+			};
+			referenced.into_iter()
+				.filter_map(|a| a.upgrade())
+				.any(|a| mutes_any(&active, &a.borrow()))
+		})
+	}
+
+	/// Returns whether any rule expired
+	pub fn remove_expired(&mut self, now: f64) -> bool {
+		let len = self.rules.len();
+		self.rules.retain(|rule| !rule.is_expired(now));
+		self.rules.len() != len
+	}
+}
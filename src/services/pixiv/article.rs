@@ -4,7 +4,7 @@ use serde::{Serialize, Deserialize};
 use derivative::Derivative;
 
 use super::SERVICE_INFO;
-use crate::articles::{ArticleBox, ArticleData, ArticleMedia, MediaQueueInfo};
+use crate::articles::{ArticleBox, ArticleData, ArticleMedia, MediaQueueInfo, RawJson};
 
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
@@ -20,7 +20,7 @@ pub struct PixivArticleData {
 	pub hidden: bool,
 	pub is_fully_fetched: bool,
 	#[derivative(Debug = "ignore")]
-	pub raw_json: serde_json::Value,
+	pub raw_json: RawJson,
 	pub like_count: u32,
 	pub liked: bool,
 	pub bookmark_count: u32,
@@ -70,7 +70,7 @@ impl ArticleData for PixivArticleData {
 	}
 
 	fn json(&self) -> serde_json::Value {
-		self.raw_json.clone()
+		self.raw_json.value()
 	}
 
 	fn url(&self) -> String {
@@ -127,10 +127,9 @@ impl PixivArticleData {
 		self.media = new.media.clone();
 		self.title = new.title.clone();
 		self.is_fully_fetched = self.is_fully_fetched || *new.is_fully_fetched();
-		match &new.raw_json {
-			serde_json::Value::Null => {}
-			new_json => self.raw_json = new_json.clone(),
-		};
+		if !new.raw_json.is_null() {
+			self.raw_json = new.raw_json.clone();
+		}
 
 		self.like_count = new.like_count;
 		self.liked = new.liked;
@@ -8,9 +8,9 @@ use wasm_bindgen::JsValue;
 
 use super::{PixivAgent, Request, SERVICE_INFO};
 use super::article::{PixivArticleData, PixivArticleCached};
-use crate::articles::{ArticleMedia, ArticleRc, ArticleWeak, MediaQueueInfo, MediaType, ValidRatio};
-use crate::services::{Endpoint, EndpointSerialized};
-use crate::services::endpoint_agent::{EndpointId, RefreshTime};
+use crate::articles::{ArticleMedia, ArticleRc, ArticleWeak, MediaQueueInfo, MediaType, RawJson, ValidRatio};
+use crate::services::{Endpoint, EndpointSerialized, Pagination};
+use crate::services::endpoint_agent::{EndpointId, RefreshRequest};
 use crate::services::storages::{ServiceStorage, get_service_storage};
 use crate::log_error;
 
@@ -86,8 +86,8 @@ pub struct FollowAPIIllust {
 	pub create_date: String,
 }
 
-impl From<(serde_json::Value, &FullPostAPI, &ServiceStorage)> for PixivArticleData {
-	fn from((raw_json, data, storage): (serde_json::Value, &FullPostAPI, &ServiceStorage)) -> Self {
+impl From<(RawJson, &FullPostAPI, &ServiceStorage)> for PixivArticleData {
+	fn from((raw_json, data, storage): (RawJson, &FullPostAPI, &ServiceStorage)) -> Self {
 		let cached: Option<PixivArticleCached> = storage.session.cached_articles.get(&data.id)
 			.and_then(|json| serde_json::from_value(json.clone()).ok());
 		let author_avatar_url = match cached {
@@ -95,9 +95,12 @@ impl From<(serde_json::Value, &FullPostAPI, &ServiceStorage)> for PixivArticleDa
 			None => "".to_owned(),
 		};
 
+		let creation_time = Date::new(&JsValue::from_str(&data.create_date));
+
 		PixivArticleData {
 			id: data.id.parse::<u32>().unwrap(),
-			creation_time: Date::new(&JsValue::from_str(&data.create_date)),
+			marked_as_read: storage.local.is_marked_as_read(data.id.as_str(), creation_time.get_time()),
+			creation_time,
 			title: data.title.clone(),
 			media: ArticleMedia {
 				media_type: MediaType::Image,
@@ -108,7 +111,6 @@ impl From<(serde_json::Value, &FullPostAPI, &ServiceStorage)> for PixivArticleDa
 			author_name: data.user_name.clone(),
 			author_id: data.user_id.parse::<u32>().unwrap(),
 			author_avatar_url,
-			marked_as_read: storage.session.articles_marked_as_read.contains(data.id.as_str()),
 			hidden: storage.local.hidden_articles.contains(data.id.as_str()),
 			is_fully_fetched: true,
 			raw_json,
@@ -120,14 +122,14 @@ impl From<(serde_json::Value, &FullPostAPI, &ServiceStorage)> for PixivArticleDa
 	}
 }
 
-impl From<(serde_json::Value, FullPostAPI, &ServiceStorage)> for PixivArticleData {
-	fn from((raw_json, data, storage): (serde_json::Value, FullPostAPI, &ServiceStorage)) -> Self {
+impl From<(RawJson, FullPostAPI, &ServiceStorage)> for PixivArticleData {
+	fn from((raw_json, data, storage): (RawJson, FullPostAPI, &ServiceStorage)) -> Self {
 		PixivArticleData::from((raw_json, &data, storage))
 	}
 }
 
-impl From<(serde_json::Value, &FollowAPIIllust, &ServiceStorage)> for PixivArticleData {
-	fn from((raw_json, data, storage): (serde_json::Value, &FollowAPIIllust, &ServiceStorage)) -> Self {
+impl From<(RawJson, &FollowAPIIllust, &ServiceStorage)> for PixivArticleData {
+	fn from((raw_json, data, storage): (RawJson, &FollowAPIIllust, &ServiceStorage)) -> Self {
 		let cached: Option<PixivArticleCached> = storage.session.cached_articles.get(&data.id)
 			.and_then(|json| serde_json::from_value(json.clone()).ok());
 		let (media, is_fully_fetched) = match cached {
@@ -140,15 +142,17 @@ impl From<(serde_json::Value, &FollowAPIIllust, &ServiceStorage)> for PixivArtic
 			}, false)
 		};
 
+		let creation_time = Date::new(&JsValue::from_str(&data.create_date));
+
 		PixivArticleData {
 			id: data.id.parse::<u32>().unwrap(),
-			creation_time: Date::new(&JsValue::from_str(&data.create_date)),
+			marked_as_read: storage.local.is_marked_as_read(data.id.as_str(), creation_time.get_time()),
+			creation_time,
 			title: data.title.clone(),
 			media,
 			author_name: data.user_name.clone(),
 			author_id: data.user_id.parse::<u32>().unwrap(),
 			author_avatar_url: data.profile_image_url.clone(),
-			marked_as_read: storage.session.articles_marked_as_read.contains(data.id.as_str()),
 			hidden: storage.local.hidden_articles.contains(data.id.as_str()),
 			is_fully_fetched,
 			raw_json,
@@ -247,10 +251,10 @@ fn parse_article(element: web_sys::Element, storage: &ServiceStorage) -> Option<
 		title,
 		author_id,
 		author_name,
-		marked_as_read: storage.session.articles_marked_as_read.contains(&id.to_string()),
+		marked_as_read: storage.local.is_marked_as_read(&id.to_string(), js_sys::Date::now()),
 		hidden: storage.local.hidden_articles.contains(&id.to_string()),
 		is_fully_fetched,
-		raw_json: serde_json::Value::Null,
+		raw_json: RawJson::default(),
 		like_count: 0,
 		liked: false,
 		bookmark_count: 0,
@@ -289,7 +293,7 @@ impl Endpoint for FollowPageEndpoint {
 		&mut self.articles
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		let mut articles = Vec::new();
 		let posts_selector = gloo_utils::document()
 			.query_selector(".sc-9y4be5-1.jtUPOE");
@@ -298,7 +302,7 @@ impl Endpoint for FollowPageEndpoint {
 				if self.timeout.is_none() {
 					let mut agent = PixivAgent::dispatcher();
 					let id = self.id;
-					let timeout = Some(Timeout::new(1_000, move || agent.send(Request::RefreshEndpoint(id, refresh_time))));
+					let timeout = Some(Timeout::new(1_000, move || agent.send(Request::RefreshEndpoint(id, refresh.time))));
 					self.timeout = timeout;
 				}
 			}
@@ -312,16 +316,16 @@ impl Endpoint for FollowPageEndpoint {
 					}
 				}
 
-				self.agent.send(Request::AddArticles(refresh_time, self.id, articles));
+				self.agent.send(Request::AddArticles(refresh, self.id, articles));
 				self.timeout = None;
 			}
 			Err(err) => log_error!("Failed to use query_selector", err),
 		};
 	}
 
-	fn eq_storage(&self, storage: &EndpointSerialized) -> bool {
-		storage.service == SERVICE_INFO.name &&
-			storage.endpoint_type == 0
+	//Built from the page rather than stored
+	fn eq_storage(&self, _storage: &EndpointSerialized) -> bool {
+		false
 	}
 }
 
@@ -330,7 +334,8 @@ pub struct FollowAPIEndpoint {
 	r18: bool,
 	articles: Vec<ArticleWeak>,
 	agent: Dispatcher<PixivAgent>,
-	page: u16,
+	start_page: u16,
+	pagination: Pagination,
 }
 
 impl FollowAPIEndpoint {
@@ -340,13 +345,30 @@ impl FollowAPIEndpoint {
 			r18,
 			articles: Vec::new(),
 			agent: PixivAgent::dispatcher(),
-			page: current_page,
+			start_page: current_page,
+			pagination: Pagination::with_page(current_page),
 		}
 	}
 
 	pub fn from_json(id: EndpointId, params: serde_json::Value) -> Self {
 		Self::new(id, params["r18"].as_bool().unwrap(), params["current_page"].as_u64().unwrap() as u16)
 	}
+
+	fn fetch_page(&mut self, refresh: RefreshRequest, page: u16) {
+		let query = web_sys::UrlSearchParams::new().unwrap();
+		if page > 0 {
+			query.append("p", &(page + 1).to_string());
+		}
+		if self.r18 {
+			query.append("mode", "r18");
+		}
+		self.agent.send(Request::FetchPosts(
+			refresh,
+			self.id,
+			//TODO Use Url
+			format!("https://www.pixiv.net/ajax/follow_latest/illust?{}", query.to_string()),
+		))
+	}
 }
 
 impl Endpoint for FollowAPIEndpoint {
@@ -362,29 +384,32 @@ impl Endpoint for FollowAPIEndpoint {
 		&mut self.articles
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
-		let query = web_sys::UrlSearchParams::new().unwrap();
-		if self.page > 0 {
-			query.append("p", &(self.page + 1).to_string());
-		}
-		if self.r18 {
-			query.append("mode", "r18");
-		}
-		self.agent.send(Request::FetchPosts(
-			refresh_time,
-			self.id,
-			//TODO Use Url
-			format!("https://www.pixiv.net/ajax/follow_latest/illust?{}", query.to_string()),
-		))
+	fn pagination(&self) -> Option<&Pagination> {
+		Some(&self.pagination)
+	}
+
+	fn get_mut_pagination(&mut self) -> Option<&mut Pagination> {
+		Some(&mut self.pagination)
 	}
 
-	fn load_bottom(&mut self, refresh_time: RefreshTime) {
-		self.page += 1;
-		self.refresh(refresh_time)
+	fn refresh(&mut self, refresh: RefreshRequest) {
+		self.fetch_page(refresh, self.start_page)
+	}
+
+	fn load_bottom(&mut self, refresh: RefreshRequest) {
+		self.fetch_page(refresh, self.pagination.page + 1)
 	}
 
 	fn eq_storage(&self, storage: &EndpointSerialized) -> bool {
 		storage.service == SERVICE_INFO.name &&
-			storage.endpoint_type == 1
+			storage.endpoint_type == 0 &&
+			storage.params["r18"].as_bool() == Some(self.r18)
+	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 0, serde_json::json!({
+			"r18": self.r18,
+			"current_page": self.start_page,
+		})))
 	}
 }
\ No newline at end of file
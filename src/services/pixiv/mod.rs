@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::rc::Rc;
-use yew_agent::{Agent, AgentLink, Context, HandlerId, Dispatched, Dispatcher};
+use yew_agent::{Agent, AgentLink, Context, HandlerId, Bridge, Bridged, Dispatched, Dispatcher};
 use std::collections::{HashMap, HashSet};
 
 pub mod endpoints;
@@ -8,34 +8,52 @@ pub mod article;
 
 use article::{PixivArticleData, PixivArticleCached};
 
-use crate::articles::{ArticleRc, ArticleWeak};
-use crate::error::RatelimitedResult;
+use crate::articles::{ArticleRc, ArticleWeak, RawJson};
+use crate::error::{Result, RatelimitedResult};
 use crate::services::{
 	service,
 	article_actions::{ArticleActionsAgent, ServiceActions, ArticleActionsRequest},
-	endpoint_agent::{EndpointAgent, EndpointRequest, EndpointId, RefreshTime, EndpointConstructorCollection, EndpointConstructor},
-	pixiv::endpoints::{APIPayload, FollowAPIEndpoint, FollowAPIResponse, FullPostAPI},
-	storages::{ServiceStorage, get_service_storage, cache_articles},
+	endpoint_agent::{EndpointAgent, EndpointRequest, EndpointId, RefreshRequest, RefreshTime, EndpointConstructorCollection, EndpointConstructor},
+	garbage_collector::{GarbageCollectorAgent, GarbageCollectorRequest, GarbageCollectorResponse},
+	fetch_worker::{FetchKind, FetchWorkerResponse, ParsedArticles, WorkerFetcher, unexpected_articles},
+	pixiv::endpoints::{APIPayload, FollowAPIEndpoint, FollowAPIIllust, FollowAPIResponse, FullPostAPI},
+	storages::{ServiceStorage, get_fetch_in_worker, get_service_storage},
+	article_cache::{ArticleCacheAgent, ArticleCacheRequest, CachedArticle, cached_articles},
 };
 
+/// What a fetch was for, to route its response
+enum FetchContext {
+	Endpoint(RefreshRequest, EndpointId),
+	Post,
+}
+
 #[service("Pixiv", PixivArticleData, u32)]
 pub struct PixivAgent {
 	link: AgentLink<Self>,
 	endpoint_agent: Dispatcher<EndpointAgent>,
 	actions_agent: Dispatcher<ArticleActionsAgent>,
 	fetching_articles: HashSet<u32>,
+	garbage_collector: Box<dyn Bridge<GarbageCollectorAgent>>,
+	article_cache: Dispatcher<ArticleCacheAgent>,
+	/// Last entries sent to the cache, to only write new or changed articles
+	cached: HashMap<u32, serde_json::Value>,
+	worker_fetcher: WorkerFetcher<FetchContext>,
 }
 
 pub enum PixivMsg {
 	FetchResponse(RatelimitedResult<Vec<ArticleRc<PixivArticleData>>>),
-	EndpointFetchResponse(RefreshTime, EndpointId, RatelimitedResult<Vec<ArticleRc<PixivArticleData>>>),
+	EndpointFetchResponse(RefreshRequest, EndpointId, RatelimitedResult<Vec<ArticleRc<PixivArticleData>>>),
 	FetchData(HandlerId, ArticleWeak),
+	GarbageCollectorResponse(GarbageCollectorResponse),
+	/// Cache entries read for articles parsed without them
+	Hydrate(Vec<u32>, HashMap<String, serde_json::Value>),
+	WorkerFetchResponse(FetchWorkerResponse),
 }
 
 pub enum PixivRequest {
-	AddArticles(RefreshTime, EndpointId, Vec<ArticleRc<PixivArticleData>>),
+	AddArticles(RefreshRequest, EndpointId, Vec<ArticleRc<PixivArticleData>>),
 	RefreshEndpoint(EndpointId, RefreshTime),
-	FetchPosts(RefreshTime, EndpointId, String),
+	FetchPosts(RefreshRequest, EndpointId, String),
 }
 
 type Msg = PixivMsg;
@@ -65,6 +83,9 @@ impl Agent for PixivAgent {
 				user_endpoint_index: None,
 			}));
 
+		let mut garbage_collector = GarbageCollectorAgent::bridge(link.callback(Msg::GarbageCollectorResponse));
+		garbage_collector.send(GarbageCollectorRequest::RegisterService(SERVICE_INFO.name));
+
 		let mut actions_agent = ArticleActionsAgent::dispatcher();
 		actions_agent.send(ArticleActionsRequest::Init(SERVICE_INFO.name, ServiceActions {
 			like: None,
@@ -72,18 +93,50 @@ impl Agent for PixivAgent {
 			fetch_data: Some(link.callback(|(id, article)| Msg::FetchData(id, article))),
 		}));
 
+		let worker_fetcher = WorkerFetcher::new(link.callback(Msg::WorkerFetchResponse));
+
 		Self {
 			link,
 			endpoint_agent,
 			actions_agent,
 			articles: HashMap::new(),
 			fetching_articles: HashSet::new(),
+			garbage_collector,
+			article_cache: ArticleCacheAgent::dispatcher(),
+			cached: HashMap::new(),
+			worker_fetcher,
 		}
 	}
 
 	fn update(&mut self, msg: Self::Message) {
 		match msg {
-			Msg::EndpointFetchResponse(refresh_time, id, r) => {
+			Msg::GarbageCollectorResponse(response) => self.handle_garbage_collector_response(response),
+			Msg::WorkerFetchResponse(response) => {
+				if let Some((context, r)) = self.worker_fetcher.take(response) {
+					//Building reads the cache entries of the articles
+					self.link.send_future(async move {
+						let storage = get_service_storage(SERVICE_INFO.name);
+						match (context, r) {
+							(FetchContext::Endpoint(refresh, id), Ok((ParsedArticles::PixivPosts(posts), _))) =>
+								Msg::EndpointFetchResponse(refresh, id, Ok((build_posts(posts, storage).await, None))),
+							(FetchContext::Endpoint(refresh, id), r) =>
+								Msg::EndpointFetchResponse(refresh, id, r.and(Err(unexpected_articles()))),
+							(FetchContext::Post, Ok((ParsedArticles::PixivPost(post, raw_json), _))) =>
+								Msg::FetchResponse(Ok((vec![build_post(post, raw_json, storage).await], None))),
+							(FetchContext::Post, r) =>
+								Msg::FetchResponse(r.and(Err(unexpected_articles()))),
+						}
+					});
+				}
+			}
+			Msg::Hydrate(ids, cached) => {
+				for id in &ids {
+					self.fetching_articles.remove(id);
+				}
+				self.hydrate_articles(cached);
+				self.check_unfetched_articles();
+			}
+			Msg::EndpointFetchResponse(refresh, id, r) => {
 				let mut valid_rc = Vec::new();
 				if let Ok((articles, _)) = &r {
 					for article in articles {
@@ -96,7 +149,7 @@ impl Agent for PixivAgent {
 					}
 				}
 				self.endpoint_agent.send(EndpointRequest::EndpointFetchResponse(
-					refresh_time,
+					refresh,
 					id,
 					r.map(move |(_, ratelimit)|
 						(
@@ -135,45 +188,68 @@ impl Agent for PixivAgent {
 				let path = format!("https://www.pixiv.net/ajax/illust/{}", borrow.id());
 
 				self.fetching_articles.insert(borrow.id().parse::<u32>().unwrap());
-				self.link.send_future(async move {
-					Msg::FetchResponse(fetch_post(&path, &get_service_storage(SERVICE_INFO.name)).await.map(|(article, _)| (vec![article], None)))
-				});
+				self.fetch_post(path);
 			}
 		}
 	}
 
 	fn handle_input(&mut self, msg: Self::Input, _id: HandlerId) {
 		match msg {
-			Request::AddArticles(refresh_time, endpoint_id, articles) => {
+			Request::AddArticles(refresh, endpoint_id, articles) => {
 				let mut valid_rc = Vec::new();
+				let mut unfetched = Vec::new();
 				for article in articles.into_iter() {
 					let borrow = article.borrow();
 					let valid_a_rc = self.articles.entry(borrow.id)
 						.and_modify(|a| a.borrow_mut().update(&borrow))
 						.or_insert_with(|| article.clone()).clone();
 
+					if !valid_a_rc.borrow().is_fully_fetched && self.fetching_articles.insert(borrow.id) {
+						unfetched.push(borrow.id);
+					}
 					valid_rc.push(valid_a_rc);
 				}
 				self.endpoint_agent.send(EndpointRequest::AddArticles(
-					refresh_time,
+					refresh,
 					endpoint_id,
 					valid_rc.into_iter()
 						.map(|article| article as ArticleRc)
 						.collect(),
 				));
 
-				self.check_unfetched_articles();
+				//Parsed without the cache, which is read before fetching what it lacks
+				if unfetched.is_empty() {
+					self.check_unfetched_articles();
+				} else {
+					self.link.send_future(async move {
+						let cached = cached_articles(SERVICE_INFO.name, unfetched.iter().map(|id| id.to_string())).await;
+						Msg::Hydrate(unfetched, cached)
+					});
+				}
 			}
 			Request::RefreshEndpoint(endpoint_id, refresh_time) => self.endpoint_agent.send(EndpointRequest::RefreshEndpoint(endpoint_id, refresh_time)),
-			Request::FetchPosts(refresh_time, endpoint_id, path) =>
+			Request::FetchPosts(refresh, endpoint_id, path) => if get_fetch_in_worker() {
+				self.worker_fetcher.fetch(path, FetchKind::PixivPosts, FetchContext::Endpoint(refresh, endpoint_id));
+			} else {
 				self.link.send_future(async move {
-					Msg::EndpointFetchResponse(refresh_time, endpoint_id, fetch_posts(&path, &get_service_storage(SERVICE_INFO.name)).await)
+					Msg::EndpointFetchResponse(refresh, endpoint_id, fetch_posts(&path, get_service_storage(SERVICE_INFO.name)).await)
 				})
+			}
 		};
 	}
 }
 
 impl PixivAgent {
+	fn fetch_post(&mut self, path: String) {
+		if get_fetch_in_worker() {
+			self.worker_fetcher.fetch(path, FetchKind::PixivPost, FetchContext::Post);
+		} else {
+			self.link.send_future(async move {
+				Msg::FetchResponse(fetch_post(&path, get_service_storage(SERVICE_INFO.name)).await.map(|(article, _)| (vec![article], None)))
+			});
+		}
+	}
+
 	fn check_unfetched_articles(&mut self) {
 		let unfetched: Vec<u32> = self.articles.values().filter_map(|a| if !a.borrow().is_fully_fetched && !self.fetching_articles.contains(&a.borrow().id) {
 			Some(a.borrow().id)
@@ -189,9 +265,7 @@ impl PixivAgent {
 					let path = format!("https://www.pixiv.net/ajax/illust/{}", &id);
 
 					self.fetching_articles.insert(id);
-					self.link.send_future(async move {
-						Msg::FetchResponse(fetch_post(&path, &get_service_storage(SERVICE_INFO.name)).await.map(|(article, _)| (vec![article], None)))
-					});
+					self.fetch_post(path);
 				}
 			}
 		} else if self.fetching_articles.is_empty() {
@@ -199,17 +273,67 @@ impl PixivAgent {
 		}
 	}
 
-	fn cache_articles(&self) {
-		log::debug!("Caching Pixiv articles...");
+	fn cache_articles(&mut self) {
+		let articles = &self.articles;
+		self.cached.retain(|id, _| articles.contains_key(id));
+
+		let mut changed = Vec::new();
+		for (id, a) in articles {
+			let borrow = a.borrow();
+			let data = serde_json::to_value(PixivArticleCached::from(&borrow)).unwrap();
+			if self.cached.get(id) != Some(&data) {
+				self.cached.insert(*id, data.clone());
+				changed.push(CachedArticle::new(
+					SERVICE_INFO.name,
+					id.to_string(),
+					borrow.creation_time.get_time(),
+					data,
+				));
+			}
+		}
 
-		cache_articles(SERVICE_INFO.name, self.articles.iter()
-			.map(|(id, a)| (id.to_string(), serde_json::to_value(PixivArticleCached::from(&a.borrow())).unwrap()))
-			.collect());
+		if !changed.is_empty() {
+			log::debug!("Caching {} Pixiv articles...", changed.len());
+			self.article_cache.send(ArticleCacheRequest::Cache(changed));
+		}
+	}
+
+	/// Completes the articles parsed without their cache entries
+	fn hydrate_articles(&mut self, cached: HashMap<String, serde_json::Value>) {
+		let mut hydrated = Vec::new();
+		for (id, json) in cached {
+			let article = match id.parse::<u32>().ok().and_then(|id| self.articles.get(&id)) {
+				Some(article) => article,
+				None => continue,
+			};
+			let mut borrow = article.borrow_mut();
+			if borrow.is_fully_fetched {
+				continue;
+			}
+
+			if let Ok(cached) = serde_json::from_value::<PixivArticleCached>(json.clone()) {
+				self.cached.insert(borrow.id, json);
+				borrow.media = cached.media;
+				borrow.is_fully_fetched = true;
+				hydrated.push(Rc::downgrade(article) as ArticleWeak);
+			}
+		}
+
+		if !hydrated.is_empty() {
+			log::debug!("Hydrated {} Pixiv articles from the cache", hydrated.len());
+			self.actions_agent.send(ArticleActionsRequest::RedrawTimelines(hydrated));
+		}
 	}
 }
 
 //TODO Stop using RatelimitedResult
-async fn fetch_posts(url: &str, storage: &ServiceStorage) -> RatelimitedResult<Vec<ArticleRc<PixivArticleData>>> {
+async fn fetch_posts(url: &str, storage: ServiceStorage) -> RatelimitedResult<Vec<ArticleRc<PixivArticleData>>> {
+	let posts = fetch_parsed_posts(url).await?;
+	Ok((build_posts(posts, storage).await, None))
+}
+
+/// Fetches and parses the posts, without building the articles
+pub async fn fetch_parsed_posts(url: &str) -> Result<Vec<(FollowAPIIllust, RawJson)>> {
 	let response = reqwest::Client::builder()
 		//.timeout(Duration::from_secs(10))
 		.build()?
@@ -223,16 +347,28 @@ async fn fetch_posts(url: &str, storage: &ServiceStorage) -> RatelimitedResult<V
 	if parsed.error {
 		Err(parsed.message.into())
 	} else {
-		Ok((parsed.body.thumbnails.illust
-				.iter().zip(response["body"]["thumbnails"]["illust"].as_array().unwrap())
-				.map(|(a, raw_json)| PixivArticleData::from((raw_json.clone(), a, storage)))
-				.map(|p| Rc::new(RefCell::new(p)))
-				.collect(),
-			None))
+		Ok(parsed.body.thumbnails.illust
+			.into_iter().zip(response["body"]["thumbnails"]["illust"].as_array().unwrap())
+			.map(|(a, raw_json)| (a, raw_json.clone().into()))
+			.collect())
 	}
 }
 
-async fn fetch_post(url: &str, storage: &ServiceStorage) -> RatelimitedResult<ArticleRc<PixivArticleData>> {
+async fn build_posts(posts: Vec<(FollowAPIIllust, RawJson)>, mut storage: ServiceStorage) -> Vec<ArticleRc<PixivArticleData>> {
+	storage.session.cached_articles = cached_articles(SERVICE_INFO.name, posts.iter().map(|(a, _)| a.id.clone())).await;
+	posts.into_iter()
+		.map(|(a, raw_json)| PixivArticleData::from((raw_json, &a, &storage)))
+		.map(|p| Rc::new(RefCell::new(p)))
+		.collect()
+}
+
+async fn fetch_post(url: &str, storage: ServiceStorage) -> RatelimitedResult<ArticleRc<PixivArticleData>> {
+	let (post, raw_json) = fetch_parsed_post(url).await?;
+	Ok((build_post(post, raw_json, storage).await, None))
+}
+
+/// Fetches and parses the post, without building the article
+pub async fn fetch_parsed_post(url: &str) -> Result<(FullPostAPI, RawJson)> {
 	let response = reqwest::Client::builder()
 		//.timeout(Duration::from_secs(10))
 		.build()?
@@ -243,5 +379,10 @@ async fn fetch_post(url: &str, storage: &ServiceStorage) -> RatelimitedResult<Ar
 
 	let response: serde_json::Value = serde_json::from_str(&json_str)?;
 	let parsed: APIPayload<FullPostAPI> = serde_json::from_value(response.clone())?;
-	Ok((Rc::new(RefCell::new(PixivArticleData::from((response["body"].clone(), parsed.body, storage)))), None))
-}
\ No newline at end of file
+	Ok((parsed.body, response["body"].clone().into()))
+}
+
+async fn build_post(post: FullPostAPI, raw_json: RawJson, mut storage: ServiceStorage) -> ArticleRc<PixivArticleData> {
+	storage.session.cached_articles = cached_articles(SERVICE_INFO.name, [post.id.clone()]).await;
+	Rc::new(RefCell::new(PixivArticleData::from((raw_json, post, &storage))))
+}
@@ -0,0 +1,60 @@
+use serde::{Serialize, Deserialize};
+
+/// Auto refresh interval over a range of local hours
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleRule {
+	/// Inclusive, from 0 to 23
+	pub start_hour: u8,
+	/// Exclusive, wraps past midnight when before `start_hour`
+	pub end_hour: u8,
+	/// None pauses auto refresh, for quiet hours
+	pub interval: Option<u32>,
+}
+
+impl ScheduleRule {
+	pub fn contains(&self, hour: u8) -> bool {
+		if self.start_hour <= self.end_hour {
+			self.start_hour <= hour && hour < self.end_hour
+		} else {
+			self.start_hour <= hour || hour < self.end_hour
+		}
+	}
+}
+
+impl Default for ScheduleRule {
+	fn default() -> Self {
+		Self {
+			start_hour: 9,
+			end_hour: 18,
+			interval: Some(120_000),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshSchedule {
+	#[serde(default)]
+	pub rules: Vec<ScheduleRule>,
+	/// Skips auto refreshes while the tab is hidden, catching up once visible
+	#[serde(default)]
+	pub pause_when_hidden: bool,
+}
+
+impl RefreshSchedule {
+	/// The first rule containing the hour wins, otherwise `default` is used.
+	/// Returns None during quiet hours.
+	pub fn interval_at(&self, hour: u8, default: u32) -> Option<u32> {
+		match self.rules.iter().find(|rule| rule.contains(hour)) {
+			Some(rule) => rule.interval,
+			None => Some(default),
+		}
+	}
+
+	pub fn current_interval(&self, default: u32) -> Option<u32> {
+		self.interval_at(js_sys::Date::new_0().get_hours() as u8, default)
+	}
+
+	pub fn is_default(&self) -> bool {
+		self == &Self::default()
+	}
+}
@@ -1,34 +1,95 @@
 use gloo_storage::Storage;
+use gloo_storage::errors::StorageError;
+use yew_agent::Dispatched;
 use std::collections::{HashMap, HashSet};
-use serde::{Serialize, Deserialize};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
 use serde_json::Value;
 
 use crate::DisplayMode;
-
-#[derive(Serialize, Deserialize, Clone, Default)]
+use crate::error::{Error, Result};
+use crate::log_warn;
+use crate::notifications::{Notification, NotificationAgent, NotificationRequest};
+use crate::services::mute::MuteList;
+use crate::timeline::agent::SoshalWorkspaceStorage;
+use crate::timeline::filter_presets::FilterPreset;
+
+/// Marks older than this are dropped
+const READ_RETENTION: f64 = 180.0 * 86_400_000.0;
+/// Read marks kept per service, dropping the oldest
+const MAX_READ_ARTICLES: usize = 10_000;
+
+#[derive(Clone, Default)]
 pub struct SessionStorageService {
-	pub articles_marked_as_read: HashSet<String>,
+	/// Filled by the fetching code from the IndexedDB article cache, for the fetched ids only
 	pub cached_articles: HashMap<String, Value>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
-pub struct SoshalSessionStorage {
-	pub services: HashMap<String, SessionStorageService>,
+/// Reads a blob, defaulting when missing but failing on a corrupt one rather than overwriting it later
+pub fn read_storage<T: DeserializeOwned + Default>(key: &str) -> Result<T> {
+	match gloo_storage::LocalStorage::get(key) {
+		Ok(value) => Ok(value),
+		Err(StorageError::KeyNotFound(_)) => Ok(T::default()),
+		Err(err) => Err(Error::from(err).with_message(&format!("Couldn't read \"{}\" from the local storage", key))),
+	}
+}
+
+fn get_local_storage() -> Result<SoshalLocalStorage> {
+	read_storage("SoshalThingYew")
+}
+
+pub(crate) fn notify_storage_error(err: Error) {
+	log::error!("{}", &err);
+	NotificationAgent::dispatcher().send(NotificationRequest::Notify(Some("LocalStorage".to_owned()), Notification::Error(err)));
 }
 
 pub fn get_service_storage(service: &str) -> ServiceStorage {
-	let local: SoshalLocalStorage = gloo_storage::LocalStorage::get("SoshalThingYew").unwrap_or_default();
-	let session: SoshalSessionStorage = gloo_storage::SessionStorage::get("SoshalThingYew").unwrap_or_default();
+	//Defaults are only read, never written back
+	let local = get_local_storage().unwrap_or_else(|err| {
+		notify_storage_error(err);
+		SoshalLocalStorage::default()
+	});
 
 	ServiceStorage {
 		local: local.services.get(service).cloned().unwrap_or_default(),
-		session: session.services.get(service).cloned().unwrap_or_default(),
+		session: SessionStorageService::default(),
 	}
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReadMark {
+	/// When it was marked as read, for retention
+	pub marked: f64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct LocalStorageService {
 	pub hidden_articles: HashSet<String>,
+	#[serde(default)]
+	pub read_articles: HashMap<String, ReadMark>,
+	/// Articles created before this timestamp count as read
+	#[serde(default)]
+	pub read_watermark: Option<f64>,
+}
+
+impl LocalStorageService {
+	pub fn is_marked_as_read(&self, id: &str, creation_time: f64) -> bool {
+		self.read_articles.contains_key(id) ||
+			self.read_watermark.map(|watermark| creation_time < watermark).unwrap_or_default()
+	}
+
+	/// Drops marks past retention, then the oldest above the cap
+	///
+	/// Marks covered by the watermark are kept, so clearing or moving it back doesn't unmark them.
+	pub fn prune_read_articles(&mut self, now: f64) {
+		self.read_articles.retain(|_, mark| now - mark.marked < READ_RETENTION);
+
+		if self.read_articles.len() > MAX_READ_ARTICLES {
+			let mut marked: Vec<f64> = self.read_articles.values().map(|mark| mark.marked).collect();
+			marked.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+			let oldest_kept = marked[MAX_READ_ARTICLES - 1];
+			self.read_articles.retain(|_, mark| mark.marked >= oldest_kept);
+		}
+	}
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -43,137 +104,107 @@ pub struct ServiceStorage {
 	pub session: SessionStorageService,
 }
 
-pub fn cache_articles(service_name: &str, articles: HashMap<String, serde_json::Value>) {
-	let session_storage: SoshalSessionStorage = match gloo_storage::SessionStorage::get("SoshalThingYew") {
-		Ok(storage) => {
-			let mut session_storage: SoshalSessionStorage = storage;
-			let service = match session_storage.services.get_mut(service_name) {
-				Some(service) => service,
-				None => {
-					let service = SessionStorageService {
-						articles_marked_as_read: HashSet::new(),
-						cached_articles: HashMap::new(),
-					};
-					session_storage.services.insert(service_name.to_owned(), service);
-					session_storage.services.get_mut(service_name).unwrap()
-				}
-			};
-
-			for (id, article) in &articles {
-				let _ = service.cached_articles.insert(id.to_string(), article.clone());
-			}
-
-			session_storage
-		}
-		Err(_err) => {
-			SoshalSessionStorage {
-				services: HashMap::from([
-					(service_name.to_owned(), SessionStorageService {
-						articles_marked_as_read: HashSet::new(),
-						cached_articles: articles.iter()
-							.map(|(id, a)| (id.to_string(), a.clone()))
-							.collect(),
-					})
-				])
-			}
+fn update_local_service(service_name: &str, update: impl FnOnce(&mut LocalStorageService)) {
+	let mut local_storage = match get_local_storage() {
+		Ok(local_storage) => local_storage,
+		Err(err) => {
+			notify_storage_error(err);
+			return;
 		}
 	};
+	let service = local_storage.services.entry(service_name.to_owned()).or_default();
+	update(service);
+	service.prune_read_articles(js_sys::Date::now());
 
-	gloo_storage::SessionStorage::set("SoshalThingYew", &session_storage)
-		.expect("couldn't write session storage");
+	if let Err(err) = gloo_storage::LocalStorage::set("SoshalThingYew", &local_storage) {
+		notify_storage_error(Error::from(err).with_message("Couldn't write the local storage"));
+	}
 }
 
-pub fn mark_article_as_read(service_name: &str, id: String, value: bool) {
-	let session_storage: SoshalSessionStorage = match gloo_storage::SessionStorage::get("SoshalThingYew") {
-		Ok(storage) => {
-			let mut session_storage: SoshalSessionStorage = storage;
-			(match session_storage.services.get_mut(service_name) {
-				Some(service) => Some(service),
-				None => {
-					let service = SessionStorageService {
-						articles_marked_as_read: HashSet::new(),
-						cached_articles: HashMap::new(),
-					};
-					session_storage.services.insert(service_name.to_owned(), service);
-					session_storage.services.get_mut(service_name)
-				}
-			})
-				.map(|s| &mut s.articles_marked_as_read).
-				map(|cached| if value {
-					cached.insert(id);
-				}else {
-					cached.remove(&id);
-				});
-
-			session_storage
-		},
-		Err(_err) => {
-			SoshalSessionStorage {
-				services: HashMap::from([
-					(service_name.to_owned(), SessionStorageService {
-						articles_marked_as_read: match value {
-							true => {
-								let mut set = HashSet::new();
-								set.insert(id);
-								set
-							},
-							false => HashSet::new(),
-						},
-						cached_articles: HashMap::new(),
-					})
-				])
-			}
+/// Marks or unmarks each article id, in a single write
+pub fn mark_articles_as_read(service_name: &str, marks: Vec<(String, bool)>) {
+	let now = js_sys::Date::now();
+	update_local_service(service_name, |service| for (id, value) in marks {
+		if value {
+			service.read_articles.insert(id, ReadMark { marked: now });
+		} else {
+			service.read_articles.remove(&id);
 		}
-	};
+	});
+}
 
-	gloo_storage::SessionStorage::set("SoshalThingYew", &session_storage)
-		.expect("couldn't write session storage");
+pub fn get_read_watermarks() -> HashMap<String, f64> {
+	let local_storage: SoshalLocalStorage = gloo_storage::LocalStorage::get("SoshalThingYew").unwrap_or_default();
+	local_storage.services.into_iter()
+		.filter_map(|(service, storage)| storage.read_watermark.map(|watermark| (service, watermark)))
+		.collect()
+}
+
+pub fn set_read_watermark(service_name: &str, watermark: Option<f64>) {
+	update_local_service(service_name, |service| service.read_watermark = watermark);
 }
 
 pub fn hide_article(service_name: &str, id: String, value: bool) {
-	let session_storage: SoshalLocalStorage = match gloo_storage::LocalStorage::get("SoshalThingYew") {
-		Ok(storage) => {
-			let mut session_storage: SoshalLocalStorage = storage;
-			(match session_storage.services.get_mut(service_name) {
-				Some(service) => Some(service),
-				None => {
-					let service = LocalStorageService {
-						hidden_articles: HashSet::new(),
-					};
-					session_storage.services.insert(service_name.to_owned(), service);
-					session_storage.services.get_mut(service_name)
-				}
-			})
-				.map(|s| &mut s.hidden_articles).
-				map(|cached| if value {
-					cached.insert(id);
-				}else {
-					cached.remove(&id);
-				});
-
-			session_storage
-		},
-		Err(_err) => {
-			SoshalLocalStorage {
-				services: HashMap::from([
-					(service_name.to_owned(), LocalStorageService {
-						hidden_articles: match value {
-							true => {
-								let mut set = HashSet::new();
-								set.insert(id);
-								set
-							},
-							false => HashSet::new(),
-						},
-					})
-				]),
-				display_mode: DisplayMode::Default,
-			}
-		}
-	};
+	update_local_service(service_name, |service| if value {
+		service.hidden_articles.insert(id);
+	} else {
+		service.hidden_articles.remove(&id);
+	});
+}
 
-	gloo_storage::LocalStorage::set("SoshalThingYew", &session_storage)
-		.expect("couldn't write session storage");
+pub fn get_article_caps() -> Result<HashMap<String, usize>> {
+	read_storage("SoshalThingYew Article Caps")
+}
+
+pub fn set_article_caps(caps: &HashMap<String, usize>) {
+	gloo_storage::LocalStorage::set("SoshalThingYew Article Caps", caps)
+		.expect("couldn't write local storage");
+}
+
+pub fn get_fetch_in_worker() -> bool {
+	gloo_storage::LocalStorage::get("SoshalThingYew Fetch In Worker").unwrap_or_default()
+}
+
+pub fn set_fetch_in_worker(value: bool) {
+	gloo_storage::LocalStorage::set("SoshalThingYew Fetch In Worker", value)
+		.expect("couldn't write local storage");
+}
+
+pub fn get_mute_list() -> Result<MuteList> {
+	read_storage("SoshalThingYew Mute List")
+}
+
+pub fn set_mute_list(mute_list: &MuteList) {
+	gloo_storage::LocalStorage::set("SoshalThingYew Mute List", mute_list)
+		.expect("couldn't write local storage");
+}
+
+pub fn get_filter_presets() -> Result<Vec<FilterPreset>> {
+	read_storage("SoshalThingYew Filter Presets")
+}
+
+pub fn set_filter_presets(presets: &[FilterPreset]) {
+	gloo_storage::LocalStorage::set("SoshalThingYew Filter Presets", presets)
+		.expect("couldn't write local storage");
+}
+
+pub fn get_workspaces() -> Result<Vec<SoshalWorkspaceStorage>> {
+	read_storage("SoshalThingYew Workspaces")
+}
+
+pub fn set_workspaces(workspaces: &[SoshalWorkspaceStorage]) -> Result<()> {
+	gloo_storage::LocalStorage::set("SoshalThingYew Workspaces", workspaces)
+		.map_err(|err| Error::from(err).with_message("Couldn't write the workspaces"))
+}
+
+/// Name of the workspace loaded on startup, None for the default "Timelines" storage
+pub fn get_current_workspace() -> Option<String> {
+	gloo_storage::LocalStorage::get("SoshalThingYew Current Workspace").unwrap_or_default()
+}
+
+pub fn set_current_workspace(workspace: Option<&str>) {
+	gloo_storage::LocalStorage::set("SoshalThingYew Current Workspace", workspace)
+		.expect("couldn't write local storage");
 }
 
 pub fn update_favviewer_settings(settings: DisplayMode) {
@@ -184,10 +215,14 @@ pub fn update_favviewer_settings(settings: DisplayMode) {
 pub fn get_or_init_favviewer_settings(settings: DisplayMode) -> DisplayMode {
 	match gloo_storage::LocalStorage::get("SoshalThingYew FavViewer") {
 		Ok(storage) => storage,
-		Err(_) => {
+		Err(StorageError::KeyNotFound(_)) => {
 			gloo_storage::LocalStorage::set("SoshalThingYew FavViewer", &settings)
 				.expect("couldn't write session storage");
 			settings
 		},
+		Err(err) => {
+			log_warn!("Couldn't read the FavViewer settings, using the defaults", err);
+			settings
+		}
 	}
 }
\ No newline at end of file
@@ -3,12 +3,12 @@ use std::cell::{RefCell, Ref};
 use js_sys::Date;
 use wasm_bindgen::JsValue;
 use std::num::{NonZeroU32, NonZeroU16};
-use serde::Deserialize;
+use serde::{Serialize, Deserialize};
 use yew::prelude::*;
 use derivative::Derivative;
 
 use super::SERVICE_INFO;
-use crate::articles::{ArticleData, ArticleMedia, MediaType, MediaQueueInfo, ArticleRefType, ValidRatio, ArticleWeak, ArticleRc, ArticleBox, UnfetchedArticleRef};
+use crate::articles::{ArticleData, ArticleMedia, MediaType, MediaQueueInfo, ArticleRefType, ValidRatio, ArticleWeak, ArticleRc, ArticleBox, RawJson, UnfetchedArticleRef};
 use crate::services::storages::ServiceStorage;
 
 #[derive(Clone, Derivative)]
@@ -24,7 +24,7 @@ pub struct TweetArticleData {
 	pub retweet_count: u32,
 	pub media: Vec<ArticleMedia>,
 	#[derivative(Debug = "ignore")]
-	pub raw_json: serde_json::Value,
+	pub raw_json: RawJson,
 	pub referenced_articles: Vec<ArticleRefType<Weak<RefCell<TweetArticleData>>>>,
 	actual_article_index: Option<usize>,
 	pub marked_as_read: bool,
@@ -32,6 +32,8 @@ pub struct TweetArticleData {
 	#[derivative(Debug = "ignore")]
 	pub text_html: Html,
 	reply_info: Option<ReplyInfo>,
+	hashtags: Vec<String>,
+	links: Vec<String>,
 }
 
 impl ArticleData for TweetArticleData {
@@ -79,7 +81,7 @@ impl ArticleData for TweetArticleData {
 	fn media(&self) -> Vec<ArticleMedia> {
 		self.media.clone()
 	}
-	fn json(&self) -> serde_json::Value { self.raw_json.clone() }
+	fn json(&self) -> serde_json::Value { self.raw_json.value() }
 	fn unfetched_references(&self) -> Vec<UnfetchedArticleRef> {
 		match &self.reply_info {
 			Some(reply_info) => vec![UnfetchedArticleRef::ReplyToUser(reply_info.screen_name.clone())],
@@ -112,6 +114,12 @@ impl ArticleData for TweetArticleData {
 			None => None,
 		}
 	}
+	fn hashtags(&self) -> Vec<String> {
+		self.hashtags.clone()
+	}
+	fn links(&self) -> Vec<String> {
+		self.links.clone()
+	}
 	fn url(&self) -> String {
 		format!("https://twitter.com/{}/status/{}", &self.author_username(), &self.id())
 	}
@@ -138,17 +146,81 @@ impl ArticleData for TweetArticleData {
 	}
 }
 
+/// Tweet parsed from the response, without the Rc graph and Html only the main thread can build
+#[derive(Serialize, Deserialize)]
+pub struct ParsedTweet {
+	pub id: u64,
+	pub created_at: String,
+	pub text: String,
+	entities: Entities,
+	/// Urls of the media, trimmed from the text
+	media_urls: Vec<String>,
+	pub media: Vec<ArticleMedia>,
+	pub author: TwitterUser,
+	pub liked: bool,
+	pub retweeted: bool,
+	pub like_count: u32,
+	pub retweet_count: u32,
+	reply_info: Option<ReplyInfo>,
+	pub retweeted_status: Option<Box<ParsedTweet>>,
+	pub quoted_status: Option<Box<ParsedTweet>>,
+	pub raw_json: RawJson,
+}
+
+impl ParsedTweet {
+	pub fn parse(json: &serde_json::Value) -> Self {
+		let nested = |key: &str| Some(&json[key])
+			.filter(|nested| !nested.is_null())
+			.map(|nested| Box::new(ParsedTweet::parse(nested)));
+
+		let reply_info = {
+			if let Some(reply_id) = json["in_reply_to_status_id"].as_u64() {
+				Some(ReplyInfo {
+					screen_name: json["in_reply_to_screen_name"].as_str().unwrap().to_owned(),
+					tweet_id: reply_id,
+				})
+			}else {
+				None
+			}
+		};
+
+		let extended_entities: Option<ExtendedEntities> = serde_json::from_value(json["extended_entities"].clone()).unwrap();
+
+		ParsedTweet {
+			id: json["id"].as_u64().unwrap(),
+			created_at: json["created_at"].as_str().unwrap().to_owned(),
+			text: match json["full_text"].as_str().or(json["text"].as_str()) {
+				Some(text) => text,
+				None => "",
+			}.to_owned(),
+			entities: serde_json::from_value(json["entities"].clone()).unwrap(),
+			media_urls: media_urls(&extended_entities),
+			media: parse_media(extended_entities.map(|e| e.media)),
+			author: TwitterUser {
+				username: json["user"]["screen_name"].as_str().unwrap().to_owned(),
+				name: json["user"]["name"].as_str().unwrap().to_owned(),
+				avatar_url: json["user"]["profile_image_url_https"].as_str().unwrap().to_owned(),
+			},
+			liked: json["favorited"].as_bool().unwrap_or_default(),
+			retweeted: json["retweeted"].as_bool().unwrap_or_default(),
+			like_count: json["favorite_count"].as_u64().unwrap() as u32,
+			retweet_count: json["retweet_count"].as_u64().unwrap() as u32,
+			reply_info,
+			retweeted_status: nested("retweeted_status"),
+			quoted_status: nested("quoted_status"),
+			raw_json: json.clone().into(),
+		}
+	}
+}
+
 impl TweetArticleData {
-	pub fn from(json: &serde_json::Value, storage: &ServiceStorage) -> (ArticleRc<Self>, Vec<StrongArticleRefType>, Option<usize>) {
-		let id = json["id"].as_u64().unwrap();
+	pub fn from(parsed: ParsedTweet, storage: &ServiceStorage) -> (ArticleRc<Self>, Vec<StrongArticleRefType>, Option<usize>) {
+		let id = parsed.id;
 
 		let mut referenced_articles: Vec<StrongArticleRefType> = Vec::new();
 		let actual_article_index = {
-			let referenced = &json["retweeted_status"];
-			let quoted = &json["quoted_status"];
-
-			if !referenced.is_null() {
-				let (parsed_rc, parsed_refs, parsed_actual) = TweetArticleData::from(&referenced.clone(), storage);
+			if let Some(referenced) = parsed.retweeted_status {
+				let (parsed_rc, parsed_refs, parsed_actual) = TweetArticleData::from(*referenced, storage);
 				match parsed_actual {
 					Some(i) => match &parsed_refs[i] {
 						ArticleRefType::Quote(parsed_ref) => referenced_articles.push(StrongArticleRefType::RepostedQuote(parsed_rc, parsed_ref.clone())),
@@ -157,8 +229,8 @@ impl TweetArticleData {
 					_ => referenced_articles.push(StrongArticleRefType::Reposted(parsed_rc)),
 				};
 				Some(0)
-			}else if !quoted.is_null() {
-				let (parsed_rc, parsed_refs, parsed_actual) = TweetArticleData::from(&quoted.clone(), storage);
+			}else if let Some(quoted) = parsed.quoted_status {
+				let (parsed_rc, parsed_refs, parsed_actual) = TweetArticleData::from(*quoted, storage);
 				match parsed_actual {
 					Some(i) => match &parsed_refs[i] {
 						ArticleRefType::Quote(parsed_ref) => {
@@ -175,41 +247,24 @@ impl TweetArticleData {
 			}
 		};
 
-		let reply_info = {
-			if let Some(reply_id) = json["in_reply_to_status_id"].as_u64() {
-				Some(ReplyInfo {
-					screen_name: json["in_reply_to_screen_name"].as_str().unwrap().to_owned(),
-					tweet_id: reply_id,
-				})
-			}else {
-				None
-			}
-		};
+		let hashtags = parsed.entities.hashtags.iter().map(|hashtag| hashtag.text.clone()).collect();
+		let links = parsed.entities.urls.iter().map(|url| url.expanded_url.clone()).collect();
+		let (text, text_html) = parse_text_trimming(parsed.text, parsed.entities, &parsed.media_urls);
 
-		let extended_entities: Option<ExtendedEntities> = serde_json::from_value(json["extended_entities"].clone()).unwrap();
-
-		let text = match json["full_text"].as_str().or(json["text"].as_str()) {
-			Some(text) => text,
-			None => "",
-		}.to_owned();
-
-		let (text, text_html) = parse_text(text, serde_json::from_value(json["entities"].clone()).unwrap(), &extended_entities);
+		let creation_time = Date::new(&JsValue::from_str(&parsed.created_at));
+		let marked_as_read = storage.local.is_marked_as_read(&id.to_string(), creation_time.get_time());
 
 		let data = Rc::new(RefCell::new(TweetArticleData {
 			id,
-			creation_time: json["created_at"].as_str().map(|datetime_str|Date::new(&JsValue::from_str(datetime_str))).unwrap(),
+			creation_time,
 			text,
-			author: TwitterUser {
-				username: json["user"]["screen_name"].as_str().unwrap().to_owned(),
-				name: json["user"]["name"].as_str().unwrap().to_owned(),
-				avatar_url: json["user"]["profile_image_url_https"].as_str().unwrap().to_owned(),
-			},
-			liked: json["favorited"].as_bool().unwrap_or_default(),
-			retweeted: json["retweeted"].as_bool().unwrap_or_default(),
-			like_count: json["favorite_count"].as_u64().unwrap() as u32,
-			retweet_count: json["retweet_count"].as_u64().unwrap() as u32,
-			media: parse_media(extended_entities.map(|e| e.media)),
-			raw_json: json.clone(),
+			author: parsed.author,
+			liked: parsed.liked,
+			retweeted: parsed.retweeted,
+			like_count: parsed.like_count,
+			retweet_count: parsed.retweet_count,
+			media: parsed.media,
+			raw_json: parsed.raw_json,
 			referenced_articles: referenced_articles.iter().map(|ref_article| match ref_article {
 				StrongArticleRefType::Reposted(a) => ArticleRefType::Reposted(Rc::downgrade(a)),
 				StrongArticleRefType::Quote(a) => ArticleRefType::Quote(Rc::downgrade(a)),
@@ -220,10 +275,12 @@ impl TweetArticleData {
 				StrongArticleRefType::Reply(a) => ArticleRefType::Reply(Rc::downgrade(a)),
 			}).collect(),
 			actual_article_index,
-			marked_as_read: storage.session.articles_marked_as_read.contains(&id.to_string()),
+			marked_as_read,
 			hidden: storage.local.hidden_articles.contains(&id.to_string()),
 			text_html,
-			reply_info,
+			reply_info: parsed.reply_info,
+			hashtags,
+			links,
 		}));
 		(data, referenced_articles, actual_article_index)
 	}
@@ -239,13 +296,13 @@ impl TweetArticleData {
 
 //TODO pub type Id = u64;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 struct ReplyInfo {
 	screen_name: String,
 	tweet_id: u64,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Entities {
 	hashtags: Vec<TweetHashtag>,
 	// media: Vec<>
@@ -254,7 +311,7 @@ pub struct Entities {
 	user_mentions: Vec<TweetMention>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct TweetUrl {
 	display_url: String,
 	expanded_url: String,
@@ -262,13 +319,13 @@ pub struct TweetUrl {
 	url: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct TweetHashtag {
 	indices: (usize, usize),
 	text: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct TweetMention {
 	indices: (usize, usize),
 	// id: u64,
@@ -358,7 +415,7 @@ pub struct VideoVariant {
 	url: String,
 }
 
-#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct TwitterUser {
 	pub username: String,
 	pub name: String,
@@ -404,8 +461,25 @@ fn parse_media(media: Option<Vec<TweetMedia>>) -> Vec<ArticleMedia> {
 	}
 }
 
+fn media_urls(extended_entities: &Option<ExtendedEntities>) -> Vec<String> {
+	extended_entities.iter()
+		.flat_map(|e| e.media.iter())
+		.map(|m| match m {
+			TweetMedia::Photo { url, .. } |
+			TweetMedia::AnimatedGif { url, .. } |
+			TweetMedia::Video { url, .. }
+			=> url.clone()
+		})
+		.collect()
+}
+
 pub fn parse_text(original: String, entities: Entities, extended_entities: &Option<ExtendedEntities>) -> (String, Html) {
-	match _parse_text(original.clone(), entities, extended_entities) {
+	parse_text_trimming(original, entities, &media_urls(extended_entities))
+}
+
+/// Parses the text, trimming the given media urls
+fn parse_text_trimming(original: String, entities: Entities, media_urls: &[String]) -> (String, Html) {
+	match _parse_text(original.clone(), entities, media_urls) {
 		Some(t) => t,
 		None => {
 			let original_c = original.clone();
@@ -414,21 +488,10 @@ pub fn parse_text(original: String, entities: Entities, extended_entities: &Opti
 	}
 }
 
-fn _parse_text(original: String, entities: Entities, extended_entities: &Option<ExtendedEntities>) -> Option<(String, Html)> {
+fn _parse_text(original: String, entities: Entities, media_urls: &[String]) -> Option<(String, Html)> {
 	let mut trimmed_text = html_escape::decode_html_entities(original.as_str()).to_string();
-	let medias_opt: Option<Vec<&String>> = extended_entities.as_ref().map(|e|
-			e.media.iter().map(|m| match m {
-				TweetMedia::Photo { url, .. } |
-				TweetMedia::AnimatedGif { url, .. } |
-				TweetMedia::Video { url, .. }
-				=> url
-			}).collect()
-		);
-
-	if let Some(medias) = medias_opt {
-		for media in medias {
-			trimmed_text = trimmed_text.replace(media, "");
-		}
+	for media in media_urls {
+		trimmed_text = trimmed_text.replace(media.as_str(), "");
 	}
 
 	let mut final_text = trimmed_text.clone();
@@ -1,11 +1,20 @@
 use reqwest::Url;
+use serde_json::json;
 use yew_agent::{Dispatched, Dispatcher};
 
 use super::{TwitterAgent, TwitterRequest, SERVICE_INFO};
 use crate::articles::ArticleWeak;
 use crate::base_url;
-use crate::services::{Endpoint, EndpointSerialized, RateLimit};
-use crate::services::endpoint_agent::{EndpointId, RefreshTime};
+use crate::services::{Endpoint, EndpointSerialized, RateLimit, Pagination};
+use crate::services::endpoint_agent::{EndpointId, RefreshRequest};
+
+/// since_id being exclusive, starts right below `id` so the batch overlaps unless there's a gap
+fn since_id_including(id: &str) -> String {
+	match id.parse::<u64>() {
+		Ok(id) => id.saturating_sub(1).to_string(),
+		Err(_) => id.to_owned(),
+	}
+}
 
 pub struct UserTimelineEndpoint {
 	id: EndpointId,
@@ -15,6 +24,7 @@ pub struct UserTimelineEndpoint {
 	articles: Vec<ArticleWeak>,
 	agent: Dispatcher<TwitterAgent>,
 	ratelimit: RateLimit,
+	pagination: Pagination,
 }
 
 impl UserTimelineEndpoint {
@@ -27,6 +37,7 @@ impl UserTimelineEndpoint {
 			articles: Vec::new(),
 			agent: TwitterAgent::dispatcher(),
 			ratelimit: RateLimit::default(),
+			pagination: Pagination::with_gaps(),
 		}
 	}
 
@@ -66,24 +77,62 @@ impl Endpoint for UserTimelineEndpoint {
 		self.ratelimit = ratelimit
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn pagination(&self) -> Option<&Pagination> {
+		Some(&self.pagination)
+	}
+
+	fn get_mut_pagination(&mut self) -> Option<&mut Pagination> {
+		Some(&mut self.pagination)
+	}
+
+	fn ratelimit_bucket(&self) -> Option<String> {
+		Some(format!("{}/statuses/user_timeline", SERVICE_INFO.name))
+	}
+
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		self.agent.send(TwitterRequest::FetchTweets(
-			refresh_time,
+			refresh,
 			self.id,
 			Url::parse(&format!("{}/proxy/twitter/user/{}?replies={}&rts={}&count=20", base_url(), self.username, &self.include_replies, &self.include_retweets)).unwrap(),
 		))
 	}
 
-	fn load_bottom(&mut self, refresh_time: RefreshTime) {
-		match self.articles.last() {
-			Some(last_id) => {
+	fn load_top(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.top {
+			Some(top) => {
+				self.agent.send(TwitterRequest::FetchTweets(
+					refresh,
+					self.id,
+					Url::parse(&format!("{}/proxy/twitter/user/{}?replies={}&rts={}&since_id={}", base_url(), &self.username, &self.include_replies, &self.include_retweets, since_id_including(top))).unwrap(),
+				))
+			}
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn load_bottom(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.bottom {
+			Some(bottom) => {
+				self.agent.send(TwitterRequest::FetchTweets(
+					refresh,
+					self.id,
+					Url::parse(&format!("{}/proxy/twitter/user/{}?replies={}&rts={}&max_id={}", base_url(), &self.username, &self.include_replies, &self.include_retweets, bottom)).unwrap(),
+				))
+			}
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn fill_gap(&mut self, refresh: RefreshRequest) {
+		match self.pagination.filling_gap() {
+			Some(gap) => {
 				self.agent.send(TwitterRequest::FetchTweets(
-					refresh_time,
+					refresh,
 					self.id,
-					Url::parse(&format!("{}/proxy/twitter/user/{}?replies={}&rts={}&max_id={}", base_url(), &self.username, &self.include_replies, &self.include_retweets, &last_id.upgrade().unwrap().borrow().id())).unwrap(),
+					Url::parse(&format!("{}/proxy/twitter/user/{}?replies={}&rts={}&since_id={}&max_id={}", base_url(), &self.username, &self.include_replies, &self.include_retweets, since_id_including(&gap.older), &gap.newer)).unwrap(),
 				))
 			}
-			None => self.refresh(refresh_time)
+			None => self.refresh(refresh)
 		}
 	}
 
@@ -95,6 +144,14 @@ impl Endpoint for UserTimelineEndpoint {
 				.map(|u| u == self.username)
 				.unwrap_or_default()
 	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 1, json!({
+			"username": self.username,
+			"include_retweets": self.include_retweets,
+			"include_replies": self.include_replies,
+		})))
+	}
 }
 
 pub struct HomeTimelineEndpoint {
@@ -102,6 +159,7 @@ pub struct HomeTimelineEndpoint {
 	articles: Vec<ArticleWeak>,
 	agent: Dispatcher<TwitterAgent>,
 	ratelimit: RateLimit,
+	pagination: Pagination,
 }
 
 impl HomeTimelineEndpoint {
@@ -111,6 +169,7 @@ impl HomeTimelineEndpoint {
 			articles: Vec::new(),
 			agent: TwitterAgent::dispatcher(),
 			ratelimit: RateLimit::default(),
+			pagination: Pagination::with_gaps(),
 		}
 	}
 }
@@ -140,24 +199,62 @@ impl Endpoint for HomeTimelineEndpoint {
 		self.ratelimit = ratelimit
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn pagination(&self) -> Option<&Pagination> {
+		Some(&self.pagination)
+	}
+
+	fn get_mut_pagination(&mut self) -> Option<&mut Pagination> {
+		Some(&mut self.pagination)
+	}
+
+	fn ratelimit_bucket(&self) -> Option<String> {
+		Some(format!("{}/statuses/home_timeline", SERVICE_INFO.name))
+	}
+
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		self.agent.send(TwitterRequest::FetchTweets(
-			refresh_time,
+			refresh,
 			self.id,
 			Url::parse(&format!("{}/proxy/twitter/home?count=20", base_url())).unwrap())
 		)
 	}
 
-	fn load_bottom(&mut self, refresh_time: RefreshTime) {
-		match self.articles.last() {
-			Some(last_id) => {
+	fn load_top(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.top {
+			Some(top) => {
+				self.agent.send(TwitterRequest::FetchTweets(
+					refresh,
+					self.id,
+					Url::parse(&format!("{}/proxy/twitter/home?since_id={}", base_url(), since_id_including(top))).unwrap(),
+				))
+			}
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn load_bottom(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.bottom {
+			Some(bottom) => {
+				self.agent.send(TwitterRequest::FetchTweets(
+					refresh,
+					self.id,
+					Url::parse(&format!("{}/proxy/twitter/home?max_id={}", base_url(), bottom)).unwrap(),
+				))
+			}
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn fill_gap(&mut self, refresh: RefreshRequest) {
+		match self.pagination.filling_gap() {
+			Some(gap) => {
 				self.agent.send(TwitterRequest::FetchTweets(
-					refresh_time,
+					refresh,
 					self.id,
-					Url::parse(&format!("{}/proxy/twitter/home?max_id={}", base_url(), &last_id.upgrade().unwrap().borrow().id())).unwrap(),
+					Url::parse(&format!("{}/proxy/twitter/home?since_id={}&max_id={}", base_url(), since_id_including(&gap.older), &gap.newer)).unwrap(),
 				))
 			}
-			None => self.refresh(refresh_time)
+			None => self.refresh(refresh)
 		}
 	}
 
@@ -165,6 +262,10 @@ impl Endpoint for HomeTimelineEndpoint {
 		storage.service == SERVICE_INFO.name &&
 			storage.endpoint_type == 0
 	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 0, json!({})))
+	}
 }
 
 pub struct ListEndpoint {
@@ -174,6 +275,7 @@ pub struct ListEndpoint {
 	articles: Vec<ArticleWeak>,
 	agent: Dispatcher<TwitterAgent>,
 	ratelimit: RateLimit,
+	pagination: Pagination,
 }
 
 impl ListEndpoint {
@@ -185,6 +287,7 @@ impl ListEndpoint {
 			articles: Vec::new(),
 			agent: TwitterAgent::dispatcher(),
 			ratelimit: RateLimit::default(),
+			pagination: Pagination::with_gaps(),
 		}
 	}
 
@@ -222,24 +325,62 @@ impl Endpoint for ListEndpoint {
 		self.ratelimit = ratelimit
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn pagination(&self) -> Option<&Pagination> {
+		Some(&self.pagination)
+	}
+
+	fn get_mut_pagination(&mut self) -> Option<&mut Pagination> {
+		Some(&mut self.pagination)
+	}
+
+	fn ratelimit_bucket(&self) -> Option<String> {
+		Some(format!("{}/lists/statuses", SERVICE_INFO.name))
+	}
+
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		self.agent.send(TwitterRequest::FetchTweets(
-			refresh_time,
+			refresh,
 			self.id,
 			Url::parse(&format!("{}/proxy/twitter/list/{}/{}", base_url(), &self.username, &self.slug)).unwrap(),
 		))
 	}
 
-	fn load_bottom(&mut self, refresh_time: RefreshTime) {
-		match self.articles.last() {
-			Some(last_id) => {
+	fn load_top(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.top {
+			Some(top) => {
+				self.agent.send(TwitterRequest::FetchTweets(
+					refresh,
+					self.id,
+					Url::parse(&format!("{}/proxy/twitter/list/{}/{}?since_id={}", base_url(), &self.username, &self.slug, since_id_including(top))).unwrap(),
+				))
+			}
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn load_bottom(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.bottom {
+			Some(bottom) => {
 				self.agent.send(TwitterRequest::FetchTweets(
-					refresh_time,
+					refresh,
 					self.id,
-					Url::parse(&format!("{}/proxy/twitter/list/{}/{}?max_id={}", base_url(), &self.username, &self.slug, &last_id.upgrade().unwrap().borrow().id())).unwrap(),
+					Url::parse(&format!("{}/proxy/twitter/list/{}/{}?max_id={}", base_url(), &self.username, &self.slug, bottom)).unwrap(),
 				))
 			}
-			None => self.refresh(refresh_time)
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn fill_gap(&mut self, refresh: RefreshRequest) {
+		match self.pagination.filling_gap() {
+			Some(gap) => {
+				self.agent.send(TwitterRequest::FetchTweets(
+					refresh,
+					self.id,
+					Url::parse(&format!("{}/proxy/twitter/list/{}/{}?since_id={}&max_id={}", base_url(), &self.username, &self.slug, since_id_including(&gap.older), &gap.newer)).unwrap(),
+				))
+			}
+			None => self.refresh(refresh)
 		}
 	}
 
@@ -255,6 +396,13 @@ impl Endpoint for ListEndpoint {
 				.map(|s| s == self.slug)
 				.unwrap_or_default()
 	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 2, json!({
+			"username": self.username,
+			"slug": self.slug,
+		})))
+	}
 }
 
 pub struct LikesEndpoint {
@@ -263,6 +411,7 @@ pub struct LikesEndpoint {
 	articles: Vec<ArticleWeak>,
 	agent: Dispatcher<TwitterAgent>,
 	ratelimit: RateLimit,
+	pagination: Pagination,
 }
 
 impl LikesEndpoint {
@@ -273,6 +422,7 @@ impl LikesEndpoint {
 			articles: Vec::new(),
 			agent: TwitterAgent::dispatcher(),
 			ratelimit: RateLimit::default(),
+			pagination: Pagination::with_gaps(),
 		}
 	}
 
@@ -309,24 +459,62 @@ impl Endpoint for LikesEndpoint {
 		self.ratelimit = ratelimit
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn pagination(&self) -> Option<&Pagination> {
+		Some(&self.pagination)
+	}
+
+	fn get_mut_pagination(&mut self) -> Option<&mut Pagination> {
+		Some(&mut self.pagination)
+	}
+
+	fn ratelimit_bucket(&self) -> Option<String> {
+		Some(format!("{}/favorites/list", SERVICE_INFO.name))
+	}
+
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		self.agent.send(TwitterRequest::FetchTweets(
-			refresh_time,
+			refresh,
 			self.id,
 			Url::parse(&format!("{}/proxy/twitter/likes/{}?count=20", base_url(), &self.username)).unwrap(),
 		))
 	}
 
-	fn load_bottom(&mut self, refresh_time: RefreshTime) {
-		match self.articles.last() {
-			Some(last_id) => {
+	fn load_top(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.top {
+			Some(top) => {
 				self.agent.send(TwitterRequest::FetchTweets(
-					refresh_time,
+					refresh,
 					self.id,
-					Url::parse(&format!("{}/proxy/twitter/likes/{}?max_id={}", base_url(), &self.username, &last_id.upgrade().unwrap().borrow().id())).unwrap(),
+					Url::parse(&format!("{}/proxy/twitter/likes/{}?since_id={}", base_url(), &self.username, since_id_including(top))).unwrap(),
 				))
 			}
-			None => self.refresh(refresh_time)
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn load_bottom(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.bottom {
+			Some(bottom) => {
+				self.agent.send(TwitterRequest::FetchTweets(
+					refresh,
+					self.id,
+					Url::parse(&format!("{}/proxy/twitter/likes/{}?max_id={}", base_url(), &self.username, bottom)).unwrap(),
+				))
+			}
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn fill_gap(&mut self, refresh: RefreshRequest) {
+		match self.pagination.filling_gap() {
+			Some(gap) => {
+				self.agent.send(TwitterRequest::FetchTweets(
+					refresh,
+					self.id,
+					Url::parse(&format!("{}/proxy/twitter/likes/{}?since_id={}&max_id={}", base_url(), &self.username, since_id_including(&gap.older), &gap.newer)).unwrap(),
+				))
+			}
+			None => self.refresh(refresh)
 		}
 	}
 
@@ -338,6 +526,12 @@ impl Endpoint for LikesEndpoint {
 				.map(|u| u == self.username)
 				.unwrap_or_default()
 	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 3, json!({
+			"username": self.username,
+		})))
+	}
 }
 
 pub struct SingleTweetEndpoint {
@@ -392,9 +586,13 @@ impl Endpoint for SingleTweetEndpoint {
 		self.ratelimit = ratelimit
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn ratelimit_bucket(&self) -> Option<String> {
+		Some(format!("{}/statuses/show", SERVICE_INFO.name))
+	}
+
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		self.agent.send(TwitterRequest::FetchTweet(
-			refresh_time,
+			refresh,
 			self.id,
 			Url::parse(&format!("{}/proxy/twitter/status/{}", base_url(), &self.tweet_id)).unwrap(),
 		))
@@ -404,10 +602,17 @@ impl Endpoint for SingleTweetEndpoint {
 		storage.service == SERVICE_INFO.name &&
 			storage.endpoint_type == 4 &&
 			storage.params["id"]
-				.as_u64()
+				.as_str()
+				.and_then(|id| id.parse::<u64>().ok())
 				.map(|id| id == self.tweet_id)
 				.unwrap_or_default()
 	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 4, json!({
+			"id": self.tweet_id.to_string(),
+		})))
+	}
 }
 
 pub struct SearchEndpoint {
@@ -416,6 +621,7 @@ pub struct SearchEndpoint {
 	articles: Vec<ArticleWeak>,
 	agent: Dispatcher<TwitterAgent>,
 	ratelimit: RateLimit,
+	pagination: Pagination,
 }
 
 impl SearchEndpoint {
@@ -426,6 +632,7 @@ impl SearchEndpoint {
 			articles: Vec::new(),
 			agent: TwitterAgent::dispatcher(),
 			ratelimit: RateLimit::default(),
+			pagination: Pagination::with_gaps(),
 		}
 	}
 
@@ -462,31 +669,76 @@ impl Endpoint for SearchEndpoint {
 		self.ratelimit = ratelimit
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn pagination(&self) -> Option<&Pagination> {
+		Some(&self.pagination)
+	}
+
+	fn get_mut_pagination(&mut self) -> Option<&mut Pagination> {
+		Some(&mut self.pagination)
+	}
+
+	fn ratelimit_bucket(&self) -> Option<String> {
+		Some(format!("{}/search/tweets", SERVICE_INFO.name))
+	}
+
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		let mut url = Url::parse(&format!("{}/proxy/twitter/search", base_url())).unwrap();
 		url.set_query(Some(&format!("query={}", self.query)));
-		self.agent.send(TwitterRequest::FetchTweets(refresh_time, self.id, url))
+		self.agent.send(TwitterRequest::FetchTweets(refresh, self.id, url))
 	}
 
-	fn load_bottom(&mut self, refresh_time: RefreshTime) {
-		match self.articles.last() {
-			Some(last_id) => {
+	fn load_top(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.top {
+			Some(top) => {
 				let mut url = Url::parse(&format!("{}/proxy/twitter/search", base_url())).unwrap();
 				url.query_pairs_mut()
 					.append_pair("query", self.query.as_str())
-					.append_pair("max_id", &last_id.upgrade().unwrap().borrow().id());
-				self.agent.send(TwitterRequest::FetchTweets(refresh_time, self.id, url))
+					.append_pair("since_id", &since_id_including(top));
+				self.agent.send(TwitterRequest::FetchTweets(refresh, self.id, url))
 			}
-			None => self.refresh(refresh_time)
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn load_bottom(&mut self, refresh: RefreshRequest) {
+		match &self.pagination.bottom {
+			Some(bottom) => {
+				let mut url = Url::parse(&format!("{}/proxy/twitter/search", base_url())).unwrap();
+				url.query_pairs_mut()
+					.append_pair("query", self.query.as_str())
+					.append_pair("max_id", bottom);
+				self.agent.send(TwitterRequest::FetchTweets(refresh, self.id, url))
+			}
+			None => self.refresh(refresh)
+		}
+	}
+
+	fn fill_gap(&mut self, refresh: RefreshRequest) {
+		match self.pagination.filling_gap() {
+			Some(gap) => {
+				let mut url = Url::parse(&format!("{}/proxy/twitter/search", base_url())).unwrap();
+				url.query_pairs_mut()
+					.append_pair("query", self.query.as_str())
+					.append_pair("since_id", &since_id_including(&gap.older))
+					.append_pair("max_id", &gap.newer);
+				self.agent.send(TwitterRequest::FetchTweets(refresh, self.id, url))
+			}
+			None => self.refresh(refresh)
 		}
 	}
 
 	fn eq_storage(&self, storage: &EndpointSerialized) -> bool {
 		storage.service == SERVICE_INFO.name &&
-			storage.endpoint_type == 4 &&
+			storage.endpoint_type == 5 &&
 			storage.params["query"]
 				.as_str()
 				.map(|s| s == self.query)
 				.unwrap_or_default()
 	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 5, json!({
+			"query": self.query,
+		})))
+	}
 }
\ No newline at end of file
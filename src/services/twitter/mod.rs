@@ -1,6 +1,6 @@
 use yew::prelude::*;
 use std::rc::Rc;
-use yew_agent::{Agent, AgentLink, Context, HandlerId, Dispatched, Dispatcher};
+use yew_agent::{Agent, AgentLink, Context, HandlerId, Bridge, Bridged, Dispatched, Dispatcher};
 use std::collections::HashMap;
 use reqwest::{StatusCode, Url};
 
@@ -8,21 +8,29 @@ pub mod endpoints;
 pub mod article;
 
 pub use article::TweetArticleData;
-use article::StrongArticleRefType;
+use article::{ParsedTweet, StrongArticleRefType};
 use crate::articles::{ArticleRc, ArticleRefType, ArticleWeak};
 use crate::{base_url, SearchEndpoint};
 use crate::notifications::{Notification, NotificationAgent, NotificationRequest};
 use crate::services::{
 	service,
 	RateLimit,
-	endpoint_agent::{EndpointAgent, EndpointRequest, EndpointId, EndpointConstructor, EndpointConstructorCollection, RefreshTime},
+	endpoint_agent::{EndpointAgent, EndpointRequest, EndpointId, EndpointConstructor, EndpointConstructorCollection, RefreshRequest},
 	article_actions::{ArticleActionsAgent, ServiceActions, ArticleActionsRequest},
+	garbage_collector::{GarbageCollectorAgent, GarbageCollectorRequest, GarbageCollectorResponse},
+	fetch_worker::{FetchKind, FetchWorkerResponse, ParsedArticles, WorkerFetcher, unexpected_articles},
 	twitter::endpoints::*,
 };
 use crate::error::{Error, RatelimitedResult};
-use crate::services::storages::{get_service_storage, ServiceStorage};
+use crate::services::storages::{get_fetch_in_worker, get_service_storage, ServiceStorage};
 
 pub async fn fetch_tweets(url: Url, storage: &ServiceStorage) -> RatelimitedResult<Vec<(ArticleRc<TweetArticleData>, Vec<StrongArticleRefType>)>> {
+	let (tweets, ratelimit) = fetch_parsed_tweets(url.as_str()).await?;
+	Ok((build_tweets(tweets, storage), ratelimit))
+}
+
+/// Fetches and parses the tweets, without building the articles
+pub async fn fetch_parsed_tweets(url: &str) -> RatelimitedResult<Vec<ParsedTweet>> {
 	let response = reqwest::Client::builder()
 		//.timeout(Duration::from_secs(10))
 		.build()?
@@ -47,23 +55,42 @@ pub async fn fetch_tweets(url: Url, storage: &ServiceStorage) -> RatelimitedResu
 	serde_json::from_str(&json_str)
 		.map(|value: serde_json::Value|
 			(match value.as_array() {
-				Some(array) => array.iter().map(|json| TweetArticleData::from(json, storage)).map(|(rc, refs, _)| (rc, refs)).collect(),
-				None => {
-					let (rc, refs, _) = TweetArticleData::from(&value, storage);
-					vec![(rc, refs)]
-				},
+				Some(array) => array.iter().map(ParsedTweet::parse).collect(),
+				None => vec![ParsedTweet::parse(&value)],
 			},
 			 Some(ratelimit))
 		)
 		.map_err(|err| Error::from(err))
 }
 
+fn build_tweets(tweets: Vec<ParsedTweet>, storage: &ServiceStorage) -> Vec<(ArticleRc<TweetArticleData>, Vec<StrongArticleRefType>)> {
+	tweets.into_iter()
+		.map(|tweet| TweetArticleData::from(tweet, storage))
+		.map(|(rc, refs, _)| (rc, refs))
+		.collect()
+}
+
 #[derive(Debug)]
 enum AuthState {
 	NotLoggedIn,
 	LoggedIn(u64)
 }
 
+/// What a fetch was for, to route its response
+enum FetchContext {
+	Endpoint(RefreshRequest, EndpointId),
+	Action(HandlerId),
+}
+
+impl FetchContext {
+	fn into_msg(self, r: RatelimitedResult<Vec<(ArticleRc<TweetArticleData>, Vec<StrongArticleRefType>)>>) -> TwitterMsg {
+		match self {
+			FetchContext::Endpoint(refresh, id) => TwitterMsg::EndpointFetchResponse(refresh, id, r),
+			FetchContext::Action(id) => TwitterMsg::FetchResponse(id, r),
+		}
+	}
+}
+
 #[service("Twitter", TweetArticleData, u64)]
 pub struct TwitterAgent {
 	link: AgentLink<Self>,
@@ -72,20 +99,24 @@ pub struct TwitterAgent {
 	auth_state: AuthState,
 	sidebar_handler: Option<HandlerId>,
 	notification_agent: Dispatcher<NotificationAgent>,
+	garbage_collector: Box<dyn Bridge<GarbageCollectorAgent>>,
+	worker_fetcher: WorkerFetcher<FetchContext>,
 }
 
 pub enum TwitterMsg {
 	FetchResponse(HandlerId, RatelimitedResult<Vec<(ArticleRc<TweetArticleData>, Vec<StrongArticleRefType>)>>),
-	EndpointFetchResponse(RefreshTime, EndpointId, RatelimitedResult<Vec<(ArticleRc<TweetArticleData>, Vec<StrongArticleRefType>)>>),
+	EndpointFetchResponse(RefreshRequest, EndpointId, RatelimitedResult<Vec<(ArticleRc<TweetArticleData>, Vec<StrongArticleRefType>)>>),
 	Like(HandlerId, ArticleWeak),
 	Retweet(HandlerId, ArticleWeak),
+	GarbageCollectorResponse(GarbageCollectorResponse),
+	WorkerFetchResponse(FetchWorkerResponse),
 }
 
 pub enum TwitterRequest {
 	Auth(Option<String>),
 	Sidebar,
-	FetchTweets(RefreshTime, EndpointId, Url),
-	FetchTweet(RefreshTime, EndpointId, Url),
+	FetchTweets(RefreshRequest, EndpointId, Url),
+	FetchTweet(RefreshRequest, EndpointId, Url),
 }
 
 pub enum TwitterResponse {
@@ -157,6 +188,9 @@ impl Agent for TwitterAgent {
 			}
 		));
 
+		let mut garbage_collector = GarbageCollectorAgent::bridge(link.callback(Msg::GarbageCollectorResponse));
+		garbage_collector.send(GarbageCollectorRequest::RegisterService(SERVICE_INFO.name));
+
 		let mut actions_agent = ArticleActionsAgent::dispatcher();
 		actions_agent.send(ArticleActionsRequest::Init(SERVICE_INFO.name, ServiceActions {
 			like: Some(link.callback(|(id, article)| Msg::Like(id, article))),
@@ -164,6 +198,8 @@ impl Agent for TwitterAgent {
 			fetch_data: None,
 		}));
 
+		let worker_fetcher = WorkerFetcher::new(link.callback(Msg::WorkerFetchResponse));
+
 		Self {
 			endpoint_agent,
 			link,
@@ -172,12 +208,25 @@ impl Agent for TwitterAgent {
 			auth_state: AuthState::NotLoggedIn,
 			sidebar_handler: None,
 			notification_agent: NotificationAgent::dispatcher(),
+			garbage_collector,
+			worker_fetcher,
 		}
 	}
 
 	fn update(&mut self, msg: Self::Message) {
 		match msg {
-			Msg::EndpointFetchResponse(refresh_time, id, r) => {
+			Msg::GarbageCollectorResponse(response) => self.handle_garbage_collector_response(response),
+			Msg::WorkerFetchResponse(response) => {
+				if let Some((context, r)) = self.worker_fetcher.take(response) {
+					let storage = get_service_storage(SERVICE_INFO.name);
+					let r = r.and_then(|(parsed, ratelimit)| match parsed {
+						ParsedArticles::Tweets(tweets) => Ok((build_tweets(tweets, &storage), ratelimit)),
+						_ => Err(unexpected_articles()),
+					});
+					self.update(context.into_msg(r));
+				}
+			}
+			Msg::EndpointFetchResponse(refresh, id, r) => {
 
 				let r = match r {
 					Ok((articles, ratelimit)) => {
@@ -205,7 +254,7 @@ impl Agent for TwitterAgent {
 					}
 				};
 
-				self.endpoint_agent.send(EndpointRequest::EndpointFetchResponse(refresh_time, id, r));
+				self.endpoint_agent.send(EndpointRequest::EndpointFetchResponse(refresh, id, r));
 			}
 			Msg::FetchResponse(_id, r) => {
 				if let Ok((articles, _)) = r {
@@ -225,9 +274,7 @@ impl Agent for TwitterAgent {
 
 				let url = Url::parse(&format!("{}/proxy/twitter/{}/{}", base_url(), if borrow.liked() { "unlike" } else { "like" }, borrow.id())).unwrap();
 
-				self.link.send_future(async move {
-					Msg::FetchResponse(id, fetch_tweets(url, &get_service_storage(SERVICE_INFO.name)).await)
-				})
+				self.fetch(FetchContext::Action(id), url);
 			}
 			Msg::Retweet(id, article) => {
 				let strong = article.upgrade().unwrap();
@@ -235,9 +282,7 @@ impl Agent for TwitterAgent {
 
 				let url = Url::parse(&format!("{}/proxy/twitter/{}/{}", base_url(), if borrow.reposted() { "unretweet" } else { "retweet" }, borrow.id())).unwrap();
 
-				self.link.send_future(async move {
-					Msg::FetchResponse(id, fetch_tweets(url, &get_service_storage(SERVICE_INFO.name)).await)
-				})
+				self.fetch(FetchContext::Action(id), url);
 			}
 		};
 	}
@@ -258,14 +303,8 @@ impl Agent for TwitterAgent {
 				self.sidebar_handler = Some(id);
 				self.link.respond(id, Response::Sidebar(self.sidebar()));
 			},
-			Request::FetchTweets(refresh_time, id, url) =>
-				self.link.send_future(async move {
-					Msg::EndpointFetchResponse(refresh_time, id, fetch_tweets(url, &get_service_storage(SERVICE_INFO.name)).await)
-				}),
-			Request::FetchTweet(refresh_time, id, url) =>
-				self.link.send_future(async move {
-					Msg::EndpointFetchResponse(refresh_time, id, fetch_tweets(url, &get_service_storage(SERVICE_INFO.name)).await)
-				})
+			Request::FetchTweets(refresh, id, url) => self.fetch(FetchContext::Endpoint(refresh, id), url),
+			Request::FetchTweet(refresh, id, url) => self.fetch(FetchContext::Endpoint(refresh, id), url),
 		}
 	}
 
@@ -277,6 +316,16 @@ impl Agent for TwitterAgent {
 }
 
 impl TwitterAgent {
+	fn fetch(&mut self, context: FetchContext, url: Url) {
+		if get_fetch_in_worker() {
+			self.worker_fetcher.fetch(url.to_string(), FetchKind::Tweets, context);
+		} else {
+			self.link.send_future(async move {
+				context.into_msg(fetch_tweets(url, &get_service_storage(SERVICE_INFO.name)).await)
+			})
+		}
+	}
+
 	fn sidebar(&self) -> Html {
 		html! {
 			<div class="box">
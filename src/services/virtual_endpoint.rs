@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+use serde::{Serialize, Deserialize};
+use yew_agent::{Dispatched, Dispatcher};
+
+use crate::articles::ArticleWeak;
+use crate::services::{Endpoint, EndpointSerialized, ServiceInfo};
+use crate::services::endpoint_agent::{EndpointAgent, EndpointId, EndpointRequest, RefreshRequest};
+use crate::timeline::filters::FilterCollection;
+use crate::timeline::sort_methods::sort_by_id;
+
+/// Not an actual service, used to recognize serialized virtual endpoints
+pub const SERVICE_INFO: ServiceInfo = ServiceInfo {
+	name: "Virtual",
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompositionMode {
+	Union,
+	Intersection,
+	/// Articles of the first source missing from the others
+	Difference,
+}
+
+impl CompositionMode {
+	pub fn iter() -> impl Iterator<Item = CompositionMode> {
+		[CompositionMode::Union, CompositionMode::Intersection, CompositionMode::Difference].into_iter()
+	}
+}
+
+impl Default for CompositionMode {
+	fn default() -> Self {
+		CompositionMode::Union
+	}
+}
+
+/// How a virtual endpoint builds its articles out of other endpoints
+#[derive(Clone, PartialEq)]
+pub struct Composition {
+	pub mode: CompositionMode,
+	pub sources: Vec<EndpointId>,
+	pub filters: FilterCollection,
+}
+
+impl Composition {
+	/// From params with the sources already resolved to `source_ids`
+	pub fn from_json(params: &serde_json::Value) -> Self {
+		Self {
+			mode: serde_json::from_value(params["mode"].clone()).unwrap_or_default(),
+			sources: serde_json::from_value(params["source_ids"].clone()).unwrap_or_default(),
+			filters: serde_json::from_value(params["filters"].clone()).unwrap_or_else(|_| FilterCollection::new()),
+		}
+	}
+
+	/// `sources` being the articles of each source, in the same order
+	pub fn combine(&self, sources: Vec<Vec<ArticleWeak>>) -> Vec<ArticleWeak> {
+		let mut sources = sources.into_iter();
+		let first = sources.next().unwrap_or_default();
+
+		let mut combined: Vec<ArticleWeak> = match self.mode {
+			CompositionMode::Union => {
+				let mut seen = HashSet::new();
+				first.into_iter()
+					.chain(sources.flatten())
+					.filter(|article| seen.insert(article_ptr(article)))
+					.collect()
+			}
+			CompositionMode::Intersection | CompositionMode::Difference => {
+				let others: Vec<HashSet<*const ()>> = sources
+					.map(|articles| articles.iter().map(article_ptr).collect())
+					.collect();
+				let keep_shared = self.mode == CompositionMode::Intersection;
+
+				first.into_iter()
+					.filter(|article| {
+						let ptr = article_ptr(article);
+						if keep_shared {
+							others.iter().all(|source| source.contains(&ptr))
+						} else {
+							!others.iter().any(|source| source.contains(&ptr))
+						}
+					})
+					.collect()
+			}
+		};
+
+		combined.retain(|article| match article.upgrade() {
+			Some(strong) => self.filters.iter().all(|instance| instance.matches(&strong.borrow())),
+			None => false,
+		});
+		combined.sort_by(sort_by_id);
+		combined
+	}
+}
+
+fn article_ptr(article: &ArticleWeak) -> *const () {
+	article.as_ptr() as *const ()
+}
+
+/// Endpoint combining the articles of other endpoints, refreshed through them
+pub struct VirtualEndpoint {
+	id: EndpointId,
+	name: String,
+	composition: Composition,
+	articles: Vec<ArticleWeak>,
+	endpoint_agent: Dispatcher<EndpointAgent>,
+}
+
+impl VirtualEndpoint {
+	pub fn new(id: EndpointId, name: Option<String>, composition: Composition) -> Self {
+		Self {
+			id,
+			name: name.unwrap_or_else(|| format!("{:?} of {} endpoints", composition.mode, composition.sources.len())),
+			composition,
+			articles: Vec::new(),
+			endpoint_agent: EndpointAgent::dispatcher(),
+		}
+	}
+
+	pub fn from_json(id: EndpointId, params: serde_json::Value) -> Self {
+		Self::new(id, params["name"].as_str().map(str::to_owned), Composition::from_json(&params))
+	}
+}
+
+impl Endpoint for VirtualEndpoint {
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+
+	fn id(&self) -> &EndpointId {
+		&self.id
+	}
+
+	fn articles(&mut self) -> &mut Vec<ArticleWeak> {
+		&mut self.articles
+	}
+
+	fn composition(&self) -> Option<&Composition> {
+		Some(&self.composition)
+	}
+
+	/// The agent refreshes the sources itself, this is only a fallback
+	fn refresh(&mut self, refresh: RefreshRequest) {
+		for source in &self.composition.sources {
+			self.endpoint_agent.send(EndpointRequest::RefreshEndpoint(*source, refresh.time));
+		}
+	}
+
+	fn eq_storage(&self, storage: &EndpointSerialized) -> bool {
+		storage.service == SERVICE_INFO.name &&
+			Composition::from_json(&storage.params) == self.composition
+	}
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use gloo_storage::Storage;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::DisplayMode;
+use crate::error::{Error, Result};
+use crate::services::migrations::{migrate, StorageBlobs, SCHEMA_VERSION};
+use crate::services::mute::MuteList;
+use crate::services::storages::{
+	LocalStorageService,
+	SoshalLocalStorage,
+	get_article_caps,
+	get_fetch_in_worker,
+	get_filter_presets,
+	get_mute_list,
+	read_storage,
+	set_article_caps,
+	set_fetch_in_worker,
+	set_filter_presets,
+	set_mute_list,
+	update_favviewer_settings,
+};
+use crate::timeline::agent::{SoshalTimelineStorage, SoshalWorkspaceStorage};
+use crate::timeline::filter_presets::FilterPreset;
+
+const TIMELINES_KEY: &str = "SoshalThingYew Timelines";
+const WORKSPACES_KEY: &str = "SoshalThingYew Workspaces";
+
+/// Every stored setting, exported as a single document to move between machines
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Workspace {
+	/// Schema version of the stored data, older documents are migrated on import
+	pub version: u32,
+	/// `SoshalTimelineStorage` list, kept as JSON to be written back as is
+	#[serde(default)]
+	pub timelines: Vec<Value>,
+	/// Named `SoshalWorkspaceStorage` list, merged by name
+	#[serde(default)]
+	pub workspaces: Vec<Value>,
+	/// Hidden and read articles per service, and the display mode
+	#[serde(default)]
+	pub local: SoshalLocalStorage,
+	#[serde(default)]
+	pub favviewer: Option<DisplayMode>,
+	#[serde(default)]
+	pub mute_list: MuteList,
+	#[serde(default)]
+	pub filter_presets: Vec<FilterPreset>,
+	#[serde(default)]
+	pub article_caps: HashMap<String, usize>,
+	#[serde(default)]
+	pub fetch_in_worker: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImportMode {
+	/// Overwrites everything stored
+	Replace,
+	/// Imported timelines replace the ones with the same title, and the rest is merged
+	Merge,
+	/// Imported timelines are added after the stored ones, and the rest is merged
+	AppendTimelines,
+}
+
+impl ImportMode {
+	pub fn iter() -> impl Iterator<Item = ImportMode> {
+		[ImportMode::Replace, ImportMode::Merge, ImportMode::AppendTimelines].into_iter()
+	}
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			ImportMode::Replace => "Replace",
+			ImportMode::Merge => "Merge",
+			ImportMode::AppendTimelines => "Append timelines",
+		}
+	}
+}
+
+/// Fails if any stored data is corrupt, rather than exporting or merging over defaults
+pub fn export_workspace() -> Result<Workspace> {
+	Ok(Workspace {
+		version: SCHEMA_VERSION,
+		timelines: read_storage(TIMELINES_KEY)?,
+		workspaces: read_storage(WORKSPACES_KEY)?,
+		local: read_storage("SoshalThingYew")?,
+		favviewer: read_storage("SoshalThingYew FavViewer")?,
+		mute_list: get_mute_list()?,
+		filter_presets: get_filter_presets()?,
+		article_caps: get_article_caps()?,
+		fetch_in_worker: get_fetch_in_worker(),
+	})
+}
+
+/// Parses an exported document, migrating it if it comes from an older version
+pub fn parse_workspace(json: &str, now: f64) -> Result<Workspace> {
+	let mut document: Value = serde_json::from_str(json)
+		.map_err(|err| Error::from(err).with_message("Couldn't parse the workspace"))?;
+	let version = document["version"].as_u64()
+		.ok_or_else(|| Error::from("Missing \"version\"").with_message("Invalid workspace"))? as u32;
+	if version > SCHEMA_VERSION {
+		return Err(Error::from(format!("{} > {}", version, SCHEMA_VERSION)).with_message("Workspace is from a newer version"));
+	}
+
+	if version < SCHEMA_VERSION {
+		let fields = [("timelines", TIMELINES_KEY), ("local", "SoshalThingYew"), ("favviewer", "SoshalThingYew FavViewer")];
+		let mut blobs: StorageBlobs = fields.iter()
+			.map(|(field, key)| (key.to_string(), document[field].take()))
+			.collect();
+
+		migrate(&mut blobs, version, now)?;
+
+		for (field, key) in fields {
+			match blobs.remove(key).filter(|value| !value.is_null()) {
+				Some(value) => document[field] = value,
+				None => if let Some(document) = document.as_object_mut() {
+					document.remove(field);
+				}
+			}
+		}
+		document["version"] = SCHEMA_VERSION.into();
+	}
+
+	let workspace: Workspace = serde_json::from_value(document)
+		.map_err(|err| Error::from(err).with_message("Invalid workspace"))?;
+	serde_json::from_value::<Vec<SoshalTimelineStorage>>(Value::Array(workspace.timelines.clone()))
+		.map_err(|err| Error::from(err).with_message("Invalid timelines in workspace"))?;
+	serde_json::from_value::<Vec<SoshalWorkspaceStorage>>(Value::Array(workspace.workspaces.clone()))
+		.map_err(|err| Error::from(err).with_message("Invalid named workspaces"))?;
+	Ok(workspace)
+}
+
+/// Combines the stored workspace with an imported one
+pub fn merge_workspaces(current: Workspace, imported: Workspace, mode: ImportMode) -> Workspace {
+	if mode == ImportMode::Replace {
+		return imported;
+	}
+
+	let mut timelines = current.timelines;
+	for timeline in imported.timelines {
+		let same_title = timelines.iter().position(|t| t["title"] == timeline["title"]);
+		match (mode, same_title) {
+			(ImportMode::Merge, Some(index)) => timelines[index] = timeline,
+			_ => timelines.push(timeline),
+		}
+	}
+
+	let mut workspaces = current.workspaces;
+	for workspace in imported.workspaces {
+		match workspaces.iter_mut().find(|w| w["name"] == workspace["name"]) {
+			Some(existing) => *existing = workspace,
+			None => workspaces.push(workspace),
+		}
+	}
+
+	let mut local = current.local;
+	local.display_mode = imported.local.display_mode;
+	for (service, imported_service) in imported.local.services {
+		let service = local.services.entry(service).or_default();
+		merge_service(service, imported_service);
+	}
+
+	let mut mute_list = current.mute_list;
+	for rule in imported.mute_list.rules {
+		if !mute_list.rules.contains(&rule) {
+			mute_list.rules.push(rule);
+		}
+	}
+
+	let mut filter_presets = current.filter_presets;
+	for preset in imported.filter_presets {
+		match filter_presets.iter_mut().find(|p| p.name == preset.name) {
+			Some(existing) => *existing = preset,
+			None => filter_presets.push(preset),
+		}
+	}
+
+	let mut article_caps = current.article_caps;
+	article_caps.extend(imported.article_caps);
+
+	Workspace {
+		version: SCHEMA_VERSION,
+		timelines,
+		workspaces,
+		local,
+		favviewer: imported.favviewer.or(current.favviewer),
+		mute_list,
+		filter_presets,
+		article_caps,
+		fetch_in_worker: imported.fetch_in_worker,
+	}
+}
+
+/// Keeps the union of hidden and read articles, and the latest watermark
+fn merge_service(service: &mut LocalStorageService, imported: LocalStorageService) {
+	service.hidden_articles.extend(imported.hidden_articles);
+	for (id, mark) in imported.read_articles {
+		let existing = service.read_articles.entry(id).or_insert(mark);
+		if mark.marked > existing.marked {
+			*existing = mark;
+		}
+	}
+	service.read_watermark = match (service.read_watermark, imported.read_watermark) {
+		(Some(a), Some(b)) => Some(a.max(b)),
+		(a, b) => a.or(b),
+	};
+}
+
+/// Writes the imported workspace, which applies on the next load
+pub fn import_workspace(json: &str, mode: ImportMode) -> Result<()> {
+	let imported = parse_workspace(json, js_sys::Date::now())?;
+	//Replacing doesn't need the current data, so it also works over corrupt storage
+	let workspace = match mode {
+		ImportMode::Replace => imported,
+		_ => merge_workspaces(export_workspace()?, imported, mode),
+	};
+
+	gloo_storage::LocalStorage::set(TIMELINES_KEY, &workspace.timelines)?;
+	gloo_storage::LocalStorage::set(WORKSPACES_KEY, &workspace.workspaces)?;
+	gloo_storage::LocalStorage::set("SoshalThingYew", &workspace.local)?;
+	match workspace.favviewer {
+		Some(settings) => update_favviewer_settings(settings),
+		None => gloo_storage::LocalStorage::delete("SoshalThingYew FavViewer"),
+	}
+	set_mute_list(&workspace.mute_list);
+	set_filter_presets(&workspace.filter_presets);
+	set_article_caps(&workspace.article_caps);
+	set_fetch_in_worker(workspace.fetch_in_worker);
+	Ok(())
+}
@@ -2,12 +2,12 @@ use std::cell::Ref;
 use std::num::NonZeroU16;
 use js_sys::Date;
 use wasm_bindgen::JsValue;
-use serde::Deserialize;
+use serde::{Serialize, Deserialize};
 use derivative::Derivative;
 use serde_json::Value;
 
 use super::SERVICE_INFO;
-use crate::articles::{ArticleBox, ArticleData, ArticleMedia, MediaQueueInfo, MediaType, ValidRatio};
+use crate::articles::{ArticleBox, ArticleData, ArticleMedia, MediaQueueInfo, MediaType, RawJson, ValidRatio};
 use crate::services::storages::ServiceStorage;
 
 #[derive(Clone, Debug)]
@@ -27,7 +27,7 @@ pub struct YouTubeArticleData {
 	pub thumbnail: ArticleMedia,
 	pub channel: YouTubeChannel,
 	#[derivative(Debug = "ignore")]
-	pub raw_json: serde_json::Value,
+	pub raw_json: RawJson,
 	pub marked_as_read: bool,
 	pub hidden: bool,
 }
@@ -93,7 +93,7 @@ impl ArticleData for YouTubeArticleData {
 	fn media_loaded(&mut self, _index: usize) {}
 
 	fn json(&self) -> Value {
-		self.raw_json.clone()
+		self.raw_json.value()
 	}
 }
 
@@ -108,16 +108,19 @@ impl YouTubeArticleData {
 	}
 }
 
-impl From<(PlaylistItem, serde_json::Value, &ServiceStorage)> for YouTubeArticleData {
-	fn from((item, raw_json, storage): (PlaylistItem, serde_json::Value, &ServiceStorage)) -> Self {
+impl From<(PlaylistItem, RawJson, &ServiceStorage)> for YouTubeArticleData {
+	fn from((item, raw_json, storage): (PlaylistItem, RawJson, &ServiceStorage)) -> Self {
 		let thumbnail = item.snippet.thumbnails.standard
 			.or(item.snippet.thumbnails.maxres)
 			.or(item.snippet.thumbnails.high)
 			.or(item.snippet.thumbnails.medium)
 			.or(item.snippet.thumbnails.default);
+		let creation_time = Date::new(&JsValue::from_str(&item.snippet.published_at));
+		let marked_as_read = storage.local.is_marked_as_read(&item.snippet.resource_id.video_id, creation_time.get_time());
+
 		YouTubeArticleData {
 			id: item.snippet.resource_id.video_id.clone(),
-			creation_time: Date::new(&JsValue::from_str(&item.snippet.published_at)),
+			creation_time,
 			title: item.snippet.title.clone(),
 			//description: item.snippet.description.clone(),
 			thumbnail: ArticleMedia {
@@ -137,13 +140,13 @@ impl From<(PlaylistItem, serde_json::Value, &ServiceStorage)> for YouTubeArticle
 			},
 			raw_json,
 			//TODO Abstract get_service_storage to ArticleData?
-			marked_as_read: storage.session.articles_marked_as_read.contains(&item.snippet.resource_id.video_id),
+			marked_as_read,
 			hidden: storage.local.hidden_articles.contains(&item.snippet.resource_id.video_id),
 		}
 	}
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct PlaylistItem {
 	//#[serde(rename = "contentDetails")]
 	//content_details: ContentDetails,
@@ -153,7 +156,7 @@ pub struct PlaylistItem {
 	snippet: PlaylistItemSnippet,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct PlaylistItemSnippet {
 	channel_id: String,
@@ -166,14 +169,14 @@ struct PlaylistItemSnippet {
 	title: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct PlaylistItemResourceId {
 	//kind: String, TODO make it an enum with kind as the tag
 	video_id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 struct Thumbnails {
 	#[serde(default)]
 	default: Option<Thumbnail>,
@@ -187,7 +190,7 @@ struct Thumbnails {
 	standard: Option<Thumbnail>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 struct Thumbnail {
 	height: u16,
 	url: String,
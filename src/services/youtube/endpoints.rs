@@ -5,13 +5,19 @@ use yew_agent::{Dispatcher, Dispatched};
 
 use super::{YouTubeAgent, Request, SERVICE_INFO};
 use crate::{base_url, Endpoint, EndpointId};
-use crate::articles::{ArticleRc, ArticleWeak};
+use crate::articles::{ArticleRc, ArticleWeak, RawJson};
 use crate::error::{Result, Error};
-use crate::services::{EndpointSerialized, RefreshTime};
+use crate::services::{EndpointSerialized, RefreshRequest};
 use crate::services::storages::ServiceStorage;
 use crate::services::youtube::article::{PlaylistItem, YouTubeArticleData};
 
 pub async fn fetch_videos(url: Url, storage: &ServiceStorage) -> Result<Vec<ArticleRc<YouTubeArticleData>>> {
+	fetch_parsed_videos(url.as_str()).await
+		.map(|videos| build_videos(videos, storage))
+}
+
+/// Fetches and parses the videos, without building the articles
+pub async fn fetch_parsed_videos(url: &str) -> Result<Vec<(PlaylistItem, RawJson)>> {
 	let response = reqwest::Client::builder()
 		//.timeout(Duration::from_secs(10))
 		.build()?
@@ -32,16 +38,20 @@ pub async fn fetch_videos(url: Url, storage: &ServiceStorage) -> Result<Vec<Arti
 
 	serde_json::from_str(&json_str)
 		.map(|value: serde_json::Value|
-			value.as_array().unwrap().iter().map(|json|
-				Rc::new(RefCell::new(YouTubeArticleData::from((
-					serde_json::from_value::<PlaylistItem>(json.clone()).unwrap(),
-					json.clone(),
-					storage
-				))))).collect(),
+			value.as_array().unwrap().iter().map(|json| (
+				serde_json::from_value::<PlaylistItem>(json.clone()).unwrap(),
+				json.clone().into(),
+			)).collect(),
 		)
 		.map_err(|err| Error::from(err))
 }
 
+pub fn build_videos(videos: Vec<(PlaylistItem, RawJson)>, storage: &ServiceStorage) -> Vec<ArticleRc<YouTubeArticleData>> {
+	videos.into_iter()
+		.map(|(item, raw_json)| Rc::new(RefCell::new(YouTubeArticleData::from((item, raw_json, storage)))))
+		.collect()
+}
+
 pub struct PlaylistEndpoint {
 	id: EndpointId,
 	articles: Vec<ArticleWeak>,
@@ -78,9 +88,9 @@ impl Endpoint for PlaylistEndpoint {
 		&mut self.articles
 	}
 
-	fn refresh(&mut self, refresh_time: RefreshTime) {
+	fn refresh(&mut self, refresh: RefreshRequest) {
 		self.agent.send(Request::FetchArticles(
-			refresh_time,
+			refresh,
 			self.id,
 			Url::parse(&format!("{}/proxy/youtube/playlist/{}", base_url(), self.playlist_id)).unwrap()
 		))
@@ -88,6 +98,16 @@ impl Endpoint for PlaylistEndpoint {
 
 	fn eq_storage(&self, storage: &EndpointSerialized) -> bool {
 		storage.service == SERVICE_INFO.name &&
-			storage.endpoint_type == 0
+			storage.endpoint_type == 0 &&
+			storage.params["id"]
+				.as_str()
+				.map(|id| id == self.playlist_id)
+				.unwrap_or_default()
+	}
+
+	fn serialize(&self) -> Option<EndpointSerialized> {
+		Some(EndpointSerialized::new(SERVICE_INFO.name, 0, serde_json::json!({
+			"id": self.playlist_id,
+		})))
 	}
 }
\ No newline at end of file
@@ -1,24 +1,28 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 use reqwest::Url;
-use yew_agent::{Agent, AgentLink, Context, Dispatcher, Dispatched, HandlerId};
+use yew_agent::{Agent, AgentLink, Context, Bridge, Bridged, Dispatcher, Dispatched, HandlerId};
 use yew::prelude::*;
 
 mod article;
 mod endpoints;
 
 use article::YouTubeArticleData;
+pub(crate) use article::PlaylistItem;
+pub(crate) use endpoints::fetch_parsed_videos;
 use crate::articles::ArticleRc;
 use crate::error::{Result, Error};
 use crate::notifications::{Notification, NotificationAgent, NotificationRequest};
 use crate::services::{
 	service,
-	RefreshTime,
+	RefreshRequest,
 	endpoint_agent::{EndpointAgent, EndpointConstructor, EndpointId, EndpointRequest},
 	article_actions::{ArticleActionsAgent, ServiceActions, ArticleActionsRequest},
 	endpoint_agent::EndpointConstructorCollection,
-	storages::get_service_storage,
-	youtube::endpoints::{fetch_videos, PlaylistEndpoint},
+	garbage_collector::{GarbageCollectorAgent, GarbageCollectorRequest, GarbageCollectorResponse},
+	fetch_worker::{FetchKind, FetchWorkerResponse, ParsedArticles, WorkerFetcher, unexpected_articles},
+	storages::{get_fetch_in_worker, get_service_storage},
+	youtube::endpoints::{build_videos, fetch_videos, PlaylistEndpoint},
 };
 
 #[derive(Debug)]
@@ -36,16 +40,20 @@ pub struct YouTubeAgent {
 	auth_state: AuthState,
 	sidebar_handler: Option<HandlerId>,
 	notification_agent: Dispatcher<NotificationAgent>,
+	garbage_collector: Box<dyn Bridge<GarbageCollectorAgent>>,
+	worker_fetcher: WorkerFetcher<(RefreshRequest, EndpointId)>,
 }
 
 pub enum YouTubeMsg {
-	EndpointFetchResponse(RefreshTime, EndpointId, Result<Vec<ArticleRc<YouTubeArticleData>>>),
+	EndpointFetchResponse(RefreshRequest, EndpointId, Result<Vec<ArticleRc<YouTubeArticleData>>>),
+	GarbageCollectorResponse(GarbageCollectorResponse),
+	WorkerFetchResponse(FetchWorkerResponse),
 }
 
 pub enum YouTubeRequest {
 	Auth(bool),
-	AddArticles(RefreshTime, EndpointId, Vec<ArticleRc<YouTubeArticleData>>),
-	FetchArticles(RefreshTime, EndpointId, Url),
+	AddArticles(RefreshRequest, EndpointId, Vec<ArticleRc<YouTubeArticleData>>),
+	FetchArticles(RefreshRequest, EndpointId, Url),
 	Sidebar,
 }
 
@@ -80,6 +88,9 @@ impl Agent for YouTubeAgent {
 				user_endpoint_index: None,
 			}));
 
+		let mut garbage_collector = GarbageCollectorAgent::bridge(link.callback(Msg::GarbageCollectorResponse));
+		garbage_collector.send(GarbageCollectorRequest::RegisterService(SERVICE_INFO.name));
+
 		let mut _actions_agent = ArticleActionsAgent::dispatcher();
 		_actions_agent.send(ArticleActionsRequest::Init(SERVICE_INFO.name, ServiceActions {
 			like: None,
@@ -87,6 +98,8 @@ impl Agent for YouTubeAgent {
 			fetch_data: None,
 		}));
 
+		let worker_fetcher = WorkerFetcher::new(link.callback(Msg::WorkerFetchResponse));
+
 		Self {
 			endpoint_agent,
 			link,
@@ -95,12 +108,25 @@ impl Agent for YouTubeAgent {
 			auth_state: AuthState::NotLoggedIn,
 			sidebar_handler: None,
 			notification_agent: NotificationAgent::dispatcher(),
+			garbage_collector,
+			worker_fetcher,
 		}
 	}
 
 	fn update(&mut self, msg: Self::Message) {
 		match msg {
-			Msg::EndpointFetchResponse(refresh_time, id, r) => {
+			Msg::GarbageCollectorResponse(response) => self.handle_garbage_collector_response(response),
+			Msg::WorkerFetchResponse(response) => {
+				if let Some(((refresh, id), r)) = self.worker_fetcher.take(response) {
+					let storage = get_service_storage(SERVICE_INFO.name);
+					let r = r.and_then(|(parsed, _)| match parsed {
+						ParsedArticles::YouTubeVideos(videos) => Ok(build_videos(videos, &storage)),
+						_ => Err(unexpected_articles()),
+					});
+					self.update(Msg::EndpointFetchResponse(refresh, id, r));
+				}
+			}
+			Msg::EndpointFetchResponse(refresh, id, r) => {
 				let r = match r {
 					Ok(articles) => {
 						let mut updated_articles = Vec::new();
@@ -128,7 +154,7 @@ impl Agent for YouTubeAgent {
 					}
 				};
 
-				self.endpoint_agent.send(EndpointRequest::EndpointFetchResponse(refresh_time, id, r));
+				self.endpoint_agent.send(EndpointRequest::EndpointFetchResponse(refresh, id, r));
 			}
 		}
 	}
@@ -145,7 +171,7 @@ impl Agent for YouTubeAgent {
 					self.link.respond(sidebar_handler, Response::Sidebar(self.sidebar()));
 				}
 			},
-			Request::AddArticles(refresh_time, endpoint_id, articles) => {
+			Request::AddArticles(refresh, endpoint_id, articles) => {
 				let mut updated_articles = Vec::new();
 				for article in articles.into_iter() {
 					let article = self.insert_or_update(article);
@@ -153,17 +179,20 @@ impl Agent for YouTubeAgent {
 					updated_articles.push(article as ArticleRc);
 				}
 				self.endpoint_agent.send(EndpointRequest::AddArticles(
-					refresh_time,
+					refresh,
 					endpoint_id,
 					updated_articles,
 				));
 
 				//self.check_unfetched_articles();
 			}
-			Request::FetchArticles(refresh_time, id, url) =>
+			Request::FetchArticles(refresh, id, url) => if get_fetch_in_worker() {
+				self.worker_fetcher.fetch(url.to_string(), FetchKind::YouTubeVideos, (refresh, id));
+			} else {
 				self.link.send_future(async move {
-					Msg::EndpointFetchResponse(refresh_time, id, fetch_videos(url, &get_service_storage(SERVICE_INFO.name)).await)
-				}),
+					Msg::EndpointFetchResponse(refresh, id, fetch_videos(url, &get_service_storage(SERVICE_INFO.name)).await)
+				})
+			}
 			Request::Sidebar => {
 				self.sidebar_handler = Some(id);
 				self.link.respond(id, Response::Sidebar(self.sidebar()));
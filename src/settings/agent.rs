@@ -1,7 +1,7 @@
 use yew_agent::{Agent, AgentLink, HandlerId, Context as AgentContext};
 
 use crate::DisplayMode;
-use crate::services::storages::update_favviewer_settings;
+use crate::services::storages::{set_fetch_in_worker, update_favviewer_settings};
 use crate::settings::ChangeSettingMsg;
 
 pub struct SettingsAgent {
@@ -16,6 +16,7 @@ pub enum SettingsRequest {
 	ShowModal,
 	InitFavViewerSettings(DisplayMode),
 	UpdateFavViewer(DisplayMode),
+	SetFetchInWorker(bool),
 	RegisterModal,
 	RegisterSidebar,
 	RegisterModel,
@@ -66,6 +67,7 @@ impl Agent for SettingsAgent {
 					self.link.respond(modal, Response::UpdateFavViewerSettings(settings))
 				}
 			}
+			Request::SetFetchInWorker(value) => set_fetch_in_worker(value),
 			Request::RegisterModal => {
 				self.modal = Some(id);
 				if let Some(settings) = self.favviewer_settings {
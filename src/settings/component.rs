@@ -4,14 +4,19 @@ use web_sys::HtmlInputElement;
 use wasm_bindgen::JsCast;
 
 use super::{AppSettings, ChangeSettingMsg, OnMediaClick, ArticleFilteredMode, SettingsAgent, SettingsResponse, SettingsRequest};
+use super::read_watermarks::ReadWatermarks;
+use super::workspace_transfer::WorkspaceTransfer;
 use crate::modals::ModalCard;
+use crate::services::storages::get_fetch_in_worker;
 use crate::components::{Dropdown, DropdownLabel};
+use crate::timeline::filter_presets::FilterPresetsManager;
 use crate::{Container, DisplayMode};
 
 pub struct SettingsModal {
 	enabled: bool,
 	settings_agent: Box<dyn Bridge<SettingsAgent>>,
 	favviewer_settings: DisplayMode,
+	fetch_in_worker: bool,
 }
 
 pub enum SettingsModalMsg {
@@ -20,12 +25,15 @@ pub enum SettingsModalMsg {
 	ChangeContainer(Container),
 	SettingsResponse(SettingsResponse),
 	ToggleFavViewerSettings,
+	ToggleFetchInWorker,
 	ChangeSetting(ChangeSettingMsg),
 }
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct SettingsModalProps {
 	pub app_settings: AppSettings,
+	/// The worker script can't be loaded from the page the favviewer is injected in
+	pub favviewer: bool,
 }
 
 type Msg = SettingsModalMsg;
@@ -43,6 +51,7 @@ impl Component for SettingsModal {
 			enabled: false,
 			settings_agent,
 			favviewer_settings: DisplayMode::default(),
+			fetch_in_worker: get_fetch_in_worker(),
 		}
 	}
 
@@ -89,6 +98,11 @@ impl Component for SettingsModal {
 				self.settings_agent.send(SettingsRequest::UpdateFavViewer(self.favviewer_settings));
 				true
 			}
+			Msg::ToggleFetchInWorker => {
+				self.fetch_in_worker = !self.fetch_in_worker;
+				self.settings_agent.send(SettingsRequest::SetFetchInWorker(self.fetch_in_worker));
+				true
+			}
 			Msg::ChangeSetting(change_msg) => {
 				self.settings_agent.send(SettingsRequest::ChangeSetting(change_msg));
 				false
@@ -122,6 +136,18 @@ impl Component for SettingsModal {
 					ctx.props().app_settings.masonry_independent_columns,
 					ctx.link().callback(Msg::ChangeSetting)
 				) }
+				{ if ctx.props().favviewer {
+					html! {}
+				} else {
+					html! {
+						<div class="block control">
+							<label class="checkbox">
+								<input type="checkbox" checked={self.fetch_in_worker} onclick={ctx.link().callback(|_| Msg::ToggleFetchInWorker)}/>
+								{ " Fetch articles in a web worker" }
+							</label>
+						</div>
+					}
+				} }
 				<div class="field">
   					<div class="control">
 						<label class="checkbox">
@@ -152,6 +178,9 @@ impl Component for SettingsModal {
 						</>
 					}
 				}else {html! {}} }
+				<FilterPresetsManager/>
+				<ReadWatermarks/>
+				<WorkspaceTransfer/>
 			</ModalCard>
 		}
 	}
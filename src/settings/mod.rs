@@ -1,5 +1,7 @@
 mod agent;
 mod component;
+mod read_watermarks;
+mod workspace_transfer;
 
 use std::fmt::{Display, Formatter};
 pub use component::{
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use yew::prelude::*;
+use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlInputElement;
+
+use crate::services::article_actions::{ArticleActionsAgent, ArticleActionsRequest};
+use crate::services::endpoint_agent::{EndpointAgent, EndpointRequest, EndpointResponse};
+use crate::services::storages::{get_read_watermarks, set_read_watermark};
+
+/// Per service date before which every article counts as read
+pub struct ReadWatermarks {
+	services: Vec<&'static str>,
+	watermarks: HashMap<String, f64>,
+	/// Date input values
+	dates: HashMap<&'static str, String>,
+	_endpoint_agent: Box<dyn Bridge<EndpointAgent>>,
+	article_actions: Dispatcher<ArticleActionsAgent>,
+}
+
+pub enum ReadWatermarksMsg {
+	EndpointResponse(EndpointResponse),
+	SetDate(&'static str, String),
+	Apply(&'static str),
+	Clear(&'static str),
+}
+
+type Msg = ReadWatermarksMsg;
+
+impl Component for ReadWatermarks {
+	type Message = Msg;
+	type Properties = ();
+
+	fn create(ctx: &Context<Self>) -> Self {
+		let mut _endpoint_agent = EndpointAgent::bridge(ctx.link().callback(Msg::EndpointResponse));
+		_endpoint_agent.send(EndpointRequest::GetState);
+
+		Self {
+			services: Vec::new(),
+			watermarks: get_read_watermarks(),
+			dates: HashMap::new(),
+			_endpoint_agent,
+			article_actions: ArticleActionsAgent::dispatcher(),
+		}
+	}
+
+	fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+		match msg {
+			Msg::EndpointResponse(EndpointResponse::UpdatedState(services, _endpoints)) => {
+				let mut services: Vec<&'static str> = services.keys().copied().collect();
+				services.sort_unstable();
+				let changed = services != self.services;
+				self.services = services;
+				changed
+			}
+			Msg::EndpointResponse(_) => false,
+			Msg::SetDate(service, date) => {
+				self.dates.insert(service, date);
+				false
+			}
+			Msg::Apply(service) => {
+				let watermark = self.dates.get(service).and_then(|date| local_midnight(date));
+
+				match watermark {
+					Some(watermark) => {
+						self.article_actions.send(ArticleActionsRequest::MarkAsReadBefore(service.to_owned(), watermark));
+						self.watermarks.insert(service.to_owned(), watermark);
+						true
+					}
+					None => false,
+				}
+			}
+			Msg::Clear(service) => {
+				set_read_watermark(service, None);
+				self.watermarks.remove(service).is_some()
+			}
+		}
+	}
+
+	fn view(&self, ctx: &Context<Self>) -> Html {
+		html! {
+			<div class="block">
+				<label class="label">{"Read Watermarks"}</label>
+				<p class="help">{"Articles created before the date count as read."}</p>
+				{ for self.services.iter().map(|service| {
+					let service = *service;
+					let oninput = ctx.link().callback(move |e: InputEvent| {
+						let input: HtmlInputElement = e.target().unwrap().dyn_into().unwrap();
+						Msg::SetDate(service, input.value())
+					});
+
+					html! {
+						<div class="field has-addons">
+							<div class="field-label is-normal">
+								<label class="label">{ service }</label>
+							</div>
+							<div class="control">
+								<input class="input" type="date" {oninput}/>
+							</div>
+							<div class="control">
+								<button class="button" onclick={ctx.link().callback(move |_| Msg::Apply(service))}>{"Mark as read"}</button>
+							</div>
+							{ match self.watermarks.get(service) {
+								Some(watermark) => html! {
+									<>
+										<div class="control">
+											<span class="button is-static">
+												{ String::from(js_sys::Date::new(&JsValue::from_f64(*watermark)).to_locale_date_string("default", &JsValue::UNDEFINED)) }
+											</span>
+										</div>
+										<div class="control">
+											<button class="button" onclick={ctx.link().callback(move |_| Msg::Clear(service))}>{"Clear"}</button>
+										</div>
+									</>
+								},
+								None => html! {},
+							} }
+						</div>
+					}
+				}) }
+			</div>
+		}
+	}
+}
+
+/// Parses a date input value as local midnight, where `Date::new` would use UTC
+fn local_midnight(date: &str) -> Option<f64> {
+	let mut parts = date.splitn(3, '-').map(|part| part.parse::<i32>().ok());
+	match (parts.next().flatten(), parts.next().flatten(), parts.next().flatten()) {
+		(Some(year), Some(month), Some(day)) => Some(js_sys::Date::new_with_year_month_day(year as u32, month - 1, day).get_time())
+			.filter(|watermark| !watermark.is_nan()),
+		_ => None,
+	}
+}
@@ -0,0 +1,139 @@
+use yew::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+
+use crate::components::{Dropdown, DropdownLabel};
+use crate::log_error;
+use crate::services::workspace::{ImportMode, export_workspace, import_workspace};
+
+/// Exporting and importing every stored setting as a single document
+pub struct WorkspaceTransfer {
+	export: Option<String>,
+	import_ref: NodeRef,
+	import_mode: ImportMode,
+	import_result: Option<Result<(), String>>,
+}
+
+pub enum WorkspaceTransferMsg {
+	Export,
+	SetImportMode(ImportMode),
+	Import,
+	Reload,
+}
+
+type Msg = WorkspaceTransferMsg;
+
+impl Component for WorkspaceTransfer {
+	type Message = Msg;
+	type Properties = ();
+
+	fn create(_ctx: &Context<Self>) -> Self {
+		Self {
+			export: None,
+			import_ref: NodeRef::default(),
+			import_mode: ImportMode::Merge,
+			import_result: None,
+		}
+	}
+
+	fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+		match msg {
+			Msg::Export => {
+				let export = export_workspace()
+					.and_then(|workspace| Ok(serde_json::to_string_pretty(&workspace)?));
+				match export {
+					Ok(export) => self.export = Some(export),
+					Err(err) => log::error!("{}", err.with_message("Failed to export the workspace")),
+				}
+				true
+			}
+			Msg::SetImportMode(mode) => {
+				self.import_mode = mode;
+				true
+			}
+			Msg::Import => {
+				let textarea = match self.import_ref.cast::<HtmlTextAreaElement>() {
+					Some(textarea) => textarea,
+					None => return false,
+				};
+
+				match import_workspace(&textarea.value(), self.import_mode) {
+					//Agents hold the state they loaded on startup
+					Ok(()) => {
+						textarea.set_value("");
+						self.import_result = Some(Ok(()));
+						ctx.link().send_message(Msg::Reload);
+					}
+					Err(err) => {
+						self.import_result = Some(Err(err.to_string()));
+						log::warn!("{}", err.with_message("Failed to import the workspace"));
+					}
+				}
+				true
+			}
+			Msg::Reload => {
+				if let Err(err) = gloo_utils::window().location().reload() {
+					log_error!("Failed to reload the page", err);
+				}
+				false
+			}
+		}
+	}
+
+	fn view(&self, ctx: &Context<Self>) -> Html {
+		let select_all = Callback::from(|e: FocusEvent| {
+			if let Some(textarea) = e.target().and_then(|t| t.dyn_into::<HtmlTextAreaElement>().ok()) {
+				textarea.select();
+			}
+		});
+
+		html! {
+			<div class="block">
+				<label class="label">{"Workspace"}</label>
+				<p class="help">{"Timelines, endpoints, settings, hidden and read articles."}</p>
+				<div class="field">
+					<div class="control">
+						<button class="button" onclick={ctx.link().callback(|_| Msg::Export)}>{"Export"}</button>
+					</div>
+				</div>
+				{ match &self.export {
+					Some(export) => html! {
+						<div class="field">
+							<div class="control">
+								<textarea class="textarea is-small" readonly=true value={export.clone()} onfocus={select_all}/>
+							</div>
+						</div>
+					},
+					None => html! {},
+				} }
+				<div class="field">
+					<label class="label is-small">{"Import"}</label>
+					<div class="control">
+						<textarea class={classes!("textarea", "is-small", if matches!(self.import_result, Some(Err(_))) { Some("is-danger") } else { None })} placeholder="Paste workspace JSON" ref={self.import_ref.clone()}/>
+					</div>
+					{ match &self.import_result {
+						Some(Ok(())) => html! {
+							<p class="help is-success">{"Imported, reloading..."}</p>
+						},
+						Some(Err(error)) => html! { <p class="help is-danger">{ error.clone() }</p> },
+						None => html! {},
+					} }
+				</div>
+				<div class="field is-grouped">
+					<div class="control">
+						<Dropdown current_label={DropdownLabel::Text(self.import_mode.name().to_owned())}>
+							{ for ImportMode::iter().map(|mode| html! {
+								<a class={classes!("dropdown-item", if mode == self.import_mode { Some("is-active") } else { None })} onclick={ctx.link().callback(move |_| Msg::SetImportMode(mode))}>
+									{ mode.name() }
+								</a>
+							}) }
+						</Dropdown>
+					</div>
+					<div class="control">
+						<button class="button" onclick={ctx.link().callback(|_| Msg::Import)}>{"Import"}</button>
+					</div>
+				</div>
+			</div>
+		}
+	}
+}
@@ -4,10 +4,22 @@ use yew_agent::{Bridge, Bridged};
 use wasm_bindgen::JsCast;
 
 use crate::services::endpoint_agent::{EndpointId, EndpointAgent, EndpointRequest, EndpointResponse, EndpointView};
+use crate::services::{Endpoint, RefreshSchedule, ScheduleRule};
+use crate::services::virtual_endpoint::{Composition, CompositionMode, VirtualEndpoint};
+use crate::timeline::filters::{FilterCollection, FilterMsg, FiltersOptions};
+
+/// Shortest interval a schedule rule can refresh at, in ms
+const MIN_SCHEDULE_INTERVAL: u32 = 10_000;
 
 pub struct EndpointOptions {
 	pub endpoints: HashMap<EndpointId, EndpointView>,
 	endpoint_agent: Box<dyn Bridge<EndpointAgent>>,
+	/// Selected sources of the virtual endpoint to create, in order
+	combine_sources: Vec<EndpointId>,
+	combine_mode: CompositionMode,
+	/// Filters of the virtual endpoint to create
+	combine_filters: FilterCollection,
+	combine_name_ref: NodeRef,
 }
 
 pub enum EndpointOptionsMsg {
@@ -15,6 +27,11 @@ pub enum EndpointOptionsMsg {
 	StartAutoRefresh(EndpointId),
 	StopAutoRefresh(EndpointId),
 	SetAutoRefreshInterval(EndpointId, u32),
+	SetRefreshSchedule(EndpointId, RefreshSchedule),
+	ToggleCombineSource(EndpointId),
+	SetCombineMode(CompositionMode),
+	CombineFilterMsg(FilterMsg),
+	CreateVirtualEndpoint,
 }
 
 type Msg = EndpointOptionsMsg;
@@ -30,6 +47,10 @@ impl Component for EndpointOptions {
 		Self {
 			endpoints: HashMap::new(),
 			endpoint_agent: _endpoint_agent,
+			combine_sources: Vec::new(),
+			combine_mode: CompositionMode::default(),
+			combine_filters: FilterCollection::new(),
+			combine_name_ref: NodeRef::default(),
 		}
 	}
 
@@ -41,6 +62,8 @@ impl Component for EndpointOptions {
 					for endpoint in endpoints {
 						self.endpoints.insert(endpoint.id, endpoint.clone());
 					}
+					let endpoints = &self.endpoints;
+					self.combine_sources.retain(|id| endpoints.contains_key(id));
 
 					true
 				}
@@ -58,6 +81,45 @@ impl Component for EndpointOptions {
 				self.endpoint_agent.send(EndpointRequest::SetAutoRefreshInterval(endpoint_id, interval));
 				false
 			}
+			Msg::SetRefreshSchedule(endpoint_id, schedule) => {
+				self.endpoint_agent.send(EndpointRequest::SetRefreshSchedule(endpoint_id, schedule));
+				false
+			}
+			Msg::ToggleCombineSource(endpoint_id) => {
+				match self.combine_sources.iter().position(|id| *id == endpoint_id) {
+					Some(index) => { self.combine_sources.remove(index); }
+					None => self.combine_sources.push(endpoint_id),
+				}
+				true
+			}
+			Msg::SetCombineMode(mode) => {
+				self.combine_mode = mode;
+				true
+			}
+			Msg::CombineFilterMsg(filter_msg) => self.combine_filters.update(filter_msg),
+			Msg::CreateVirtualEndpoint => {
+				if self.combine_sources.is_empty() {
+					return false;
+				}
+
+				let name = self.combine_name_ref.cast::<web_sys::HtmlInputElement>()
+					.map(|input| {
+						let name = input.value().trim().to_owned();
+						input.set_value("");
+						name
+					})
+					.filter(|name| !name.is_empty());
+				let composition = Composition {
+					mode: self.combine_mode,
+					sources: std::mem::take(&mut self.combine_sources),
+					filters: std::mem::take(&mut self.combine_filters),
+				};
+				self.endpoint_agent.send(EndpointRequest::AddEndpoint {
+					id_to_endpoint: Box::new(move |id| Box::new(VirtualEndpoint::new(id, name, composition)) as Box<dyn Endpoint>),
+					shared: true,
+				});
+				true
+			}
 		}
 	}
 
@@ -67,20 +129,109 @@ impl Component for EndpointOptions {
 				html! {
 					<div class="block">
 						{ endpoint.name.clone() }
+						{ self.view_failure_streak(&endpoint) }
+						{ if endpoint.refresh_deferred {
+							html! { <span class="tag is-info">{"Refresh deferred"}</span> }
+						} else {
+							html! {}
+						} }
+						{ if endpoint.exhausted {
+							html! { <span class="tag">{"No more articles"}</span> }
+						} else {
+							html! {}
+						} }
 						{ self.view_ratelimit(&endpoint) }
 						{ self.view_autorefresh(ctx, &endpoint) }
+						{ self.view_schedule(ctx, &endpoint) }
 					</div>
 				}
 			}) }
+			{ self.view_combine(ctx) }
 		}
 	}
 }
 
 impl EndpointOptions {
+	/// Form creating a virtual endpoint out of the selected endpoints
+	fn view_combine(&self, ctx: &Context<Self>) -> Html {
+		if self.endpoints.is_empty() {
+			return html! {};
+		}
+
+		let mut endpoints: Vec<&EndpointView> = self.endpoints.values().collect();
+		endpoints.sort_by_key(|endpoint| endpoint.id);
+
+		html! {
+			<div class="block">
+				<label class="label">{"Combine endpoints"}</label>
+				{ for endpoints.into_iter().map(|endpoint| {
+					let id = endpoint.id;
+					let position = self.combine_sources.iter().position(|source| *source == id);
+					html! {
+						<div class="field">
+							<label class="checkbox">
+								<input type="checkbox" checked={position.is_some()} onclick={ctx.link().callback(move |_| Msg::ToggleCombineSource(id))}/>
+								{ format!(" {}", endpoint.name) }
+							</label>
+							{ match position {
+								Some(position) => html! { <span class="tag">{ (position + 1).to_string() }</span> },
+								None => html! {},
+							} }
+						</div>
+					}
+				}) }
+				<div class="field">
+					<div class="buttons has-addons">
+						{ for CompositionMode::iter().map(|mode| html! {
+							<button class={classes!("button", "is-small", if mode == self.combine_mode { Some("is-selected") } else { None })} onclick={ctx.link().callback(move |_| Msg::SetCombineMode(mode))}>
+								{ format!("{:?}", mode) }
+							</button>
+						}) }
+					</div>
+					{ if self.combine_mode == CompositionMode::Difference {
+						html! { <p class="help">{"Articles of the first selected endpoint missing from the others."}</p> }
+					} else {
+						html! {}
+					} }
+				</div>
+				<FiltersOptions filters={self.combine_filters.clone()} callback={ctx.link().callback(Msg::CombineFilterMsg)}/>
+				<div class="field has-addons">
+					<div class="control">
+						<input class="input" type="text" placeholder="Name" ref={self.combine_name_ref.clone()}/>
+					</div>
+					<div class="control">
+						<button class="button" disabled={self.combine_sources.is_empty()} onclick={ctx.link().callback(|_| Msg::CreateVirtualEndpoint)}>
+							{"Create"}
+						</button>
+					</div>
+				</div>
+			</div>
+		}
+	}
+
+	fn view_failure_streak(&self, endpoint: &EndpointView) -> Html {
+		if endpoint.failure_streak > 0 {
+			html! {
+				<div class="block">
+					<span class="tag is-danger">
+						{ format!("{} failed refreshes", &endpoint.failure_streak) }
+					</span>
+					{ if endpoint.auto_refresh_suspended {
+						html! { <span class="tag is-warning">{"Auto refresh suspended"}</span> }
+					} else {
+						html! {}
+					} }
+				</div>
+			}
+		} else {
+			html! {}
+		}
+	}
+
 	fn view_ratelimit(&self, endpoint: &EndpointView) -> Html {
 		match &endpoint.ratelimit {
 			Some(ratelimit) => {
-				let time_left = (((ratelimit.reset as f64 * 1000.0) - js_sys::Date::now()) / 60000.0).ceil();
+				let time_left = ((ratelimit.reset_time() - js_sys::Date::now()) / 60000.0).ceil();
 				html! {
 					<>
 						<progress class="progress" value={ratelimit.remaining.to_string()} max={ratelimit.limit.to_string()}>
@@ -94,6 +245,95 @@ impl EndpointOptions {
 		}
 	}
 
+	fn view_schedule(&self, ctx: &Context<Self>, endpoint: &EndpointView) -> Html {
+		let id = endpoint.id;
+		let schedule = &endpoint.refresh_schedule;
+
+		let toggle_hidden = {
+			let mut schedule = schedule.clone();
+			schedule.pause_when_hidden = !schedule.pause_when_hidden;
+			ctx.link().callback(move |_| Msg::SetRefreshSchedule(id, schedule.clone()))
+		};
+		let add_rule = {
+			let mut schedule = schedule.clone();
+			schedule.rules.push(ScheduleRule::default());
+			ctx.link().callback(move |_| Msg::SetRefreshSchedule(id, schedule.clone()))
+		};
+
+		html! {
+			<div class="block">
+				{ for schedule.rules.iter().enumerate().map(|(i, rule)| {
+					//Builds the schedule with this rule changed, once an input's value is committed
+					let on_rule_change = |change: fn(&mut ScheduleRule, u32)| {
+						let schedule = schedule.clone();
+						ctx.link().batch_callback(move |e: Event| {
+							let value = e.target()
+								.and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+								.and_then(|i| i.value().parse::<u32>().ok())?;
+							let mut schedule = schedule.clone();
+							change(&mut schedule.rules[i], value);
+							Some(Msg::SetRefreshSchedule(id, schedule))
+						})
+					};
+					let toggle_paused = {
+						let mut schedule = schedule.clone();
+						schedule.rules[i].interval = match rule.interval {
+							Some(_) => None,
+							None => Some(endpoint.autorefresh_interval.max(MIN_SCHEDULE_INTERVAL)),
+						};
+						ctx.link().callback(move |_| Msg::SetRefreshSchedule(id, schedule.clone()))
+					};
+					let remove_rule = {
+						let mut schedule = schedule.clone();
+						schedule.rules.remove(i);
+						ctx.link().callback(move |_| Msg::SetRefreshSchedule(id, schedule.clone()))
+					};
+
+					html! {
+						<div class="field has-addons">
+							<div class="control">
+								<input class="input" type="number" min=0 max=23 title="Start hour" value={rule.start_hour.to_string()}
+									onchange={on_rule_change(|rule, value| rule.start_hour = value.min(23) as u8)}/>
+							</div>
+							<div class="control">
+								<input class="input" type="number" min=0 max=23 title="End hour" value={rule.end_hour.to_string()}
+									onchange={on_rule_change(|rule, value| rule.end_hour = value.min(23) as u8)}/>
+							</div>
+							<div class="control">
+								<input class="input" type="number" min={(MIN_SCHEDULE_INTERVAL / 1000).to_string()} title="Interval"
+									value={rule.interval.map(|i| (i / 1000).to_string()).unwrap_or_default()}
+									disabled={rule.interval.is_none()}
+									onchange={on_rule_change(|rule, value| rule.interval = Some(value.saturating_mul(1000).max(MIN_SCHEDULE_INTERVAL)))}/>
+							</div>
+							<div class="control">
+								<a class="button is-static">{"seconds"}</a>
+							</div>
+							<div class="control">
+								<button class={classes!("button", if rule.interval.is_none() { Some("is-selected") } else { None })} onclick={toggle_paused} title="Don't refresh during these hours">
+									{"Paused"}
+								</button>
+							</div>
+							<div class="control">
+								<button class="button" onclick={remove_rule}>{"Remove"}</button>
+							</div>
+						</div>
+					}
+				}) }
+				<div class="field is-grouped">
+					<div class="control">
+						<button class="button" onclick={add_rule}>{"Add schedule rule"}</button>
+					</div>
+					<div class="control">
+						<label class="checkbox">
+							<input type="checkbox" checked={schedule.pause_when_hidden} onclick={toggle_hidden}/>
+							{ " Pause when hidden" }
+						</label>
+					</div>
+				</div>
+			</div>
+		}
+	}
+
 	fn view_autorefresh(&self, ctx: &Context<Self>, endpoint: &EndpointView) -> Html {
 		let id_c = endpoint.id;
 		let interval = endpoint.autorefresh_interval.to_string();
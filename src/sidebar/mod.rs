@@ -1,19 +1,24 @@
 use yew::prelude::*;
-use yew_agent::{Dispatcher, Dispatched};
+use yew_agent::{Bridge, Bridged, Dispatcher, Dispatched};
 
 mod endpoint_options;
+mod workspace_options;
 
 use endpoint_options::EndpointOptions;
+use workspace_options::WorkspaceOptions;
 use crate::timeline::agent::{TimelineAgent, TimelineRequest};
 use crate::settings::{SettingsAgent, SettingsRequest};
 use crate::components::{FA, IconSize, IconType};
 use crate::modals::modal_agent::{ModalAgent, ModalRequest, ModalType};
+use crate::services::endpoint_agent::{EndpointAgent, EndpointRequest, EndpointResponse};
 
 pub struct Sidebar {
 	expanded: bool,
 	add_timeline_agent: Dispatcher<TimelineAgent>,
 	settings_agent: Dispatcher<SettingsAgent>,
 	modal_agent: Dispatcher<ModalAgent>,
+	_endpoint_agent: Box<dyn Bridge<EndpointAgent>>,
+	muted_count: usize,
 }
 
 pub enum SidebarMsg {
@@ -21,6 +26,9 @@ pub enum SidebarMsg {
 	AddTimeline,
 	ShowSettings,
 	BatchAction,
+	EndpointDashboard,
+	MuteList,
+	EndpointResponse(EndpointResponse),
 }
 
 #[derive(Properties, PartialEq, Clone)]
@@ -37,15 +45,20 @@ impl Component for Sidebar {
 	type Message = Msg;
 	type Properties = Props;
 
-	fn create(_ctx: &Context<Self>) -> Self {
+	fn create(ctx: &Context<Self>) -> Self {
 		let mut settings_agent = SettingsAgent::dispatcher();
 		settings_agent.send(SettingsRequest::RegisterSidebar);
 
+		let mut _endpoint_agent = EndpointAgent::bridge(ctx.link().callback(Msg::EndpointResponse));
+		_endpoint_agent.send(EndpointRequest::GetMuteList);
+
 		Self {
 			expanded: false,
 			add_timeline_agent: TimelineAgent::dispatcher(),
 			settings_agent,
 			modal_agent: ModalAgent::dispatcher(),
+			_endpoint_agent,
+			muted_count: 0,
 		}
 	}
 
@@ -67,6 +80,22 @@ impl Component for Sidebar {
 				self.modal_agent.send(ModalRequest::ActivateModal(ModalType::BatchAction));
 				false
 			}
+			Msg::EndpointDashboard => {
+				self.modal_agent.send(ModalRequest::ActivateModal(ModalType::EndpointDashboard));
+				false
+			}
+			Msg::MuteList => {
+				self.modal_agent.send(ModalRequest::ActivateModal(ModalType::MuteList));
+				false
+			}
+			Msg::EndpointResponse(response) => match response {
+				EndpointResponse::UpdatedMuteList(_mute_list, muted_count) => {
+					let changed = muted_count != self.muted_count;
+					self.muted_count = muted_count;
+					changed
+				}
+				_ => false,
+			}
 		}
 	}
 
@@ -87,6 +116,9 @@ impl Component for Sidebar {
 				{if self.expanded { html! {
 					<div class="sidebarMenu">
 						{ for ctx.props().services.iter().cloned() }
+						<div class="box">
+							<WorkspaceOptions/>
+						</div>
 						<div class="box">
 							<EndpointOptions/>
 						</div>
@@ -101,6 +133,17 @@ impl Component for Sidebar {
 							<FA icon="plus" size={IconSize::X2}/>
 						</button>
 						{ batch_callback }
+						<button onclick={ctx.link().callback(|_| Msg::EndpointDashboard)} title="Endpoint dashboard">
+							<FA icon="stethoscope" size={IconSize::X2}/>
+						</button>
+						<button onclick={ctx.link().callback(|_| Msg::MuteList)} title={format!("Mute list, {} articles muted", self.muted_count)}>
+							<FA icon="volume-mute" size={IconSize::X2}/>
+							{ if self.muted_count > 0 {
+								html! { <span class="mutedCount tag is-rounded is-dark">{ self.muted_count }</span> }
+							} else {
+								html! {}
+							} }
+						</button>
 						{ for ctx.props().children.iter() }
 					</div>
 					<div>
@@ -0,0 +1,116 @@
+use yew::prelude::*;
+use yew_agent::{Bridge, Bridged};
+use web_sys::HtmlInputElement;
+
+use crate::timeline::agent::{TimelineAgent, TimelineRequest, TimelineResponse};
+
+/// Switching between named sets of timelines
+pub struct WorkspaceOptions {
+	workspaces: Vec<String>,
+	current: Option<String>,
+	name_ref: NodeRef,
+	timeline_agent: Box<dyn Bridge<TimelineAgent>>,
+}
+
+pub enum WorkspaceOptionsMsg {
+	TimelineResponse(TimelineResponse),
+	Switch(Option<String>),
+	Save,
+	Remove(String),
+}
+
+type Msg = WorkspaceOptionsMsg;
+
+impl Component for WorkspaceOptions {
+	type Message = Msg;
+	type Properties = ();
+
+	fn create(ctx: &Context<Self>) -> Self {
+		let mut timeline_agent = TimelineAgent::bridge(ctx.link().callback(Msg::TimelineResponse));
+		timeline_agent.send(TimelineRequest::RegisterWorkspaceOptions);
+
+		Self {
+			workspaces: Vec::new(),
+			current: None,
+			name_ref: NodeRef::default(),
+			timeline_agent,
+		}
+	}
+
+	fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+		match msg {
+			Msg::TimelineResponse(TimelineResponse::UpdatedWorkspaces(workspaces, current)) => {
+				self.workspaces = workspaces;
+				self.current = current;
+				true
+			}
+			Msg::TimelineResponse(_) => false,
+			Msg::Switch(workspace) => {
+				if workspace != self.current {
+					self.timeline_agent.send(TimelineRequest::SwitchWorkspace(workspace));
+				}
+				false
+			}
+			Msg::Save => {
+				if let Some(input) = self.name_ref.cast::<HtmlInputElement>() {
+					let name = input.value().trim().to_owned();
+					if !name.is_empty() {
+						self.timeline_agent.send(TimelineRequest::SaveWorkspace(name));
+						input.set_value("");
+					}
+				}
+				false
+			}
+			Msg::Remove(name) => {
+				self.timeline_agent.send(TimelineRequest::RemoveWorkspace(name));
+				false
+			}
+		}
+	}
+
+	fn view(&self, ctx: &Context<Self>) -> Html {
+		html! {
+			<div class="block">
+				<label class="label">{"Workspaces"}</label>
+				<div class="buttons has-addons">
+					<button class={classes!("button", if self.current.is_none() { Some("is-selected") } else { None })} onclick={ctx.link().callback(|_| Msg::Switch(None))}>
+						{"Default"}
+					</button>
+					{ for self.workspaces.iter().map(|name| {
+						let switch_name = name.clone();
+						html! {
+							<button class={classes!("button", if self.current.as_ref() == Some(name) { Some("is-selected") } else { None })} onclick={ctx.link().callback(move |_| Msg::Switch(Some(switch_name.clone())))}>
+								{ name.clone() }
+							</button>
+						}
+					}) }
+				</div>
+				{ match &self.current {
+					Some(current) => {
+						let name = current.clone();
+						html! {
+							<div class="field">
+								<div class="control">
+									<button class="button is-small" onclick={ctx.link().callback(move |_| Msg::Remove(name.clone()))}>
+										{ format!("Remove \"{}\"", current) }
+									</button>
+								</div>
+							</div>
+						}
+					}
+					None => html! {},
+				} }
+				<div class="field has-addons">
+					<div class="control">
+						<input class="input" type="text" placeholder="Workspace name" ref={self.name_ref.clone()}/>
+					</div>
+					<div class="control">
+						<button class="button" onclick={ctx.link().callback(|_| Msg::Save)} title="Copy the current timelines to this workspace">
+							{"Save as"}
+						</button>
+					</div>
+				</div>
+			</div>
+		}
+	}
+}
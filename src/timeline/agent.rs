@@ -1,8 +1,8 @@
-use std::collections::HashMap;
-use gloo_storage::errors::StorageError;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use yew_agent::{Agent, AgentLink, HandlerId, Context as AgentContext, Dispatcher, Dispatched};
 use gloo_storage::Storage;
 use serde::{Serialize, Deserialize};
+use wasm_bindgen::JsValue;
 
 use super::{
 	TimelineId, TimelineProps, Container,
@@ -10,6 +10,8 @@ use super::{
 };
 use crate::services::EndpointSerialized;
 use crate::services::endpoint_agent::{EndpointRequest, EndpointAgent};
+use crate::error::Result;
+use crate::services::storages::{get_current_workspace, get_workspaces, notify_storage_error, read_storage, set_current_workspace, set_workspaces};
 use crate::TimelineEndpointWrapper;
 use crate::log_warn;
 use crate::timeline::filters::FilterCollection;
@@ -22,8 +24,27 @@ pub struct TimelineAgent {
 	choose_endpoints: Option<HandlerId>,
 	timeline_container: Option<HandlerId>,
 	display_mode: Option<HandlerId>,
+	workspace_options: Option<HandlerId>,
 	endpoint_agent: Dispatcher<EndpointAgent>,
 	timelines: HashMap<TimelineId, HandlerId>,
+	/// None for the default "Timelines" storage
+	workspace: Option<String>,
+	pending_save: Option<PendingSave>,
+}
+
+/// Live timelines being gathered to be written to a workspace
+struct PendingSave {
+	/// None for the default "Timelines" storage
+	workspace: Option<String>,
+	/// Timelines yet to answer
+	remaining: HashSet<TimelineId>,
+	timelines: BTreeMap<TimelineId, SoshalTimelineStorage>,
+	/// Workspace to switch to once saved
+	switch_to: Option<Option<String>>,
+}
+
+pub enum TimelineAgentMsg {
+	SerializedEndpoints(Vec<Vec<EndpointSerialized>>),
 }
 
 pub enum TimelineRequest {
@@ -31,6 +52,7 @@ pub enum TimelineRequest {
 	RegisterChooseEndpoints,
 	RegisterTimelineContainer,
 	RegisterDisplayMode,
+	RegisterWorkspaceOptions,
 	RegisterTimeline(TimelineId),
 	AddTimeline,
 	AddUserTimeline(&'static str, String),
@@ -41,6 +63,13 @@ pub enum TimelineRequest {
 	RemoveTimeline(TimelineId),
 	LoadStorageTimelines,
 	LoadedStorageTimelines(Vec<Vec<TimelineEndpointWrapper>>),
+	/// Replaces the timelines with the ones of the workspace, None for the default one
+	SwitchWorkspace(Option<String>),
+	/// Saves the live timelines to a workspace
+	SaveWorkspace(String),
+	/// Current state of a timeline, None for modal ones
+	SerializedTimeline(TimelineId, Option<SoshalTimelineStorage>),
+	RemoveWorkspace(String),
 	BatchAction(Action, Vec<TimelineId>, FilterCollection),
 }
 
@@ -54,15 +83,21 @@ pub enum TimelineResponse {
 	SetMainColumnCount(u8),
 	RemoveTimeline(TimelineId),
 	CreateTimelines(Vec<TimelinePropsClosure>),
+	ClearTimelines,
+	/// Workspace names and the current one
+	UpdatedWorkspaces(Vec<String>, Option<String>),
 	BatchAction(Action, FilterCollection),
+	/// Asks timelines for their current state
+	SerializeTimeline,
 }
 
+type Msg = TimelineAgentMsg;
 type Request = TimelineRequest;
 type Response = TimelineResponse;
 
 impl Agent for TimelineAgent {
 	type Reach = AgentContext<Self>;
-	type Message = ();
+	type Message = Msg;
 	type Input = Request;
 	type Output = Response;
 
@@ -72,13 +107,38 @@ impl Agent for TimelineAgent {
 			choose_endpoints: None,
 			timeline_container: None,
 			display_mode: None,
+			workspace_options: None,
 			endpoint_agent: EndpointAgent::dispatcher(),
 			link,
 			timelines: HashMap::new(),
+			workspace: workspace_param().or_else(get_current_workspace),
+			pending_save: None,
 		}
 	}
 
-	fn update(&mut self, _msg: Self::Message) {}
+	fn update(&mut self, msg: Self::Message) {
+		match msg {
+			Msg::SerializedEndpoints(endpoints) => {
+				let pending = match self.pending_save.take() {
+					Some(pending) => pending,
+					None => return,
+				};
+
+				let timelines = pending.timelines.into_values()
+					.zip(endpoints)
+					.map(|(timeline, endpoints)| SoshalTimelineStorage { endpoints, ..timeline })
+					.collect();
+				if let Err(err) = set_stored_timelines(pending.workspace.as_deref(), timelines) {
+					notify_storage_error(err.with_message("Failed to save the timelines"));
+				}
+
+				match pending.switch_to {
+					Some(workspace) => self.switch_workspace(workspace),
+					None => self.send_workspaces(),
+				}
+			}
+		}
+	}
 
 	fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
 		match msg {
@@ -86,6 +146,10 @@ impl Agent for TimelineAgent {
 			Request::RegisterChooseEndpoints => self.choose_endpoints = Some(id),
 			Request::RegisterTimelineContainer => self.timeline_container = Some(id),
 			Request::RegisterDisplayMode => self.display_mode = Some(id),
+			Request::RegisterWorkspaceOptions => {
+				self.workspace_options = Some(id);
+				self.send_workspaces();
+			}
 			Request::RegisterTimeline(timeline_id) => {
 				self.timelines.insert(timeline_id, id);
 			}
@@ -133,56 +197,29 @@ impl Agent for TimelineAgent {
 					self.link.respond(timeline_container, Response::RemoveTimeline(id));
 				}
 			}
-			Request::LoadStorageTimelines => {
-				if let Some(_timeline_container) = self.timeline_container {
-					let storage: Vec<SoshalTimelineStorage> = match gloo_storage::LocalStorage::get("SoshalThingYew Timelines") {
-						Ok(storage) => storage,
-						Err(err) => {
-							if let StorageError::SerdeError(_) | StorageError::JsError(_) =  err {
-								log_warn!("Failed to parse timeline storage", err);
-							}
-
-							Vec::new()
-						}
-					};
-
-					let callbacks = storage.into_iter().map(|t| {
-						let name = t.title.clone();
-						let width = t.width;
-						let column_count = t.column_count;
-						let container = t.container;
-						let filters = t.filters;
-						let sort_method = t.sort_method;
-						let compact = t.compact;
-						let animated_as_gifs = t.animated_as_gifs;
-						let hide_text = t.hide_text;
-
-						(
-							t.endpoints,
-							Box::new(move |id, endpoints|
-								 yew::props! { TimelineProps {
-									name,
-									id,
-									endpoints,
-									container,
-									width,
-									column_count,
-									filters,
-									sort_method,
-									compact,
-									animated_as_gifs,
-									hide_text,
-								}}
-							) as TimelinePropsEndpointsClosure,
-						)
-					}).collect();
-
-					self.endpoint_agent.send(EndpointRequest::BatchNewEndpoints(callbacks));
-				}
-			}
+			Request::LoadStorageTimelines => self.load_timelines(),
 			Request::LoadedStorageTimelines(timelines) => {
 				log::debug!("Received endpoints for {} timelines", timelines.len());
 			}
+			//The current workspace is saved before switching away from it
+			Request::SwitchWorkspace(workspace) => self.save_timelines(self.workspace.clone(), Some(workspace)),
+			Request::SaveWorkspace(name) => self.save_timelines(Some(name), None),
+			Request::SerializedTimeline(timeline_id, timeline) => self.gathered_timeline(timeline_id, timeline),
+			Request::RemoveWorkspace(name) => {
+				let removed = get_workspaces().and_then(|mut workspaces| {
+					workspaces.retain(|w| w.name != name);
+					set_workspaces(&workspaces)
+				});
+				if let Err(err) = removed {
+					notify_storage_error(err.with_message("Failed to remove the workspace"));
+				}
+
+				if self.workspace.as_ref() == Some(&name) {
+					self.switch_workspace(None);
+				} else {
+					self.send_workspaces();
+				}
+			}
 			Request::BatchAction(action, timelines, filters) => {
 				//TODO timelines: Iterator<Item = HandlerId>?
 				let timelines: Vec<HandlerId> = if timelines.is_empty() {
@@ -207,31 +244,247 @@ impl Agent for TimelineAgent {
 			self.timeline_container = None
 		}else if self.display_mode == Some(id) {
 			self.display_mode = None
+		}else if self.workspace_options == Some(id) {
+			self.workspace_options = None
+		}else if let Some(timeline_id) = self.timelines.iter().find(|(_, handler)| **handler == id).map(|(timeline_id, _)| *timeline_id) {
+			self.timelines.remove(&timeline_id);
+			self.gathered_timeline(timeline_id, None);
+		}
+	}
+}
+
+impl TimelineAgent {
+	fn load_timelines(&mut self) {
+		if self.timeline_container.is_none() {
+			return;
+		}
+
+		let timelines = stored_timelines(self.workspace.as_deref()).unwrap_or_else(|err| {
+			notify_storage_error(err);
+			Vec::new()
+		});
+		let callbacks = timelines.into_iter().map(|t| {
+			let name = t.title.clone();
+			let width = t.width;
+			let column_count = t.column_count;
+			let container = t.container;
+			let filters = t.filters;
+			let filter_preset = t.filter_preset;
+			let sort_method = t.sort_method;
+			let compact = t.compact;
+			let animated_as_gifs = t.animated_as_gifs;
+			let hide_text = t.hide_text;
+			let collapse_duplicates = t.collapse_duplicates;
+
+			(
+				t.endpoints,
+				Box::new(move |id, endpoints|
+					 yew::props! { TimelineProps {
+						name,
+						id,
+						endpoints,
+						container,
+						width,
+						column_count,
+						filters,
+						filter_preset,
+						sort_method,
+						compact,
+						animated_as_gifs,
+						hide_text,
+						collapse_duplicates,
+					}}
+				) as TimelinePropsEndpointsClosure,
+			)
+		}).collect();
+
+		self.endpoint_agent.send(EndpointRequest::BatchNewEndpoints(callbacks));
+	}
+
+	/// Gathers the live timelines then writes them, switching workspace afterwards if `switch_to` is set
+	fn save_timelines(&mut self, workspace: Option<String>, switch_to: Option<Option<String>>) {
+		if self.pending_save.is_some() {
+			log::warn!("Timelines are already being saved");
+			return;
+		}
+
+		self.pending_save = Some(PendingSave {
+			workspace,
+			remaining: self.timelines.keys().copied().collect(),
+			timelines: BTreeMap::new(),
+			switch_to,
+		});
+		if self.timelines.is_empty() {
+			self.serialize_endpoints();
+		} else {
+			for timeline in self.timelines.values() {
+				self.link.respond(*timeline, Response::SerializeTimeline);
+			}
+		}
+	}
+
+	fn gathered_timeline(&mut self, timeline_id: TimelineId, timeline: Option<SoshalTimelineStorage>) {
+		if let Some(pending) = &mut self.pending_save {
+			if !pending.remaining.remove(&timeline_id) {
+				return;
+			}
+			if let Some(timeline) = timeline {
+				pending.timelines.insert(timeline_id, timeline);
+			}
+			if pending.remaining.is_empty() {
+				self.serialize_endpoints();
+			}
+		}
+	}
+
+	/// Endpoints are serialized by the endpoint agent, for the gathered timelines in order
+	fn serialize_endpoints(&mut self) {
+		if let Some(pending) = &self.pending_save {
+			self.endpoint_agent.send(EndpointRequest::SerializeTimelineEndpoints(
+				pending.timelines.keys().copied().collect(),
+				self.link.callback(Msg::SerializedEndpoints),
+			));
+		}
+	}
+
+	fn switch_workspace(&mut self, workspace: Option<String>) {
+		set_current_workspace(workspace.as_deref());
+		if let Err(err) = set_workspace_param(workspace.as_deref()) {
+			log_warn!("Failed to update the workspace query parameter", err);
+		}
+		self.workspace = workspace;
+
+		if let Some(timeline_container) = self.timeline_container {
+			self.link.respond(timeline_container, Response::ClearTimelines);
+		}
+		self.load_timelines();
+		self.send_workspaces();
+	}
+
+	fn send_workspaces(&self) {
+		if let Some(workspace_options) = self.workspace_options {
+			let names = match get_workspaces() {
+				Ok(workspaces) => workspaces.into_iter().map(|w| w.name).collect(),
+				Err(err) => {
+					log::warn!("{}", err);
+					Vec::new()
+				}
+			};
+			self.link.respond(workspace_options, Response::UpdatedWorkspaces(names, self.workspace.clone()));
+		}
+	}
+}
+
+/// Timelines of the named workspace, or of the default "Timelines" storage
+fn stored_timelines(workspace: Option<&str>) -> Result<Vec<SoshalTimelineStorage>> {
+	match workspace {
+		Some(name) => match get_workspaces()?.into_iter().find(|w| w.name == name) {
+			Some(workspace) => Ok(workspace.timelines),
+			None => {
+				log::warn!("Couldn't find workspace \"{}\"", name);
+				Ok(Vec::new())
+			}
+		}
+		None => read_storage("SoshalThingYew Timelines"),
+	}
+}
+
+/// Writes the timelines of the named workspace, or of the default "Timelines" storage
+///
+/// Fails without writing if the other workspaces couldn't be read.
+fn set_stored_timelines(workspace: Option<&str>, timelines: Vec<SoshalTimelineStorage>) -> Result<()> {
+	match workspace {
+		Some(name) => {
+			let mut workspaces = get_workspaces()?;
+			match workspaces.iter_mut().find(|w| w.name == name) {
+				Some(workspace) => workspace.timelines = timelines,
+				None => workspaces.push(SoshalWorkspaceStorage { name: name.to_owned(), timelines }),
+			}
+			set_workspaces(&workspaces)
+		}
+		None => Ok(gloo_storage::LocalStorage::set("SoshalThingYew Timelines", timelines)?),
+	}
+}
+
+/// Lets `update` edit the stored endpoints of the current workspace, writing them back if it returned true for any
+pub fn update_stored_endpoints(mut update: impl FnMut(&mut EndpointSerialized) -> bool) {
+	let workspace = workspace_param().or_else(get_current_workspace);
+	let mut timelines = match stored_timelines(workspace.as_deref()) {
+		Ok(timelines) => timelines,
+		Err(err) => {
+			notify_storage_error(err.with_message("Failed to update the stored endpoints"));
+			return;
 		}
+	};
+
+	let mut changed = false;
+	for endpoint in timelines.iter_mut().flat_map(|t| t.endpoints.iter_mut()) {
+		changed |= update(endpoint);
+	}
+
+	if changed {
+		if let Err(err) = set_stored_timelines(workspace.as_deref(), timelines) {
+			notify_storage_error(err.with_message("Failed to save the timelines"));
+		}
+	}
+}
+
+/// `?workspace=name` in the page url
+fn workspace_param() -> Option<String> {
+	let search = gloo_utils::window().location().search().ok()?;
+	web_sys::UrlSearchParams::new_with_str(&search).ok()?.get("workspace")
+}
+
+fn set_workspace_param(workspace: Option<&str>) -> std::result::Result<(), JsValue> {
+	let window = gloo_utils::window();
+	let location = window.location();
+	let search = web_sys::UrlSearchParams::new_with_str(&location.search()?)?;
+	match workspace {
+		Some(workspace) => search.set("workspace", workspace),
+		None => search.delete("workspace"),
 	}
+
+	let search = String::from(search.to_string());
+	let url = match search.is_empty() {
+		true => format!("{}{}", location.pathname()?, location.hash()?),
+		false => format!("{}?{}{}", location.pathname()?, search, location.hash()?),
+	};
+	window.history()?.replace_state_with_url(&JsValue::NULL, "", Some(&url))
 }
 
-#[derive(Serialize, Deserialize)]
+/// Named set of timelines, switchable from the sidebar
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SoshalWorkspaceStorage {
+	pub name: String,
+	#[serde(default)]
+	pub timelines: Vec<SoshalTimelineStorage>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct SoshalTimelineStorage {
-	title: String,
+	pub title: String,
 	#[serde(default)]
-	container: Container,
+	pub container: Container,
 	#[serde(default)]
-	endpoints: Vec<EndpointSerialized>,
+	pub endpoints: Vec<EndpointSerialized>,
 	#[serde(default = "default_1")]
-	column_count: u8,
+	pub column_count: u8,
 	#[serde(default = "default_1")]
-	width: u8,
+	pub width: u8,
 	#[serde(default)]
-	filters: Option<FilterCollection>,
+	pub filters: Option<FilterCollection>,
+	#[serde(default)]
+	pub filter_preset: Option<String>,
 	#[serde(default = "default_sort_method")]
-	sort_method: Option<(SortMethod, bool)>,
+	pub sort_method: Option<(SortMethod, bool)>,
+	#[serde(default)]
+	pub compact: bool,
 	#[serde(default)]
-	compact: bool,
+	pub animated_as_gifs: bool,
 	#[serde(default)]
-	animated_as_gifs: bool,
+	pub hide_text: bool,
 	#[serde(default)]
-	hide_text: bool,
+	pub collapse_duplicates: bool,
 }
 
 fn default_1() -> u8 {
@@ -0,0 +1,155 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use super::filters::FilterCollection;
+use super::sort_methods::SortMethod;
+use crate::articles::ArticleWeak;
+
+/// Sorted articles of a timeline with the index of the filter rejecting each one,
+/// kept up to date instead of being recomputed on every redraw
+#[derive(Default)]
+pub struct ArticleIndex {
+	entries: Vec<(ArticleWeak, Option<usize>)>,
+	/// Incremented on every change, to know when data derived from the index is stale
+	generation: usize,
+}
+
+impl ArticleIndex {
+	pub fn new(articles: &[ArticleWeak], filters: &FilterCollection, sort_method: &(Option<SortMethod>, bool)) -> Self {
+		let mut index = Self::default();
+		index.insert(articles, filters, sort_method);
+		index
+	}
+
+	pub fn entries(&self) -> &[(ArticleWeak, Option<usize>)] {
+		&self.entries
+	}
+
+	pub fn generation(&self) -> usize {
+		self.generation
+	}
+
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.generation += 1;
+	}
+
+	/// Adds articles missing from the index, and re-evaluates the ones already in it
+	pub fn insert(&mut self, articles: &[ArticleWeak], filters: &FilterCollection, sort_method: &(Option<SortMethod>, bool)) {
+		self.entries.retain(|(a, _)| a.strong_count() > 0);
+		let existing: HashSet<*const ()> = self.entries.iter().map(|(a, _)| article_ptr(a)).collect();
+		let (updated, new): (Vec<&ArticleWeak>, Vec<&ArticleWeak>) = articles.iter()
+			.partition(|a| existing.contains(&article_ptr(a)));
+
+		if !updated.is_empty() {
+			self.update(&updated.into_iter().cloned().collect::<Vec<ArticleWeak>>(), filters, sort_method);
+		}
+		self.generation += 1;
+
+		let now = js_sys::Date::now();
+		for article in new {
+			let entry = (article.clone(), rejecting_index(article, filters));
+			match sort_method {
+				//Keys changing over time leave the entries out of order for a binary search
+				(Some(method), _) if method.is_time_dependent() => self.entries.push(entry),
+				(Some(method), reversed) => {
+					let position = self.entries.partition_point(|(a, _)| compare(method, *reversed, a, article, now) != Ordering::Greater);
+					self.entries.insert(position, entry);
+				}
+				(None, _) => self.entries.push(entry),
+			}
+		}
+
+		if let (Some(method), reversed) = sort_method {
+			if method.is_time_dependent() {
+				self.entries.sort_by(|(a, _), (b, _)| compare(method, *reversed, a, b, now));
+			}
+		}
+	}
+
+	/// Re-evaluates mutated articles, moving them if their sort key changed
+	pub fn update(&mut self, articles: &[ArticleWeak], filters: &FilterCollection, sort_method: &(Option<SortMethod>, bool)) {
+		let updated: HashSet<*const ()> = articles.iter().map(article_ptr).collect();
+		self.generation += 1;
+
+		match sort_method {
+			(Some(_), _) => {
+				let moved: Vec<ArticleWeak> = self.entries.iter()
+					.filter(|(a, _)| updated.contains(&article_ptr(a)))
+					.map(|(a, _)| a.clone())
+					.collect();
+				self.entries.retain(|(a, _)| !updated.contains(&article_ptr(a)));
+				self.insert(&moved, filters, sort_method);
+			}
+			(None, _) => for (article, rejected_by) in self.entries.iter_mut() {
+				if updated.contains(&article_ptr(article)) {
+					*rejected_by = rejecting_index(article, filters);
+				}
+			}
+		}
+	}
+
+	/// Re-evaluates every article after the filters changed
+	pub fn refilter(&mut self, filters: &FilterCollection) {
+		self.generation += 1;
+		for (article, rejected_by) in self.entries.iter_mut() {
+			*rejected_by = rejecting_index(article, filters);
+		}
+	}
+
+	/// Without a sort method, articles follow the order of `articles`
+	pub fn resort(&mut self, articles: &[ArticleWeak], sort_method: &(Option<SortMethod>, bool)) {
+		self.generation += 1;
+		match sort_method {
+			(Some(method), reversed) => {
+				let now = js_sys::Date::now();
+				self.entries.sort_by(|(a, _), (b, _)| compare(method, *reversed, a, b, now))
+			}
+			(None, _) => {
+				let order: HashMap<*const (), usize> = articles.iter()
+					.enumerate()
+					.map(|(i, a)| (article_ptr(a), i))
+					.collect();
+				self.entries.sort_by_key(|(a, _)| order.get(&article_ptr(a)).copied().unwrap_or(usize::MAX));
+			}
+		}
+	}
+
+	/// Drops forgotten and already dropped articles
+	pub fn forget(&mut self, forgotten: &[ArticleWeak]) -> bool {
+		let forgotten: HashSet<*const ()> = forgotten.iter().map(article_ptr).collect();
+		let len = self.entries.len();
+		self.entries.retain(|(a, _)| a.strong_count() > 0 && !forgotten.contains(&article_ptr(a)));
+		self.generation += 1;
+		self.entries.len() != len
+	}
+
+	/// Number of articles rejected by each filter
+	pub fn rejection_counts(&self, filter_count: usize) -> Vec<usize> {
+		let mut counts = vec![0; filter_count];
+		for index in self.entries.iter().filter_map(|(_, rejected_by)| *rejected_by) {
+			if let Some(count) = counts.get_mut(index) {
+				*count += 1;
+			}
+		}
+		counts
+	}
+}
+
+fn rejecting_index(article: &ArticleWeak, filters: &FilterCollection) -> Option<usize> {
+	match article.upgrade() {
+		Some(strong) => filters.rejecting_index(&strong.borrow()),
+		None => None,
+	}
+}
+
+fn compare(method: &SortMethod, reversed: bool, a: &ArticleWeak, b: &ArticleWeak, now: f64) -> Ordering {
+	match reversed {
+		false => method.compare_at(a, b, now),
+		true => method.compare_at(a, b, now).reverse(),
+	}
+}
+
+fn article_ptr(article: &ArticleWeak) -> *const () {
+	article.as_ptr() as *const ()
+}
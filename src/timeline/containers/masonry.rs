@@ -1,6 +1,6 @@
 use yew::prelude::*;
 
-use super::ContainerProps;
+use super::{ContainerProps, gap_markers};
 use crate::articles::{ArticleComponent, ArticleBox};
 use crate::timeline::ArticleStruct;
 use crate::timeline::containers::ContainerMsg;
@@ -55,7 +55,7 @@ impl MasonryContainer {
 			<div class="articlesContainer masonryContainer" ref={ctx.props().container_ref.clone()}>
 				{ for columns.iter().enumerate().map(|(column_index, (column, _))| html! {
 					<div class="masonryColumn" key={column_index}>
-						{ for column.iter().enumerate().map(|(load_priority, article_struct)| html! {
+						{ for column.iter().enumerate().flat_map(|(load_priority, article_struct)| std::iter::once(html! {
 							<ArticleComponent
 								key={format!("{:?}{}", &article_view, article_struct.boxed.id())}
 								article_struct={(*article_struct).clone()}
@@ -68,7 +68,7 @@ impl MasonryContainer {
 								column_count={ctx.props().column_count}
 								app_settings={ctx.props().app_settings}
 							/>
-						}) }
+						}).chain(gap_markers(ctx.props(), article_struct))) }
 					</div>
 				})}
 			</div>
@@ -73,6 +73,17 @@ pub struct ContainerProps {
 	pub articles: Vec<ArticleStruct>,
 	pub lazy_loading: bool,
 	pub app_settings: AppSettings,
+	/// Keyed markers shown after the article with this global id
+	#[prop_or_default]
+	pub gap_markers: Vec<(String, Html)>,
+}
+
+/// Markers of the gaps right below this article
+pub fn gap_markers<'a>(props: &'a ContainerProps, article_struct: &ArticleStruct) -> impl Iterator<Item = Html> + 'a {
+	let global_id = article_struct.global_id();
+	props.gap_markers.iter()
+		.filter(move |(after, _)| after == &global_id)
+		.map(|(_, marker)| marker.clone())
 }
 
 pub enum ContainerMsg {
@@ -84,7 +95,7 @@ pub fn column_container(props: &ContainerProps) -> Html {
 	let article_view = props.article_view.clone();
 	html! {
 		<div class="articlesContainer columnContainer" ref={props.container_ref.clone()}>
-			{ for props.articles.iter().enumerate().map(|(load_priority, article_struct)| html! {
+			{ for props.articles.iter().enumerate().flat_map(|(load_priority, article_struct)| std::iter::once(html! {
 				<ArticleComponent
 					key={format!("{:?}{}", &article_view, article_struct.boxed.id())}
 					article_struct={article_struct.clone()}
@@ -97,7 +108,7 @@ pub fn column_container(props: &ContainerProps) -> Html {
 					column_count=1
 					app_settings={props.app_settings}
 				/>
-			}) }
+			}).chain(gap_markers(props, article_struct))) }
 		</div>
 	}
 }
@@ -113,7 +124,7 @@ pub fn row_container(props: &ContainerProps) -> Html {
 	let article_view = props.article_view.clone();
 	html! {
 		<div class="articlesContainer rowContainer" ref={props.container_ref.clone()} {style}>
-			{ for props.articles.iter().enumerate().map(|(load_priority, article_struct)| { html! {
+			{ for props.articles.iter().enumerate().flat_map(|(load_priority, article_struct)| std::iter::once(html! {
 				<ArticleComponent
 					key={format!("{:?}{}", &article_view, article_struct.boxed.id())}
 					article_struct={article_struct.clone()}
@@ -127,7 +138,7 @@ pub fn row_container(props: &ContainerProps) -> Html {
 					column_count={props.column_count}
 					app_settings={props.app_settings}
 				/>
-			}}) }
+			}).chain(gap_markers(props, article_struct))) }
 		</div>
 	}
 }
\ No newline at end of file
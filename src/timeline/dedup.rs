@@ -0,0 +1,303 @@
+use std::cell::Ref;
+use std::collections::{HashMap, HashSet};
+use yew_agent::{Agent, AgentLink, Context, HandlerId};
+use gloo_timers::callback::Timeout;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement};
+
+use crate::articles::{ArticleData, ArticleWeak, MediaType};
+
+/// Max differing bits for two thumbnails to be considered the same picture
+const MAX_HASH_DISTANCE: u32 = 4;
+/// Thumbnails with a smaller luma range are too uniform for their hash to mean anything
+const MIN_LUMA_RANGE: u32 = 24;
+/// Time new hashes are batched for before being sent to timelines
+const HASH_BATCH_DELAY: u32 = 500;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum DuplicateKey {
+	/// Same article, through a repost
+	Article(&'static str, String),
+	/// Media url without its query
+	Media(String),
+	/// Pixiv post, or a link to it
+	Pixiv(String),
+}
+
+/// For each article, the index of the earlier article it duplicates
+pub fn find_duplicates(articles: &[ArticleWeak], hashes: &HashMap<String, u64>) -> Vec<Option<usize>> {
+	let mut parents: Vec<usize> = (0..articles.len()).collect();
+	let mut key_owners: HashMap<DuplicateKey, usize> = HashMap::new();
+	let mut article_hashes: Vec<(usize, u64)> = Vec::new();
+
+	for (i, article) in articles.iter().enumerate() {
+		let strong = match article.upgrade() {
+			Some(strong) => strong,
+			None => continue,
+		};
+		let borrow = strong.borrow();
+		let actual = borrow.actual_article().and_then(|a| a.upgrade());
+		let actual_borrow = actual.as_ref().map(|a| a.borrow());
+		let actual_borrow = actual_borrow.as_ref().unwrap_or(&borrow);
+
+		for key in duplicate_keys(actual_borrow) {
+			match key_owners.get(&key) {
+				Some(owner) => union(&mut parents, *owner, i),
+				None => { key_owners.insert(key, i); }
+			}
+		}
+
+		if let Some(hash) = thumbnail_url(actual_borrow).and_then(|url| hashes.get(&url)) {
+			for (other, other_hash) in &article_hashes {
+				if (hash ^ other_hash).count_ones() <= MAX_HASH_DISTANCE {
+					union(&mut parents, *other, i);
+				}
+			}
+			article_hashes.push((i, *hash));
+		}
+	}
+
+	(0..articles.len())
+		.map(|i| {
+			let root = find(&mut parents, i);
+			if root == i { None } else { Some(root) }
+		})
+		.collect()
+}
+
+fn find(parents: &mut Vec<usize>, mut i: usize) -> usize {
+	while parents[i] != i {
+		parents[i] = parents[parents[i]];
+		i = parents[i];
+	}
+	i
+}
+
+/// The earliest article stays the root
+fn union(parents: &mut Vec<usize>, a: usize, b: usize) {
+	let (a, b) = (find(parents, a), find(parents, b));
+	if a < b {
+		parents[b] = a;
+	} else if b < a {
+		parents[a] = b;
+	}
+}
+
+fn duplicate_keys(article: &Ref<dyn ArticleData>) -> Vec<DuplicateKey> {
+	let mut keys = vec![DuplicateKey::Article(article.service(), article.id())];
+
+	keys.extend(article.media().into_iter()
+		.map(|media| DuplicateKey::Media(strip_query(&media.src).to_owned())));
+
+	if article.service() == "Pixiv" {
+		keys.push(DuplicateKey::Pixiv(article.id()));
+	}
+	keys.extend(article.links().iter()
+		.chain(std::iter::once(&article.text()))
+		.flat_map(|text| pixiv_ids(text))
+		.map(DuplicateKey::Pixiv));
+
+	keys
+}
+
+fn strip_query(url: &str) -> &str {
+	url.split(|c| c == '?' || c == '#').next().unwrap_or(url)
+}
+
+/// Ids of the Pixiv posts linked in the text
+pub fn pixiv_ids(text: &str) -> Vec<String> {
+	["pixiv.net/artworks/", "pixiv.net/en/artworks/", "illust_id="].iter()
+		.flat_map(|pattern| text.match_indices(pattern).map(move |(i, _)| &text[i + pattern.len()..]))
+		.map(|rest| rest.chars().take_while(char::is_ascii_digit).collect::<String>())
+		.filter(|id| !id.is_empty())
+		.collect()
+}
+
+/// First image of the article, used for perceptual hashing
+pub fn thumbnail_url(article: &Ref<dyn ArticleData>) -> Option<String> {
+	article.media().into_iter()
+		.find(|media| media.media_type == MediaType::Image)
+		.map(|media| media.src)
+}
+
+fn luma(pixels: &[u8]) -> Vec<u32> {
+	pixels.chunks_exact(4)
+		.map(|p| (p[0] as u32 * 299 + p[1] as u32 * 587 + p[2] as u32 * 114) / 1000)
+		.collect()
+}
+
+/// Near-uniform RGBA picture, like a blank or placeholder thumbnail
+pub fn is_uniform(pixels: &[u8]) -> bool {
+	let luma = luma(pixels);
+	let min = luma.iter().min().copied().unwrap_or_default();
+	let max = luma.iter().max().copied().unwrap_or_default();
+	max - min < MIN_LUMA_RANGE
+}
+
+/// Difference hash from a 9x8 RGBA picture, one bit per horizontal gradient
+pub fn dhash(pixels: &[u8]) -> u64 {
+	let luma = luma(pixels);
+
+	let mut hash = 0u64;
+	for y in 0..8 {
+		for x in 0..8 {
+			hash <<= 1;
+			if luma[y * 9 + x] < luma[y * 9 + x + 1] {
+				hash |= 1;
+			}
+		}
+	}
+	hash
+}
+
+/// Computes thumbnail hashes through a canvas, shared by every timeline
+pub struct ThumbnailHashAgent {
+	link: AgentLink<Self>,
+	subscribers: HashSet<HandlerId>,
+	hashes: HashMap<String, u64>,
+	/// Hashes computed since the last batch was sent
+	new_hashes: HashMap<String, u64>,
+	batch_timeout: Option<Timeout>,
+	/// Thumbnails that failed to load, tainted the canvas or are too uniform
+	failed: HashSet<String>,
+	loading: HashMap<String, (HtmlImageElement, Closure<dyn Fn()>, Closure<dyn Fn()>)>,
+	canvas: CanvasRenderingContext2d,
+}
+
+pub enum ThumbnailHashMsg {
+	Loaded(String),
+	Failed(String),
+	SendHashes,
+}
+
+pub enum ThumbnailHashRequest {
+	Hash(Vec<String>),
+}
+
+pub enum ThumbnailHashResponse {
+	/// Only the hashes the subscriber doesn't have yet
+	UpdatedHashes(HashMap<String, u64>),
+}
+
+type Msg = ThumbnailHashMsg;
+type Request = ThumbnailHashRequest;
+type Response = ThumbnailHashResponse;
+
+impl Agent for ThumbnailHashAgent {
+	type Reach = Context<Self>;
+	type Message = Msg;
+	type Input = Request;
+	type Output = Response;
+
+	fn create(link: AgentLink<Self>) -> Self {
+		let canvas: HtmlCanvasElement = gloo_utils::document().create_element("canvas").unwrap()
+			.dyn_into().unwrap();
+		canvas.set_width(9);
+		canvas.set_height(8);
+		let canvas = canvas.get_context("2d").unwrap().unwrap()
+			.dyn_into().unwrap();
+
+		Self {
+			link,
+			subscribers: HashSet::new(),
+			hashes: HashMap::new(),
+			new_hashes: HashMap::new(),
+			batch_timeout: None,
+			failed: HashSet::new(),
+			loading: HashMap::new(),
+			canvas,
+		}
+	}
+
+	fn update(&mut self, msg: Self::Message) {
+		match msg {
+			Msg::Loaded(url) => {
+				if let Some((image, _, _)) = self.loading.remove(&url) {
+					let pixels = self.canvas.draw_image_with_html_image_element_and_dw_and_dh(&image, 0.0, 0.0, 9.0, 8.0)
+						.and_then(|_| self.canvas.get_image_data(0.0, 0.0, 9.0, 8.0));
+					match pixels {
+						Ok(pixels) if is_uniform(&pixels.data()) => {
+							log::trace!("{} is too uniform to hash", &url);
+							self.failed.insert(url);
+						}
+						Ok(pixels) => {
+							let hash = dhash(&pixels.data());
+							self.hashes.insert(url.clone(), hash);
+							self.new_hashes.insert(url, hash);
+							if self.batch_timeout.is_none() {
+								let callback = self.link.callback(|_| Msg::SendHashes);
+								self.batch_timeout = Some(Timeout::new(HASH_BATCH_DELAY, move || callback.emit(())));
+							}
+						}
+						Err(_) => {
+							log::trace!("Couldn't read {} from canvas", &url);
+							self.failed.insert(url);
+						}
+					}
+				}
+			}
+			Msg::Failed(url) => {
+				self.loading.remove(&url);
+				self.failed.insert(url);
+			}
+			Msg::SendHashes => {
+				self.batch_timeout = None;
+				let hashes = std::mem::take(&mut self.new_hashes);
+				for sub in &self.subscribers {
+					if sub.is_respondable() {
+						self.link.respond(*sub, Response::UpdatedHashes(hashes.clone()));
+					}
+				}
+			}
+		}
+	}
+
+	fn connected(&mut self, id: HandlerId) {
+		self.subscribers.insert(id);
+	}
+
+	fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+		match msg {
+			Request::Hash(urls) => {
+				let mut known = HashMap::new();
+				for url in urls {
+					if let Some(hash) = self.hashes.get(&url) {
+						known.insert(url, *hash);
+						continue;
+					}
+					if self.failed.contains(&url) || self.loading.contains_key(&url) {
+						continue;
+					}
+
+					let image: HtmlImageElement = gloo_utils::document().create_element("img").unwrap()
+						.dyn_into().unwrap();
+					image.set_cross_origin(Some("anonymous"));
+
+					let onload = {
+						let callback = self.link.callback(Msg::Loaded);
+						let url = url.clone();
+						Closure::wrap(Box::new(move || callback.emit(url.clone())) as Box<dyn Fn()>)
+					};
+					let onerror = {
+						let callback = self.link.callback(Msg::Failed);
+						let url = url.clone();
+						Closure::wrap(Box::new(move || callback.emit(url.clone())) as Box<dyn Fn()>)
+					};
+					image.set_onload(Some(onload.as_ref().unchecked_ref()));
+					image.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+					image.set_src(&url);
+
+					self.loading.insert(url, (image, onload, onerror));
+				}
+
+				if !known.is_empty() && id.is_respondable() {
+					self.link.respond(id, Response::UpdatedHashes(known));
+				}
+			}
+		}
+	}
+
+	fn disconnected(&mut self, id: HandlerId) {
+		self.subscribers.remove(&id);
+	}
+}
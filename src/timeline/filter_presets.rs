@@ -0,0 +1,351 @@
+use std::collections::HashSet;
+use serde::{Serialize, Deserialize};
+use yew::prelude::*;
+use yew_agent::{Agent, AgentLink, Bridge, Bridged, Context as AgentContext, HandlerId};
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use wasm_bindgen::JsCast;
+
+use super::filters::{FilterCollection, FilterMsg};
+use crate::components::{Dropdown, DropdownLabel};
+use crate::services::storages::{get_filter_presets, notify_storage_error, set_filter_presets};
+use crate::log_warn;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FilterPreset {
+	pub name: String,
+	pub filters: FilterCollection,
+}
+
+/// Accepts either a list of presets or a single one
+pub fn parse_presets(json: &str) -> serde_json::Result<Vec<FilterPreset>> {
+	serde_json::from_str::<Vec<FilterPreset>>(json)
+		.or_else(|err| serde_json::from_str::<FilterPreset>(json)
+			.map(|preset| vec![preset])
+			.map_err(|_| err)
+		)
+}
+
+pub struct FilterPresetAgent {
+	link: AgentLink<Self>,
+	subscribers: HashSet<HandlerId>,
+	presets: Vec<FilterPreset>,
+	/// Unset when the stored presets couldn't be read, so they aren't overwritten
+	writable: bool,
+}
+
+pub enum FilterPresetRequest {
+	GetPresets,
+	/// Replaces the preset with the same name, `propagate` updating the timelines linked to it
+	Save {
+		preset: FilterPreset,
+		propagate: bool,
+	},
+	Remove(String),
+	/// Presets with an existing name replace it, without propagating
+	Import(Vec<FilterPreset>),
+}
+
+#[derive(Clone)]
+pub enum FilterPresetResponse {
+	/// Linked timelines adopt the filters of their preset
+	UpdatedPresets(Vec<FilterPreset>),
+	PresetChanged(FilterPreset),
+	/// Replaced without propagating, linked timelines with other filters unlink from it
+	PresetReplaced(FilterPreset),
+}
+
+type Request = FilterPresetRequest;
+type Response = FilterPresetResponse;
+
+impl Agent for FilterPresetAgent {
+	type Reach = AgentContext<Self>;
+	type Message = ();
+	type Input = Request;
+	type Output = Response;
+
+	fn create(link: AgentLink<Self>) -> Self {
+		let (presets, writable) = match get_filter_presets() {
+			Ok(presets) => (presets, true),
+			Err(err) => {
+				notify_storage_error(err);
+				(Vec::new(), false)
+			}
+		};
+
+		Self {
+			link,
+			subscribers: HashSet::new(),
+			presets,
+			writable,
+		}
+	}
+
+	fn update(&mut self, _msg: Self::Message) {}
+
+	fn connected(&mut self, id: HandlerId) {
+		self.subscribers.insert(id);
+	}
+
+	fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+		match msg {
+			Request::GetPresets => self.link.respond(id, Response::UpdatedPresets(self.presets.clone())),
+			Request::Save { preset, propagate } => {
+				self.insert(preset.clone());
+				if propagate {
+					self.respond_all(Response::PresetChanged(preset));
+				} else {
+					self.respond_all(Response::PresetReplaced(preset));
+				}
+				self.save();
+			}
+			Request::Remove(name) => {
+				self.presets.retain(|preset| preset.name != name);
+				self.save();
+			}
+			Request::Import(presets) => {
+				for preset in presets {
+					if self.presets.iter().any(|p| p.name == preset.name) {
+						self.respond_all(Response::PresetReplaced(preset.clone()));
+					}
+					self.insert(preset);
+				}
+				self.save();
+			}
+		}
+	}
+
+	fn disconnected(&mut self, id: HandlerId) {
+		self.subscribers.remove(&id);
+	}
+}
+
+impl FilterPresetAgent {
+	fn insert(&mut self, preset: FilterPreset) {
+		match self.presets.iter_mut().find(|p| p.name == preset.name) {
+			Some(existing) => *existing = preset,
+			None => self.presets.push(preset),
+		}
+	}
+
+	fn save(&self) {
+		if self.writable {
+			set_filter_presets(&self.presets);
+		}
+		self.respond_all(Response::UpdatedPresets(self.presets.clone()));
+	}
+
+	fn respond_all(&self, response: Response) {
+		for sub in &self.subscribers {
+			if sub.is_respondable() {
+				self.link.respond(*sub, response.clone());
+			}
+		}
+	}
+}
+
+/// Loading and saving presets, as part of `FiltersOptions`
+pub struct FilterPresetsOptions {
+	presets: Vec<FilterPreset>,
+	propagate: bool,
+	name_ref: NodeRef,
+	agent: Box<dyn Bridge<FilterPresetAgent>>,
+}
+
+pub enum FilterPresetsOptionsMsg {
+	AgentResponse(FilterPresetResponse),
+	Save,
+	TogglePropagate,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct FilterPresetsOptionsProps {
+	pub filters: FilterCollection,
+	pub callback: Callback<FilterMsg>,
+}
+
+impl Component for FilterPresetsOptions {
+	type Message = FilterPresetsOptionsMsg;
+	type Properties = FilterPresetsOptionsProps;
+
+	fn create(ctx: &Context<Self>) -> Self {
+		let mut agent = FilterPresetAgent::bridge(ctx.link().callback(FilterPresetsOptionsMsg::AgentResponse));
+		agent.send(FilterPresetRequest::GetPresets);
+
+		Self {
+			presets: Vec::new(),
+			propagate: true,
+			name_ref: NodeRef::default(),
+			agent,
+		}
+	}
+
+	fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+		match msg {
+			FilterPresetsOptionsMsg::AgentResponse(FilterPresetResponse::UpdatedPresets(presets)) => {
+				self.presets = presets;
+				true
+			}
+			FilterPresetsOptionsMsg::AgentResponse(_) => false,
+			FilterPresetsOptionsMsg::Save => {
+				let name = match self.name_ref.cast::<HtmlInputElement>() {
+					Some(input) if !input.value().trim().is_empty() => input.value().trim().to_owned(),
+					_ => return false,
+				};
+
+				self.agent.send(FilterPresetRequest::Save {
+					preset: FilterPreset { name: name.clone(), filters: ctx.props().filters.clone() },
+					propagate: self.propagate,
+				});
+				ctx.props().callback.emit(FilterMsg::LoadPreset(name, ctx.props().filters.clone()));
+				false
+			}
+			FilterPresetsOptionsMsg::TogglePropagate => {
+				self.propagate = !self.propagate;
+				true
+			}
+		}
+	}
+
+	fn view(&self, ctx: &Context<Self>) -> Html {
+		html! {
+			<div class="field has-addons">
+				<div class="control">
+					<Dropdown current_label={DropdownLabel::Text("Load Preset".to_owned())}>
+						{ for self.presets.iter().cloned().map(|FilterPreset { name, filters }| {
+							let callback = ctx.props().callback.clone();
+							let label = name.clone();
+							html! {
+								<a class="dropdown-item" onclick={Callback::from(move |_| callback.emit(FilterMsg::LoadPreset(name.clone(), filters.clone())))}>
+									{ label }
+								</a>
+							}
+						}) }
+					</Dropdown>
+				</div>
+				<div class="control">
+					<input class="input" type="text" placeholder="Preset name" ref={self.name_ref.clone()}/>
+				</div>
+				<div class="control">
+					<button class="button" onclick={ctx.link().callback(|_| FilterPresetsOptionsMsg::Save)}>{"Save Preset"}</button>
+				</div>
+				<div class="control">
+					<label class="checkbox">
+						<input type="checkbox" checked={self.propagate} onclick={ctx.link().callback(|_| FilterPresetsOptionsMsg::TogglePropagate)}/>
+						{ " Update linked timelines" }
+					</label>
+				</div>
+			</div>
+		}
+	}
+}
+
+/// Removing, importing and exporting presets, in the settings
+pub struct FilterPresetsManager {
+	presets: Vec<FilterPreset>,
+	import_ref: NodeRef,
+	import_error: Option<String>,
+	agent: Box<dyn Bridge<FilterPresetAgent>>,
+}
+
+pub enum FilterPresetsManagerMsg {
+	AgentResponse(FilterPresetResponse),
+	Remove(String),
+	Import,
+}
+
+impl Component for FilterPresetsManager {
+	type Message = FilterPresetsManagerMsg;
+	type Properties = ();
+
+	fn create(ctx: &Context<Self>) -> Self {
+		let mut agent = FilterPresetAgent::bridge(ctx.link().callback(FilterPresetsManagerMsg::AgentResponse));
+		agent.send(FilterPresetRequest::GetPresets);
+
+		Self {
+			presets: Vec::new(),
+			import_ref: NodeRef::default(),
+			import_error: None,
+			agent,
+		}
+	}
+
+	fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+		match msg {
+			FilterPresetsManagerMsg::AgentResponse(FilterPresetResponse::UpdatedPresets(presets)) => {
+				self.presets = presets;
+				true
+			}
+			FilterPresetsManagerMsg::AgentResponse(_) => false,
+			FilterPresetsManagerMsg::Remove(name) => {
+				self.agent.send(FilterPresetRequest::Remove(name));
+				false
+			}
+			FilterPresetsManagerMsg::Import => {
+				let textarea = match self.import_ref.cast::<HtmlTextAreaElement>() {
+					Some(textarea) => textarea,
+					None => return false,
+				};
+
+				match parse_presets(&textarea.value()) {
+					Ok(presets) => {
+						self.agent.send(FilterPresetRequest::Import(presets));
+						textarea.set_value("");
+						self.import_error = None;
+					}
+					Err(err) => {
+						self.import_error = Some(err.to_string());
+						log_warn!("Failed to import filter presets", err);
+					}
+				}
+				true
+			}
+		}
+	}
+
+	fn view(&self, ctx: &Context<Self>) -> Html {
+		let export = serde_json::to_string_pretty(&self.presets).unwrap_or_default();
+		let select_all = Callback::from(|e: FocusEvent| {
+			if let Some(textarea) = e.target().and_then(|t| t.dyn_into::<HtmlTextAreaElement>().ok()) {
+				textarea.select();
+			}
+		});
+
+		html! {
+			<div class="block">
+				<label class="label">{"Filter Presets"}</label>
+				{ for self.presets.iter().map(|preset| {
+					let name = preset.name.clone();
+					html! {
+						<div class="field is-grouped">
+							<div class="control is-expanded">
+								{ format!("{} ({} filters)", &preset.name, preset.filters.len()) }
+							</div>
+							<div class="control">
+								<button class="button is-small" onclick={ctx.link().callback(move |_| FilterPresetsManagerMsg::Remove(name.clone()))}>{"Remove"}</button>
+							</div>
+						</div>
+					}
+				}) }
+				<div class="field">
+					<label class="label is-small">{"Export"}</label>
+					<div class="control">
+						<textarea class="textarea is-small" readonly=true value={export} onfocus={select_all}/>
+					</div>
+				</div>
+				<div class="field">
+					<label class="label is-small">{"Import"}</label>
+					<div class="control">
+						<textarea class={classes!("textarea", "is-small", self.import_error.as_ref().map(|_| "is-danger"))} placeholder="Paste presets JSON" ref={self.import_ref.clone()}/>
+					</div>
+					{ match &self.import_error {
+						Some(error) => html! { <p class="help is-danger">{ error.clone() }</p> },
+						None => html! {},
+					} }
+				</div>
+				<div class="control">
+					<button class="button" onclick={ctx.link().callback(|_| FilterPresetsManagerMsg::Import)}>{"Import"}</button>
+				</div>
+			</div>
+		}
+	}
+}
@@ -1,16 +1,38 @@
-use std::cell::Ref;
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use yew::prelude::*;
 use std::ops::{Deref, DerefMut};
 use web_sys::HtmlInputElement;
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen::prelude::wasm_bindgen;
 
 use crate::articles::{ArticleData, ArticleMedia, ArticleRefType, ArticleWeak, MediaType};
 use crate::components::{Dropdown, DropdownLabel};
+use super::filter_presets::FilterPresetsOptions;
 
 pub type FilterPredicate = fn(&ArticleWeak, inverted: &bool) -> bool;
 
-const ALL_FILTERS: [Filter; 9] = [
+#[wasm_bindgen]
+extern "C" {
+	#[wasm_bindgen(catch, js_name = RegExp)]
+	pub(crate) fn try_new_regexp(pattern: &str, flags: &str) -> Result<js_sys::RegExp, JsValue>;
+}
+
+thread_local! {
+	/// Case-insensitive regexes by pattern, None for invalid ones
+	static REGEXES: RefCell<HashMap<String, Option<js_sys::RegExp>>> = RefCell::new(HashMap::new());
+}
+
+/// Case-insensitive regex compiled on first use of the pattern, None if it is invalid
+pub(crate) fn cached_regexp(pattern: &str) -> Option<js_sys::RegExp> {
+	REGEXES.with(|regexes| regexes.borrow_mut()
+		.entry(pattern.to_owned())
+		.or_insert_with(|| try_new_regexp(pattern, "i").ok())
+		.clone())
+}
+
+const ALL_FILTERS: [Filter; 27] = [
 	Filter::Media,
 	Filter::Animated,
 	Filter::NotMarkedAsRead,
@@ -20,6 +42,24 @@ const ALL_FILTERS: [Filter; 9] = [
 	Filter::PlainTweet,
 	Filter::Repost { by_username: None },
 	Filter::Quote { by_username: None },
+	Filter::TextContains { keyword: String::new() },
+	Filter::TextWholeWord { word: String::new() },
+	Filter::TextRegex { pattern: String::new() },
+	Filter::Authors { usernames: Vec::new() },
+	Filter::LikeCount { min: 0, max: None },
+	Filter::RepostCount { min: 0, max: None },
+	Filter::CreatedWithin { hours: 24 },
+	Filter::MediaCount { min: 1 },
+	Filter::MediaType { media_type: MediaType::Image },
+	Filter::MediaType { media_type: MediaType::Video },
+	Filter::MediaType { media_type: MediaType::VideoGif },
+	Filter::MediaType { media_type: MediaType::Gif },
+	Filter::Orientation { orientation: Orientation::Portrait },
+	Filter::Orientation { orientation: Orientation::Landscape },
+	Filter::Orientation { orientation: Orientation::Square },
+	Filter::AspectRatio { min: None, max: None },
+	Filter::Group { operator: GroupOperator::And, filters: FilterCollection::new() },
+	Filter::Group { operator: GroupOperator::Or, filters: FilterCollection::new() },
 ];
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -37,9 +77,90 @@ pub enum Filter {
 	Quote {
 		by_username: Option<String>
 	},
+	/// Case-insensitive, on the text of the article and the ones it references
+	TextContains {
+		keyword: String,
+	},
+	TextWholeWord {
+		word: String,
+	},
+	/// Case-insensitive JS regular expression, invalid patterns matching nothing
+	TextRegex {
+		pattern: String,
+	},
+	/// Allow list, or deny list when inverted, of author usernames or names
+	Authors {
+		usernames: Vec<String>,
+	},
+	/// Inclusive range
+	LikeCount {
+		min: u32,
+		max: Option<u32>,
+	},
+	/// Inclusive range
+	RepostCount {
+		min: u32,
+		max: Option<u32>,
+	},
+	CreatedWithin {
+		hours: u32,
+	},
+	/// Counting the reposted and quoted articles' media
+	MediaCount {
+		min: u32,
+	},
+	MediaType {
+		media_type: MediaType,
+	},
+	Orientation {
+		orientation: Orientation,
+	},
+	/// Height over width, in hundredths
+	AspectRatio {
+		min: Option<u32>,
+		max: Option<u32>,
+	},
+	/// Nested filters, NOT being the instance's `inverted`
+	Group {
+		operator: GroupOperator,
+		filters: FilterCollection,
+	},
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Orientation {
+	Portrait,
+	Landscape,
+	/// Within 5% of a square
+	Square,
+}
+
+impl Orientation {
+	fn matches(&self, ratio: f32) -> bool {
+		match self {
+			Orientation::Portrait => ratio > 1.05,
+			Orientation::Landscape => ratio < 0.95,
+			Orientation::Square => (0.95..=1.05).contains(&ratio),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum GroupOperator {
+	And,
+	Or,
 }
 
 impl Filter {
+	/// Whether the result changes with the current time
+	pub fn is_time_dependent(&self) -> bool {
+		match self {
+			Filter::CreatedWithin { .. } => true,
+			Filter::Group { filters, .. } => filters.is_time_dependent(),
+			_ => false,
+		}
+	}
+
 	pub fn name(&self, inverted: bool) -> &'static str {
 		if inverted {
 			match self {
@@ -52,6 +173,24 @@ impl Filter {
 				Filter::PlainTweet => "Not a Plain Tweet",
 				Filter::Repost { .. } => "Not a Repost",
 				Filter::Quote { .. } => "No a Quote",
+				Filter::TextContains { .. } => "Text doesn't contain",
+				Filter::TextWholeWord { .. } => "Text doesn't have word",
+				Filter::TextRegex { .. } => "Text doesn't match regex",
+				Filter::Authors { .. } => "Authors not in",
+				Filter::LikeCount { .. } => "Like count outside",
+				Filter::RepostCount { .. } => "Repost count outside",
+				Filter::CreatedWithin { .. } => "Older than",
+				Filter::MediaCount { .. } => "Media count under",
+				Filter::MediaType { media_type: MediaType::Image } => "Without Image",
+				Filter::MediaType { media_type: MediaType::Video } => "Without Video",
+				Filter::MediaType { media_type: MediaType::VideoGif } => "Without Video GIF",
+				Filter::MediaType { media_type: MediaType::Gif } => "Without GIF",
+				Filter::Orientation { orientation: Orientation::Portrait } => "Without Portrait Media",
+				Filter::Orientation { orientation: Orientation::Landscape } => "Without Landscape Media",
+				Filter::Orientation { orientation: Orientation::Square } => "Without Square Media",
+				Filter::AspectRatio { .. } => "Aspect ratio outside",
+				Filter::Group { operator: GroupOperator::And, .. } => "Not all of",
+				Filter::Group { operator: GroupOperator::Or, .. } => "None of",
 			}
 		} else {
 			match self {
@@ -64,6 +203,24 @@ impl Filter {
 				Filter::PlainTweet => "Plain Tweet",
 				Filter::Repost { .. } => "Repost",
 				Filter::Quote { .. } => "Quote",
+				Filter::TextContains { .. } => "Text contains",
+				Filter::TextWholeWord { .. } => "Text has word",
+				Filter::TextRegex { .. } => "Text matches regex",
+				Filter::Authors { .. } => "Authors in",
+				Filter::LikeCount { .. } => "Like count between",
+				Filter::RepostCount { .. } => "Repost count between",
+				Filter::CreatedWithin { .. } => "Created within",
+				Filter::MediaCount { .. } => "Media count at least",
+				Filter::MediaType { media_type: MediaType::Image } => "Has Image",
+				Filter::MediaType { media_type: MediaType::Video } => "Has Video",
+				Filter::MediaType { media_type: MediaType::VideoGif } => "Has Video GIF",
+				Filter::MediaType { media_type: MediaType::Gif } => "Has GIF",
+				Filter::Orientation { orientation: Orientation::Portrait } => "Has Portrait Media",
+				Filter::Orientation { orientation: Orientation::Landscape } => "Has Landscape Media",
+				Filter::Orientation { orientation: Orientation::Square } => "Has Square Media",
+				Filter::AspectRatio { .. } => "Aspect ratio between",
+				Filter::Group { operator: GroupOperator::And, .. } => "All of",
+				Filter::Group { operator: GroupOperator::Or, .. } => "Any of",
 			}
 		}
 	}
@@ -139,6 +296,66 @@ impl Filter {
 					_ => false,
 				})
 			}
+			Filter::TextContains { keyword } => {
+				let keyword = keyword.to_lowercase();
+				article_texts(article).iter().any(|text| text.to_lowercase().contains(&keyword))
+			}
+			Filter::TextWholeWord { word } => {
+				let word = word.to_lowercase();
+				article_texts(article).iter().any(|text| contains_whole_word(&text.to_lowercase(), &word))
+			}
+			Filter::TextRegex { pattern } => match cached_regexp(pattern) {
+				Some(regex) => article_texts(article).iter().any(|text| regex.test(text)),
+				None => false,
+			}
+			Filter::Authors { usernames } => {
+				let mut authors = vec![article.author_username(), article.author_name()];
+				if let Some(actual) = article.actual_article().and_then(|a| a.upgrade()) {
+					let actual = actual.borrow();
+					authors.push(actual.author_username());
+					authors.push(actual.author_name());
+				}
+				usernames.iter().any(|username| authors.iter().any(|author| author.eq_ignore_ascii_case(username)))
+			}
+			Filter::LikeCount { min, max } => {
+				let count = match article.actual_article() {
+					Some(a) => a.upgrade().unwrap().borrow().like_count(),
+					None => article.like_count(),
+				};
+				*min <= count && max.map(|max| count <= max).unwrap_or(true)
+			}
+			Filter::RepostCount { min, max } => {
+				let count = match article.actual_article() {
+					Some(a) => a.upgrade().unwrap().borrow().repost_count(),
+					None => article.repost_count(),
+				};
+				*min <= count && max.map(|max| count <= max).unwrap_or(true)
+			}
+			Filter::CreatedWithin { hours } => {
+				let creation_time = match article.actual_article() {
+					Some(a) => a.upgrade().unwrap().borrow().creation_time(),
+					None => article.creation_time(),
+				};
+				js_sys::Date::now() - creation_time.get_time() <= *hours as f64 * 3_600_000.0
+			}
+			Filter::MediaCount { min } => article_media(article).len() >= *min as usize,
+			Filter::MediaType { media_type } => article_media(article).iter()
+				.any(|media| media.media_type == *media_type),
+			Filter::Orientation { orientation } => article_media(article).iter()
+				.any(|media| orientation.matches(*media.ratio.get())),
+			Filter::AspectRatio { min, max } => article_media(article).iter()
+				.any(|media| {
+					let ratio = media.ratio.get() * 100.0;
+					min.map(|min| ratio >= min as f32).unwrap_or(true) &&
+						max.map(|max| ratio <= max as f32).unwrap_or(true)
+				}),
+			//An empty group lets everything through, rather than nothing for Or
+			Filter::Group { operator: GroupOperator::And, filters } => filters.iter()
+				.all(|instance| instance.matches(article)),
+			Filter::Group { operator: GroupOperator::Or, filters } => {
+				let mut enabled = filters.iter().filter(|instance| instance.enabled).peekable();
+				enabled.peek().is_none() || enabled.any(|instance| instance.matches(article))
+			}
 		}
 	}
 
@@ -158,6 +375,58 @@ impl Filter {
 					</div>
 				}
 			}
+			Filter::TextContains { keyword: text } | Filter::TextWholeWord { word: text } | Filter::TextRegex { pattern: text } => {
+				let label = match self {
+					Filter::TextContains { .. } => "Keyword",
+					Filter::TextWholeWord { .. } => "Word",
+					_ => "Regex",
+				};
+				let invalid_regex = matches!(self, Filter::TextRegex { .. }) && cached_regexp(text).is_none();
+				html! {
+					<div class="field has-addons">
+						<div class="field-label is-small">
+							<label class="label">{ label }</label>
+						</div>
+						<div class="field-body">
+							<div class="control">
+								<input type="text" class={classes!("input", invalid_regex.then(|| "is-danger"))} onchange={move |input| callback.emit((0, input))} value={text.clone()}/>
+							</div>
+						</div>
+					</div>
+				}
+			}
+			Filter::Authors { usernames } => {
+				html! {
+					<div class="field has-addons">
+						<div class="field-label is-small">
+							<label class="label">{ "Usernames" }</label>
+						</div>
+						<div class="field-body">
+							<div class="control">
+								<input type="text" class="input" placeholder="Comma separated" onchange={move |input| callback.emit((0, input))} value={usernames.join(", ")}/>
+							</div>
+						</div>
+					</div>
+				}
+			}
+			Filter::AspectRatio { min, max } => {
+				html! {
+					<>
+						{ number_parameter_view("Min height/width %", *min, 0, callback.clone()) }
+						{ number_parameter_view("Max height/width %", *max, 1, callback) }
+					</>
+				}
+			}
+			Filter::LikeCount { min, max } | Filter::RepostCount { min, max } => {
+				html! {
+					<>
+						{ number_parameter_view("Min", Some(*min), 0, callback.clone()) }
+						{ number_parameter_view("Max", *max, 1, callback) }
+					</>
+				}
+			}
+			Filter::CreatedWithin { hours } => number_parameter_view("Hours", Some(*hours), 0, callback),
+			Filter::MediaCount { min } => number_parameter_view("Min", Some(*min), 0, callback),
 			_ => html! {}
 		}
 	}
@@ -192,11 +461,143 @@ impl Filter {
 					_ => false
 				}
 			}
+			Filter::TextContains { keyword: text } | Filter::TextWholeWord { word: text } | Filter::TextRegex { pattern: text } => {
+				match param_index {
+					0 => {
+						let new_text = event.target().unwrap()
+							.dyn_into::<HtmlInputElement>().unwrap()
+							.value();
+						if new_text == *text {
+							false
+						} else {
+							*text = new_text;
+							true
+						}
+					}
+					_ => false
+				}
+			}
+			Filter::Authors { usernames } => {
+				match param_index {
+					0 => {
+						let new_usernames: Vec<String> = event.target().unwrap()
+							.dyn_into::<HtmlInputElement>().unwrap()
+							.value()
+							.split(',')
+							.map(|username| username.trim().trim_start_matches('@').to_owned())
+							.filter(|username| !username.is_empty())
+							.collect();
+						if new_usernames == *usernames {
+							false
+						} else {
+							*usernames = new_usernames;
+							true
+						}
+					}
+					_ => false
+				}
+			}
+			Filter::LikeCount { min, max } | Filter::RepostCount { min, max } => {
+				let new_value = number_parameter_value(&event);
+				match param_index {
+					0 => replace_if_changed(min, new_value.unwrap_or_default()),
+					1 => replace_if_changed(max, new_value),
+					_ => false
+				}
+			}
+			Filter::AspectRatio { min, max } => {
+				let new_value = number_parameter_value(&event);
+				match param_index {
+					0 => replace_if_changed(min, new_value),
+					1 => replace_if_changed(max, new_value),
+					_ => false
+				}
+			}
+			Filter::CreatedWithin { hours: value } | Filter::MediaCount { min: value } => {
+				match param_index {
+					0 => replace_if_changed(value, number_parameter_value(&event).unwrap_or_default()),
+					_ => false
+				}
+			}
 			_ => false,
 		}
 	}
 }
 
+fn number_parameter_view(label: &'static str, value: Option<u32>, param_index: u8, callback: Callback<(u8, Event)>) -> Html {
+	html! {
+		<div class="field has-addons">
+			<div class="field-label is-small">
+				<label class="label">{ label }</label>
+			</div>
+			<div class="field-body">
+				<div class="control">
+					<input type="number" class="input" min=0 onchange={move |input| callback.emit((param_index, input))} value={value.map(|v| v.to_string()).unwrap_or_default()}/>
+				</div>
+			</div>
+		</div>
+	}
+}
+
+/// None if left empty or invalid
+fn number_parameter_value(event: &Event) -> Option<u32> {
+	event.target().unwrap()
+		.dyn_into::<HtmlInputElement>().unwrap()
+		.value()
+		.parse().ok()
+}
+
+fn replace_if_changed<T: PartialEq>(value: &mut T, new_value: T) -> bool {
+	if *value == new_value {
+		false
+	} else {
+		*value = new_value;
+		true
+	}
+}
+
+/// Media of the article along with the reposted and quoted ones
+fn article_media(article: &Ref<dyn ArticleData>) -> Vec<ArticleMedia> {
+	let mut media = article.media();
+	for ref_article in article.referenced_articles() {
+		let referenced = match ref_article {
+			ArticleRefType::Reposted(a) | ArticleRefType::Quote(a) => vec![a],
+			ArticleRefType::RepostedQuote(a, q) => vec![a, q],
+			ArticleRefType::Reply(_) => vec![],
+		};
+		media.extend(referenced.into_iter().filter_map(|a| a.upgrade()).flat_map(|a| a.borrow().media()));
+	}
+	media
+}
+
+/// Text of the article along with the reposted and quoted ones
+fn article_texts(article: &Ref<dyn ArticleData>) -> Vec<String> {
+	let mut texts = vec![article.text()];
+	for ref_article in article.referenced_articles() {
+		let referenced = match ref_article {
+			ArticleRefType::Reposted(a) | ArticleRefType::Quote(a) => vec![a],
+			ArticleRefType::RepostedQuote(a, q) => vec![a, q],
+			ArticleRefType::Reply(_) => vec![],
+		};
+		texts.extend(referenced.into_iter().filter_map(|a| a.upgrade()).map(|a| a.borrow().text()));
+	}
+	texts
+}
+
+/// Both already lowercased, `word` can span multiple words
+pub fn contains_whole_word(text: &str, word: &str) -> bool {
+	if word.is_empty() {
+		return true;
+	}
+
+	text.match_indices(word).any(|(start, _)| {
+		let before = text[..start].chars().next_back();
+		let after = text[start + word.len()..].chars().next();
+		!before.map(char::is_alphanumeric).unwrap_or(false) &&
+			!after.map(char::is_alphanumeric).unwrap_or(false)
+	})
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct FilterInstance {
 	pub filter: Filter,
@@ -220,6 +621,30 @@ impl FilterInstance {
 			inverted: false,
 		}
 	}
+
+	/// Whether the article passes, disabled filters letting everything through
+	pub fn matches(&self, article: &Ref<dyn ArticleData>) -> bool {
+		!self.enabled || self.filter.filter(article) != self.inverted
+	}
+
+	/// Name along with the parameters, to tell instances of the same filter apart
+	pub fn describe(&self) -> String {
+		let name = self.filter.name(self.inverted);
+		let bound = |bound: &Option<u32>| bound.map(|b| b.to_string()).unwrap_or_else(|| "any".to_owned());
+		match &self.filter {
+			Filter::Repost { by_username: Some(username) } | Filter::Quote { by_username: Some(username) } =>
+				format!("{} by @{}", name, username),
+			Filter::TextContains { keyword: text } | Filter::TextWholeWord { word: text } | Filter::TextRegex { pattern: text } =>
+				format!("{} \"{}\"", name, text),
+			Filter::Authors { usernames } => format!("{} {}", name, usernames.join(", ")),
+			Filter::LikeCount { min, max } | Filter::RepostCount { min, max } => format!("{} {} and {}", name, min, bound(max)),
+			Filter::CreatedWithin { hours } => format!("{} {} hours", name, hours),
+			Filter::MediaCount { min } => format!("{} {}", name, min),
+			Filter::AspectRatio { min, max } => format!("{} {} and {} hundredths", name, bound(min), bound(max)),
+			Filter::Group { filters, .. } => format!("{} {} filters", name, filters.len()),
+			_ => name.to_owned(),
+		}
+	}
 }
 
 fn is_animated(media: &ArticleMedia) -> bool {
@@ -240,9 +665,14 @@ pub enum FilterMsg {
 	AddFilter((Filter, bool)),
 	RemoveFilter(usize),
 	ParameterChange(usize, u8, Event),
+	ToggleGroupOperator(usize),
+	/// Message for the filters of the group at this index
+	InGroup(usize, Box<FilterMsg>),
+	/// Replaces every filter with the named preset's
+	LoadPreset(String, FilterCollection),
 }
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FilterCollection(Vec<FilterInstance>);
 
 impl<const N: usize> From<[FilterInstance; N]> for FilterCollection {
@@ -291,10 +721,20 @@ impl<'a> IntoIterator for &'a FilterCollection {
 }
 
 impl FilterCollection {
-	pub fn new() -> Self {
+	pub const fn new() -> Self {
 		Self(Vec::new())
 	}
 
+	/// Whether any enabled filter needs the articles to be refiltered as time passes
+	pub fn is_time_dependent(&self) -> bool {
+		self.iter().any(|instance| instance.enabled && instance.filter.is_time_dependent())
+	}
+
+	/// Index of the first filter rejecting the article
+	pub fn rejecting_index(&self, article: &Ref<dyn ArticleData>) -> Option<usize> {
+		self.iter().position(|instance| !instance.matches(article))
+	}
+
 	pub fn update(&mut self, msg: FilterMsg) -> bool {
 		match msg {
 			FilterMsg::ToggleFilterEnabled(index) => {
@@ -316,6 +756,24 @@ impl FilterCollection {
 			FilterMsg::ParameterChange(index, param_index, event) => {
 				self[index].filter.parameter_change(param_index, event)
 			}
+			FilterMsg::ToggleGroupOperator(index) => match &mut self[index].filter {
+				Filter::Group { operator, .. } => {
+					*operator = match operator {
+						GroupOperator::And => GroupOperator::Or,
+						GroupOperator::Or => GroupOperator::And,
+					};
+					true
+				}
+				_ => false,
+			}
+			FilterMsg::InGroup(index, msg) => match &mut self[index].filter {
+				Filter::Group { filters, .. } => filters.update(*msg),
+				_ => false,
+			}
+			FilterMsg::LoadPreset(_name, filters) => {
+				*self = filters;
+				true
+			}
 		}
 	}
 }
@@ -324,6 +782,11 @@ impl FilterCollection {
 pub struct FilterOptionsProps {
 	pub filters: FilterCollection,
 	pub callback: Callback<FilterMsg>,
+	#[prop_or(true)]
+	pub show_presets: bool,
+	/// Articles rejected by each filter, by index
+	#[prop_or_default]
+	pub rejection_counts: Vec<usize>,
 }
 
 #[function_component(FiltersOptions)]
@@ -365,6 +828,10 @@ pub fn filters_options(props: &FilterOptionsProps) -> Html {
 						<div class="field has-addons">
 							<div class="field-label is-normal">
 								<label class="label">{ filter_instance.filter.name(filter_instance.inverted) }</label>
+								{ match props.rejection_counts.get(filter_index) {
+									Some(count) => html! { <span class="tag" title="Articles rejected">{ count }</span> },
+									None => html! {},
+								} }
 							</div>
 							<div class="field-body">
 								<div class="control">
@@ -385,9 +852,39 @@ pub fn filters_options(props: &FilterOptionsProps) -> Html {
 							</div>
 						</div>
 						{filter_instance.filter.parameter_view(param_callback)}
+						{ if let Filter::Group { filters, .. } = &filter_instance.filter {
+							let toggle_operator_onclick = {
+								let callback = props.callback.clone();
+								Callback::from(move |_| callback.emit(FilterMsg::ToggleGroupOperator(filter_index)))
+							};
+							let group_callback = {
+								let callback = props.callback.clone();
+								Callback::from(move |msg| callback.emit(FilterMsg::InGroup(filter_index, Box::new(msg))))
+							};
+
+							html! {
+								<div class="box">
+									<div class="field">
+										<div class="control">
+											<button class="button is-small" onclick={toggle_operator_onclick}>
+												{"Switch AND/OR"}
+											</button>
+										</div>
+									</div>
+									<FiltersOptions filters={filters.clone()} callback={group_callback} show_presets=false/>
+								</div>
+							}
+						} else {
+							html! {}
+						} }
 					</>
 				}
 			}) }
+			{ if props.show_presets {
+				html! { <FilterPresetsOptions filters={props.filters.clone()} callback={props.callback.clone()}/> }
+			} else {
+				html! {}
+			} }
 			// TODO has-addons
 			<Dropdown current_label={DropdownLabel::Text("New Filter".to_owned())}>
 				{ for Filter::iter().cloned().map(|filter| {
@@ -1,26 +1,36 @@
 use std::rc::{Rc, Weak};
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
 use yew::prelude::*;
-use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
+use yew_agent::{Bridge, Bridged};
 use wasm_bindgen::JsCast;
 use web_sys::{Element, HtmlInputElement};
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{thread_rng, Rng};
+use gloo_timers::callback::Interval;
 
 pub mod sort_methods;
 pub mod agent;
 pub mod filters;
 pub mod timeline_container;
+pub mod dedup;
+pub mod filter_presets;
+pub mod article_index;
 mod containers;
 mod autoscroll;
 
 pub use containers::Container;
+use article_index::ArticleIndex;
 use autoscroll::{AutoScroll, start_autoscroll, scroll_to_top};
 use containers::{view_container, ContainerProps as ContainerProps, ContainerMsg};
 use filters::{FilterCollection, FilterMsg, FiltersOptions};
 use sort_methods::SortMethod;
-use agent::{TimelineAgent, TimelineRequest};
+use agent::{SoshalTimelineStorage, TimelineAgent, TimelineRequest};
+use filter_presets::{FilterPresetAgent, FilterPresetRequest, FilterPresetResponse};
+use dedup::{ThumbnailHashAgent, ThumbnailHashRequest, ThumbnailHashResponse, find_duplicates, thumbnail_url};
 use crate::articles::{ArticleView, ArticleRefType, ArticleWeak, ArticleBox};
-use crate::services::endpoint_agent::{EndpointAgent, EndpointRequest};
+use crate::services::Gap;
+use crate::services::endpoint_agent::{EndpointAgent, EndpointId, EndpointRequest, EndpointResponse, EndpointView};
+use crate::services::garbage_collector::forget_articles;
 use crate::modals::ModalCard;
 use crate::choose_endpoints::ChooseEndpoints;
 use crate::components::{Dropdown, DropdownLabel, FA, IconSize};
@@ -33,11 +43,23 @@ pub type TimelineId = i8;
 pub struct Timeline {
 	endpoints: Rc<RefCell<Vec<TimelineEndpointWrapper>>>,
 	articles: Vec<ArticleWeak>,
+	/// Filtered and sorted articles, updated incrementally
+	article_index: ArticleIndex,
 	options_shown: bool,
 	compact: bool,
 	animated_as_gifs: bool,
 	hide_text: bool,
+	collapse_duplicates: bool,
+	thumbnail_hashes: HashMap<String, u64>,
+	thumbnail_hash_agent: Box<dyn Bridge<ThumbnailHashAgent>>,
+	/// Group of each duplicate article, for the index generation and hash count it was found with
+	duplicate_groups: RefCell<((usize, usize), HashMap<*const (), *const ()>)>,
 	filters: FilterCollection,
+	/// Preset whose changes are applied to the filters
+	filter_preset: Option<String>,
+	_filter_preset_agent: Box<dyn Bridge<FilterPresetAgent>>,
+	/// Redraws periodically while the filters depend on the current time
+	refilter_interval: Option<Interval>,
 	sort_method: (Option<SortMethod>, bool),
 	_container: Container,
 	_column_count: u8,
@@ -54,7 +76,10 @@ pub struct Timeline {
 	should_organize_articles: bool,
 	article_actions: Box<dyn Bridge<ArticleActionsAgent>>,
 	timeline_agent: Box<dyn Bridge<TimelineAgent>>,
-	endpoint_agent: Dispatcher<EndpointAgent>,
+	endpoint_agent: Box<dyn Bridge<EndpointAgent>>,
+	/// Whether none of the endpoints have more articles to load at the bottom
+	exhausted: bool,
+	gaps: Vec<(EndpointId, Gap)>,
 }
 
 pub enum TimelineMsg {
@@ -69,16 +94,22 @@ pub enum TimelineMsg {
 	ToggleCompact,
 	ToggleAnimatedAsGifs,
 	ToggleHideText,
+	ToggleCollapseDuplicates,
+	ThumbnailHashResponse(ThumbnailHashResponse),
 	ChangeContainer(Container),
 	ChangeArticleView(ArticleView),
 	ChangeColumnCount(u8),
 	ChangeWidth(u8),
+	/// Stable shuffle with a new seed
 	Shuffle,
 	SetChooseEndpointModal(bool),
 	Autoscroll,
 	ScrollTop,
 	FilterMsg(FilterMsg),
+	FilterPresetResponse(FilterPresetResponse),
+	UnlinkFilterPreset,
 	SetSortMethod(Option<&'static SortMethod>),
+	AddSortKey(&'static SortMethod, bool),
 	ToggleSortReversed,
 	SortOnce(&'static SortMethod),
 	ActionsCallback(ArticleActionsResponse),
@@ -94,6 +125,8 @@ pub enum TimelineMsg {
 	BalanceContainer,
 	ContainerCallback(ContainerMsg),
 	TimelineResponse(TimelineResponse),
+	EndpointResponse(EndpointResponse),
+	FillGap(EndpointId, Gap),
 }
 
 #[derive(Properties, Clone)]
@@ -123,6 +156,8 @@ pub struct TimelineProps {
 	#[prop_or_default]
 	pub filters: Option<FilterCollection>,
 	#[prop_or_default]
+	pub filter_preset: Option<String>,
+	#[prop_or_default]
 	pub sort_method: Option<(SortMethod, bool)>,
 	#[prop_or_default]
 	pub compact: bool,
@@ -131,6 +166,8 @@ pub struct TimelineProps {
 	#[prop_or_default]
 	pub hide_text: bool,
 	#[prop_or_default]
+	pub collapse_duplicates: bool,
+	#[prop_or_default]
 	pub rtl: bool,
 	#[prop_or(false)]
 	pub modal: bool,
@@ -167,25 +204,52 @@ impl Component for Timeline {
 			None => Rc::new(RefCell::new(Vec::new()))
 		};
 
-		let mut endpoint_agent = EndpointAgent::dispatcher();
+		let mut endpoint_agent = EndpointAgent::bridge(ctx.link().callback(Msg::EndpointResponse));
 		endpoint_agent.send(EndpointRequest::InitTimeline(ctx.props().id.clone(), endpoints.clone(), ctx.link().callback(Msg::NewArticles)));
+		endpoint_agent.send(EndpointRequest::GetState);
 
 		let mut timeline_agent = TimelineAgent::bridge(ctx.link().callback(Msg::TimelineResponse));
 		timeline_agent.send(TimelineRequest::RegisterTimeline(ctx.props().id));
 
+		//Checks the linked preset still exists and adopts its filters
+		let mut filter_preset_agent = FilterPresetAgent::bridge(ctx.link().callback(Msg::FilterPresetResponse));
+		if ctx.props().filter_preset.is_some() {
+			filter_preset_agent.send(FilterPresetRequest::GetPresets);
+		}
+
+		let mut thumbnail_hash_agent = ThumbnailHashAgent::bridge(ctx.link().callback(Msg::ThumbnailHashResponse));
+		if ctx.props().collapse_duplicates {
+			thumbnail_hash_agent.send(ThumbnailHashRequest::Hash(thumbnail_urls(&ctx.props().articles)));
+		}
+
+		let filters = ctx.props().filters.as_ref().map(|f| f.clone()).unwrap_or_else(|| FilterCollection::default());
+		let sort_method = match &ctx.props().sort_method {
+			Some((method, reversed)) => (Some(method.clone()), *reversed),
+			None => (None, true)
+		};
+
+		let refilter_interval = refilter_interval(ctx, &filters);
+
 		Self {
 			endpoints,
 			articles: ctx.props().articles.clone(),
+			article_index: ArticleIndex::new(&ctx.props().articles, &filters, &sort_method),
 			options_shown: false,
 			compact: ctx.props().compact,
 			animated_as_gifs: ctx.props().animated_as_gifs,
 			hide_text: ctx.props().hide_text,
+			collapse_duplicates: ctx.props().collapse_duplicates,
+			thumbnail_hashes: HashMap::new(),
+			thumbnail_hash_agent,
+			duplicate_groups: RefCell::new(((usize::MAX, 0), HashMap::new())),
 			endpoint_agent,
-			filters: ctx.props().filters.as_ref().map(|f| f.clone()).unwrap_or_else(|| FilterCollection::default()),
-			sort_method: match ctx.props().sort_method {
-				Some((method, reversed)) => (Some(method), reversed),
-				None => (None, true)
-			},
+			exhausted: false,
+			gaps: Vec::new(),
+			filters,
+			filter_preset: ctx.props().filter_preset.clone(),
+			_filter_preset_agent: filter_preset_agent,
+			refilter_interval,
+			sort_method,
 			_container: ctx.props().container,
 			_column_count: ctx.props().column_count,
 			width: ctx.props().width,
@@ -219,28 +283,45 @@ impl Component for Timeline {
 				false
 			}
 			Msg::Refreshed(articles) => {
+				if self.collapse_duplicates {
+					self.thumbnail_hash_agent.send(ThumbnailHashRequest::Hash(thumbnail_urls(&articles)));
+				}
+				self.article_index.insert(&articles, &self.filters, &self.sort_method);
 				self.articles.extend(articles);
 				true
 			}
 			Msg::RefreshFail => false,
 			Msg::NewArticles(articles) => {
+				if self.collapse_duplicates {
+					self.thumbnail_hash_agent.send(ThumbnailHashRequest::Hash(thumbnail_urls(&articles)));
+				}
+				let mut indexed = Vec::new();
 				for a in articles {
-					let exists = self.articles.iter()
-						.any(
+					let existing = self.articles.iter()
+						.find(
 							|existing| existing.upgrade()
 								.zip(a.upgrade())
 								.map(|(e_s, a_s)| e_s.borrow().id() == a_s.borrow().id())
 								.unwrap_or(false)
-						);
+						)
+						.map(|existing| Weak::ptr_eq(existing, &a));
 
-					if !exists {
-						self.articles.push(a);
+					match existing {
+						//Already there, re-evaluated in case it changed
+						Some(true) => indexed.push(a),
+						Some(false) => {}
+						None => {
+							self.articles.push(a.clone());
+							indexed.push(a);
+						}
 					}
 				}
+				self.article_index.insert(&indexed, &self.filters, &self.sort_method);
 				true
 			}
 			Msg::ClearArticles => {
 				self.articles.clear();
+				self.article_index.clear();
 				true
 			}
 			Msg::ToggleOptions => {
@@ -259,6 +340,18 @@ impl Component for Timeline {
 				self.hide_text = !self.hide_text;
 				true
 			}
+			Msg::ToggleCollapseDuplicates => {
+				self.collapse_duplicates = !self.collapse_duplicates;
+				if self.collapse_duplicates {
+					self.thumbnail_hash_agent.send(ThumbnailHashRequest::Hash(thumbnail_urls(&self.articles)));
+				}
+				true
+			}
+			Msg::ThumbnailHashResponse(ThumbnailHashResponse::UpdatedHashes(hashes)) => {
+				let count = self.thumbnail_hashes.len();
+				self.thumbnail_hashes.extend(hashes);
+				self.collapse_duplicates && self.thumbnail_hashes.len() != count
+			}
 			Msg::ChangeContainer(c) => {
 				if ctx.props().main_timeline {
 					self.timeline_agent.send(TimelineRequest::SetMainContainer(c))
@@ -284,8 +377,8 @@ impl Component for Timeline {
 				true
 			}
 			Msg::Shuffle => {
-				self.articles.shuffle(&mut thread_rng());
-				self.sort_method = (None, false);
+				self.sort_method = (Some(SortMethod::Random(thread_rng().gen())), false);
+				self.article_index.resort(&self.articles, &self.sort_method);
 				true
 			}
 			Msg::SetChooseEndpointModal(value) => {
@@ -302,28 +395,93 @@ impl Component for Timeline {
 
 				false
 			}
-			Msg::FilterMsg(msg) => self.filters.update(msg),
+			Msg::FilterMsg(msg) => {
+				let loaded_preset = match &msg {
+					FilterMsg::LoadPreset(name, _) => Some(name.clone()),
+					_ => None,
+				};
+				let changed = self.filters.update(msg);
+				if changed {
+					self.article_index.refilter(&self.filters);
+					self.refilter_interval = refilter_interval(ctx, &self.filters);
+				}
+
+				//Local edits unlink the preset, so propagating it doesn't overwrite them
+				let linked = self.filter_preset.is_some();
+				if loaded_preset.is_some() || changed {
+					self.filter_preset = loaded_preset;
+				}
+				changed || linked != self.filter_preset.is_some()
+			}
+			Msg::UnlinkFilterPreset => {
+				self.filter_preset = None;
+				true
+			}
+			Msg::FilterPresetResponse(response) => match response {
+				FilterPresetResponse::PresetChanged(preset) if self.filter_preset.as_ref() == Some(&preset.name) => {
+					self.filters = preset.filters;
+					self.article_index.refilter(&self.filters);
+					self.refilter_interval = refilter_interval(ctx, &self.filters);
+					true
+				}
+				FilterPresetResponse::PresetReplaced(preset) if self.filter_preset.as_ref() == Some(&preset.name) && self.filters != preset.filters => {
+					self.filter_preset = None;
+					true
+				}
+				//Catches up with changes made while the timeline wasn't loaded
+				FilterPresetResponse::UpdatedPresets(presets) => match &self.filter_preset {
+					Some(name) => match presets.into_iter().find(|preset| &preset.name == name) {
+						Some(preset) if preset.filters != self.filters => {
+							self.filters = preset.filters;
+							self.article_index.refilter(&self.filters);
+							self.refilter_interval = refilter_interval(ctx, &self.filters);
+							true
+						}
+						Some(_) => false,
+						None => {
+							self.filter_preset = None;
+							true
+						}
+					}
+					None => false,
+				}
+				_ => false,
+			}
 			Msg::SetSortMethod(new_method) => {
-				self.sort_method.0 = new_method.map(|method| *method);
+				self.sort_method.0 = new_method.cloned();
+				self.article_index.resort(&self.articles, &self.sort_method);
+				true
+			}
+			Msg::AddSortKey(method, reversed) => {
+				self.sort_method = match self.sort_method.0.take() {
+					Some(current) => (Some(current.then_by(self.sort_method.1, method.clone(), reversed)), false),
+					None => (Some(method.clone()), reversed),
+				};
+				self.article_index.resort(&self.articles, &self.sort_method);
 				true
 			}
 			Msg::SortOnce(method) => {
+				let now = js_sys::Date::now();
 				self.articles.sort_by(|a, b| {
 					match self.sort_method.1 {
-						false => method.compare(&a, &b),
-						true => method.compare(&a, &b).reverse(),
+						false => method.compare_at(&a, &b, now),
+						true => method.compare_at(&a, &b, now).reverse(),
 					}
 				});
+				self.article_index.resort(&self.articles, &self.sort_method);
 				true
 			}
 			Msg::ToggleSortReversed => {
 				self.sort_method.1 = !self.sort_method.1;
+				self.article_index.resort(&self.articles, &self.sort_method);
 				true
 			}
 			Msg::ActionsCallback(response) => {
 				match response {
-					//Could filter articles for perfs
-					ArticleActionsResponse::RedrawTimelines(_articles) => true
+					ArticleActionsResponse::RedrawTimelines(articles) => {
+						self.article_index.update(&articles, &self.filters, &self.sort_method);
+						true
+					}
 				}
 			}
 			Msg::SetMainTimeline => {
@@ -351,7 +509,12 @@ impl Component for Timeline {
 				self.lazy_loading = !self.lazy_loading;
 				true
 			}
-			Msg::Redraw => true,
+			Msg::Redraw => {
+				if self.filters.is_time_dependent() {
+					self.article_index.refilter(&self.filters);
+				}
+				true
+			}
 			Msg::MarkAllAsRead => {
 				self.article_actions.send(ArticleActionsRequest::Action(Action::MarkAsRead, self.filtered_sectioned_articles(ctx, None)));
 				false
@@ -384,21 +547,62 @@ impl Component for Timeline {
 					self.article_actions.send(ArticleActionsRequest::Action(action, self.filtered_sectioned_articles(ctx, Some(filters))));
 					false
 				}
+				TimelineResponse::SerializeTimeline => {
+					let storage = if ctx.props().modal { None } else { Some(self.storage(ctx)) };
+					self.timeline_agent.send(TimelineRequest::SerializedTimeline(ctx.props().id, storage));
+					false
+				}
+				_ => false,
+			}
+			Msg::EndpointResponse(response) => match response {
+				EndpointResponse::UpdatedState(_services, endpoint_views) => {
+					let endpoints = self.endpoints.borrow();
+					//Load bottom only goes through paginated on_refresh endpoints
+					let paginated: Vec<&EndpointView> = endpoint_views.iter()
+						.filter(|view| view.paginated && endpoints.iter().any(|e| e.on_refresh && e.id == view.id))
+						.collect();
+					let exhausted = !paginated.is_empty() && paginated.iter().all(|view| view.exhausted);
+
+					let gaps: Vec<(EndpointId, Gap)> = endpoint_views.iter()
+						.filter(|view| endpoints.iter().any(|e| e.id == view.id))
+						.flat_map(|view| view.gaps.iter().map(move |gap| (view.id, gap.clone())))
+						.collect();
+
+					let changed = exhausted != self.exhausted || gaps != self.gaps;
+					self.exhausted = exhausted;
+					self.gaps = gaps;
+					changed
+				}
+				EndpointResponse::ForgotArticles(forgotten) | EndpointResponse::MutedArticles(forgotten) => {
+					let changed = forget_articles(&mut self.articles, &forgotten);
+					self.article_index.forget(&forgotten) || changed
+				}
 				_ => false,
 			}
+			Msg::FillGap(endpoint_id, gap) => {
+				self.endpoint_agent.send(EndpointRequest::FillGap(endpoint_id, gap));
+				false
+			}
 		}
 	}
 
 	fn view(&self, ctx: &Context<Self>) -> Html {
 		let articles = self.sectioned_articles(ctx, None);
+		let articles = if self.collapse_duplicates {
+			self.collapse_duplicates(articles)
+		} else {
+			articles.into_iter().map(|(a, rejected_by)| (a, rejected_by, Vec::new())).collect()
+		};
 
 		let articles: Vec<ArticleStruct> = articles.into_iter()
-			.map(|(a, included)| {
+			.map(|(a, rejected_by, also_posted_on)| {
 				let strong = a.upgrade().expect("upgrading article");
 				let borrow = strong.borrow();
 				ArticleStruct {
 					weak: a,
-					in_section: included,
+					in_section: rejected_by.is_none(),
+					rejected_by,
+					also_posted_on,
 					boxed: borrow.clone_data(),
 					boxed_actual_article_index_opt: borrow.actual_article_index(),
 					boxed_actual_article_opt: borrow.actual_article().map(|a| a.upgrade().unwrap().borrow().clone_data()),
@@ -426,6 +630,8 @@ impl Component for Timeline {
 			None
 		};
 
+		let (gap_markers, unanchored_gaps) = self.gap_markers(ctx, &articles);
+
 		let article_count = articles.len() as u8;
 		let column_count = if self.app_settings(ctx).keep_column_count {
 			self.column_count(ctx)
@@ -473,7 +679,7 @@ impl Component for Timeline {
 						<button onclick={ctx.link().callback(|_| Msg::Refresh)} title="Refresh">
 							<FA icon="sync-alt" size={IconSize::Large}/>
 						</button>
-						<button onclick={ctx.link().callback(|_| Msg::LoadBottom)} title="Load Bottom">
+						<button onclick={ctx.link().callback(|_| Msg::LoadBottom)} title={if self.exhausted { "No more articles" } else { "Load Bottom" }} disabled={self.exhausted}>
 							<FA icon="arrow-down" size={IconSize::Large}/>
 						</button>
 						<button onclick={ctx.link().callback(|_| Msg::LoadTop)} title="Load Top">
@@ -485,6 +691,7 @@ impl Component for Timeline {
 					</div>
 				</div>
 				{ self.view_options(ctx) }
+				{ for unanchored_gaps }
 				{ view_container(&self.container(ctx), yew::props! {ContainerProps {
 					container_ref: self.container_ref.clone(),
 					compact: self.compact,
@@ -497,7 +704,8 @@ impl Component for Timeline {
 					lazy_loading: self.lazy_loading,
 					article_view: self.article_view,
 					articles,
-					app_settings: self.app_settings(ctx)
+					app_settings: self.app_settings(ctx),
+					gap_markers,
 				}}) }
 			</div>
 		}
@@ -521,10 +729,50 @@ impl Timeline {
 		}
 	}
 
+	/// Current state, the endpoints being serialized by the endpoint agent
+	fn storage(&self, ctx: &Context<Self>) -> SoshalTimelineStorage {
+		SoshalTimelineStorage {
+			title: ctx.props().name.clone(),
+			container: self.container(ctx),
+			endpoints: Vec::new(),
+			column_count: self.column_count(ctx),
+			width: self.width,
+			filters: Some(self.filters.clone()),
+			filter_preset: self.filter_preset.clone(),
+			sort_method: self.sort_method.0.clone().map(|method| (method, self.sort_method.1)),
+			compact: self.compact,
+			animated_as_gifs: self.animated_as_gifs,
+			hide_text: self.hide_text,
+			collapse_duplicates: self.collapse_duplicates,
+		}
+	}
+
 	fn app_settings(&self, ctx: &Context<Self>) -> AppSettings {
 		ctx.props().app_settings.unwrap().override_settings(&self.app_settings_override)
 	}
 
+	/// Markers placed after the article above each gap, and the ones without a shown article above them
+	fn gap_markers(&self, ctx: &Context<Self>, articles: &[ArticleStruct]) -> (Vec<(String, Html)>, Vec<Html>) {
+		let mut anchored = Vec::new();
+		let mut unanchored = Vec::new();
+		for (endpoint_id, gap) in self.gaps.iter().cloned() {
+			let title = format!("Missing articles between {} and {}", &gap.older, &gap.newer);
+			let key = format!("gap{}{}", endpoint_id, &gap.newer);
+			let above = articles.iter().find(|a| a.boxed.id() == gap.newer).map(ArticleStruct::global_id);
+			let marker = html! {
+				<button class="button is-small is-fullwidth gapMarker" {key} {title} onclick={ctx.link().callback(move |_| Msg::FillGap(endpoint_id, gap.clone()))}>
+					{"Load missing articles"}
+				</button>
+			};
+
+			match above {
+				Some(above) => anchored.push((above, marker)),
+				None => unanchored.push(marker),
+			}
+		}
+		(anchored, unanchored)
+	}
+
 	//TODO Collapse boxes
 	//TODO Move options to separate file/component?
 	fn view_options(&self, ctx: &Context<Self>) -> Html {
@@ -660,6 +908,12 @@ impl Timeline {
 						{ " Lazy media loading" }
 					</label>
 				</div>
+				<div class="control">
+					<label class="checkbox">
+						<input type="checkbox" checked={self.collapse_duplicates} onclick={ctx.link().callback(|_| Msg::ToggleCollapseDuplicates)}/>
+						{ " Collapse duplicates" }
+					</label>
+				</div>
 				{ match self.article_view {
 					ArticleView::Social => html! {
 						<>
@@ -726,16 +980,26 @@ impl Timeline {
 	fn view_filters_options(&self, ctx: &Context<Self>) -> Html {
 		html! {
 			<div class="box">
+				{ match &self.filter_preset {
+					Some(preset) => html! {
+						<p class="help">
+							{ format!("Linked to preset \"{}\", editing the filters unlinks it. ", preset) }
+							<a onclick={ctx.link().callback(|_| Msg::UnlinkFilterPreset)}>{"Unlink"}</a>
+						</p>
+					},
+					None => html! {},
+				} }
 				<FiltersOptions
 					filters={self.filters.clone()}
 					callback={ctx.link().callback(Msg::FilterMsg)}
+					rejection_counts={self.article_index.rejection_counts(self.filters.len())}
 				/>
 			</div>
 		}
 	}
 
 	fn view_sort_options(&self, ctx: &Context<Self>) -> Html {
-		let current_method_name = self.sort_method.0.map(|method| (method.to_string()));
+		let current_method_name = self.sort_method.0.as_ref().map(|method| method.to_string());
 		let sort_once = if self.sort_method.0.is_none() {
 			html! {
 				<div class="control">
@@ -752,6 +1016,22 @@ impl Timeline {
 			html! {}
 		};
 
+		let then_by = if self.sort_method.0.is_some() {
+			html! {
+				<div class="control">
+					<Dropdown current_label={DropdownLabel::Text("Then by".to_owned())}>
+						{ for SortMethod::iter().flat_map(|method| [false, true].map(|reversed| html! {
+							<a class="dropdown-item" onclick={ctx.link().callback(move |_| Msg::AddSortKey(method, reversed))}>
+								{ format!("{} - {}", method, method.direction_label(reversed)) }
+							</a>
+						})) }
+					</Dropdown>
+				</div>
+			}
+		} else {
+			html! {}
+		};
+
 		html! {
 			<div class="box">
 				<div class="block field has-addons">
@@ -771,51 +1051,41 @@ impl Timeline {
 						</div>
 						<div class="control">
 							<button class="button" onclick={ctx.link().callback(|_| Msg::ToggleSortReversed)}>
-								{ match self.sort_method.0 {
+								{ match &self.sort_method.0 {
 									Some(method) => method.direction_label(self.sort_method.1),
 									None => if self.sort_method.1 { "Reversed" } else { "Normal" }
 								} }
 							</button>
 						</div>
 						{ sort_once }
+						{ then_by }
 					</div>
 				</div>
 			</div>
 		}
 	}
 
-	fn sectioned_articles(&self, ctx: &Context<Self>, extra_filters: Option<FilterCollection>) -> Vec<(ArticleWeak, bool)> {
-		let mut articles: Vec<(ArticleWeak, bool)> = self.articles.iter().cloned().map(|a| (a, true)).collect();
+	/// Articles along with the name of the filter rejecting them, if any
+	fn sectioned_articles(&self, ctx: &Context<Self>, extra_filters: Option<FilterCollection>) -> Vec<(ArticleWeak, Option<String>)> {
+		let name = |filters: &FilterCollection, index: usize| filters.get(index)
+			.map(|instance| format!("filter {}, {}", index + 1, instance.describe()));
 
-		let mut filters = self.filters.clone();
-		if let Some(extra_filters) = extra_filters {
-			filters.extend(extra_filters);
-		}
-
-		for instance in filters {
-			if instance.enabled {
-				articles = articles.into_iter().map(|(a, included)| {
-					let strong = a.upgrade();
-					if let Some(strong) = strong {
-						(a, included && instance.filter.filter(&strong.borrow()) != instance.inverted)
-					} else {
-						(a, false)
-					}
-				}).collect();
-			}
-		}
+		let mut articles: Vec<(ArticleWeak, Option<String>)> = self.article_index.entries().iter()
+			.filter(|(a, _)| a.strong_count() > 0)
+			.map(|(a, rejected_by)| {
+				let rejected_by = match (rejected_by, &extra_filters) {
+					(Some(index), _) => name(&self.filters, *index),
+					(None, Some(extra_filters)) => a.upgrade()
+						.and_then(|strong| extra_filters.rejecting_index(&strong.borrow()))
+						.and_then(|index| name(extra_filters, index)),
+					(None, None) => None,
+				};
+				(a.clone(), rejected_by)
+			})
+			.collect();
 
 		if let ArticleFilteredMode::Hidden = self.app_settings(ctx).article_filtered_mode {
-			articles = articles.into_iter().filter(|(_, included)| *included).collect();
-		}
-
-		if let Some(method) = self.sort_method.0 {
-			articles.sort_by(|(a, _), (b, _)| {
-				match self.sort_method.1 {
-					false => method.compare(&a, &b),
-					true => method.compare(&a, &b).reverse(),
-				}
-			});
+			articles = articles.into_iter().filter(|(_, rejected_by)| rejected_by.is_none()).collect();
 		}
 
 		if self.use_section {
@@ -828,8 +1098,59 @@ impl Timeline {
 		articles
 	}
 
+	/// Duplicate groups of the included articles, only searched again once the index or the hashes changed
+	fn duplicate_groups(&self) -> Ref<HashMap<*const (), *const ()>> {
+		let key = (self.article_index.generation(), self.thumbnail_hashes.len());
+		if self.duplicate_groups.borrow().0 != key {
+			let included: Vec<ArticleWeak> = self.article_index.entries().iter()
+				.filter(|(a, rejected_by)| rejected_by.is_none() && a.strong_count() > 0)
+				.map(|(a, _)| a.clone())
+				.collect();
+			let groups = find_duplicates(&included, &self.thumbnail_hashes).into_iter()
+				.enumerate()
+				.filter_map(|(i, original)| original.map(|original| (article_ptr(&included[i]), article_ptr(&included[original]))))
+				.collect();
+			*self.duplicate_groups.borrow_mut() = (key, groups);
+		}
+
+		Ref::map(self.duplicate_groups.borrow(), |(_, groups)| groups)
+	}
+
+	/// Keeps the first shown article of each group of duplicates, along with the service and url of the others
+	fn collapse_duplicates(&self, articles: Vec<(ArticleWeak, Option<String>)>) -> Vec<(ArticleWeak, Option<String>, Vec<(&'static str, String)>)> {
+		let groups = self.duplicate_groups();
+		let mut shown_groups: HashMap<*const (), usize> = HashMap::new();
+		let mut collapsed: Vec<(ArticleWeak, Option<String>, Vec<(&'static str, String)>)> = Vec::new();
+
+		for (a, rejected_by) in articles {
+			if rejected_by.is_some() {
+				collapsed.push((a, rejected_by, Vec::new()));
+				continue;
+			}
+
+			let ptr = article_ptr(&a);
+			let group = groups.get(&ptr).copied().unwrap_or(ptr);
+			match shown_groups.get(&group) {
+				Some(shown) => if let (Some(strong), Some(shown_strong)) = (a.upgrade(), collapsed[*shown].0.upgrade()) {
+					let borrow = strong.borrow();
+					let posted = (borrow.service(), borrow.url());
+					let others = &mut collapsed[*shown].2;
+					if shown_strong.borrow().url() != posted.1 && !others.contains(&posted) {
+						others.push(posted);
+					}
+				}
+				None => {
+					shown_groups.insert(group, collapsed.len());
+					collapsed.push((a, rejected_by, Vec::new()));
+				}
+			}
+		}
+
+		collapsed
+	}
+
 	fn filtered_sectioned_articles(&self, ctx: &Context<Self>, extra_filters: Option<FilterCollection>) -> Vec<ArticleWeak> {
-		self.sectioned_articles(ctx, extra_filters).into_iter().filter_map(|(a, included)| if included {
+		self.sectioned_articles(ctx, extra_filters).into_iter().filter_map(|(a, rejected_by)| if rejected_by.is_none() {
 			Some(a)
 		} else {
 			None
@@ -837,9 +1158,43 @@ impl Timeline {
 	}
 }
 
+/// First image of each article, to be hashed
+fn thumbnail_urls(articles: &[ArticleWeak]) -> Vec<String> {
+	articles.iter()
+		.filter_map(|a| a.upgrade())
+		.filter_map(|a| {
+			let borrow = a.borrow();
+			let actual = borrow.actual_article().and_then(|actual| actual.upgrade());
+			let url = match &actual {
+				Some(actual) => thumbnail_url(&actual.borrow()),
+				None => thumbnail_url(&borrow),
+			};
+			url
+		})
+		.collect()
+}
+
+/// Redraws every minute when the filters depend on the current time, to refilter the articles
+fn refilter_interval(ctx: &Context<Timeline>, filters: &FilterCollection) -> Option<Interval> {
+	if filters.is_time_dependent() {
+		let callback = ctx.link().callback(|_| Msg::Redraw);
+		Some(Interval::new(60_000, move || callback.emit(())))
+	} else {
+		None
+	}
+}
+
+fn article_ptr(article: &ArticleWeak) -> *const () {
+	article.as_ptr() as *const ()
+}
+
 pub struct ArticleStruct {
 	pub weak: ArticleWeak,
 	pub in_section: bool,
+	/// Index and description of the filter excluding the article from the section
+	pub rejected_by: Option<String>,
+	/// Service and url of the collapsed duplicates
+	pub also_posted_on: Vec<(&'static str, String)>,
 	pub boxed: ArticleBox,
 	boxed_actual_article_index_opt: Option<usize>,
 	boxed_actual_article_opt: Option<ArticleBox>,
@@ -864,6 +1219,8 @@ impl Clone for ArticleStruct {
 		Self {
 			weak: self.weak.clone(),
 			in_section: self.in_section,
+			rejected_by: self.rejected_by.clone(),
+			also_posted_on: self.also_posted_on.clone(),
 			boxed: self.boxed.clone_data(),
 			boxed_actual_article_index_opt: self.boxed_actual_article_index_opt,
 			boxed_actual_article_opt: self.boxed_actual_article_opt.as_ref().map(|a| a.clone_data()),
@@ -876,6 +1233,8 @@ impl PartialEq for ArticleStruct {
 	fn eq(&self, other: &Self) -> bool {
 		Weak::ptr_eq(&self.weak, &other.weak) &&
 			self.in_section == other.in_section &&
+			self.rejected_by == other.rejected_by &&
+			self.also_posted_on == other.also_posted_on &&
 			&self.boxed == &other.boxed &&
 			self.boxed_refs == other.boxed_refs
 	}
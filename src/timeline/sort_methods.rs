@@ -1,38 +1,81 @@
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 use serde::{Serialize, Deserialize};
 
 use crate::articles::{weak_actual_article, ArticleWeak};
 
-//TODO Check for cases where Copy is derivable
-#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub enum SortMethod {
 	Id,
 	Index,
 	Date,
 	Likes,
 	Reposts,
+	/// Likes and reposts per hour since creation
+	EngagementPerHour,
+	/// Alphabetical by author username
+	Author,
+	MediaCount,
+	/// Stable shuffle, ordering articles by a hash of their id and the seed
+	Random(u64),
+	/// Compares each key in turn, each with its own direction
+	MultiKey(Vec<(SortMethod, bool)>),
 }
 
+/// Single key methods offered in the options
+static SORT_METHODS: [SortMethod; 8] = [
+	SortMethod::Id,
+	SortMethod::Index,
+	SortMethod::Date,
+	SortMethod::Likes,
+	SortMethod::Reposts,
+	SortMethod::EngagementPerHour,
+	SortMethod::Author,
+	SortMethod::MediaCount,
+];
+
 impl SortMethod {
 	pub fn iter() -> impl ExactSizeIterator<Item = &'static SortMethod> {
-		[
-			SortMethod::Id,
-			SortMethod::Index,
-			SortMethod::Date,
-			SortMethod::Likes,
-			SortMethod::Reposts,
-		].iter()
+		SORT_METHODS.iter()
 	}
 
 	pub fn direction_label(&self, reversed: bool) -> &'static str {
 		match self {
 			SortMethod::Date => if reversed { "Reverse chronological" } else { "Chronological" },
+			SortMethod::Author => if reversed { "Z to A" } else { "A to Z" },
+			SortMethod::Random(_) => if reversed { "Reversed" } else { "Normal" },
 			_ => if reversed { "Descending" } else { "Ascending" },
 		}
 	}
 
+	/// Whether the key of an article changes over time, and not only when the article does
+	pub fn is_time_dependent(&self) -> bool {
+		match self {
+			SortMethod::EngagementPerHour => true,
+			SortMethod::MultiKey(keys) => keys.iter().any(|(method, _)| method.is_time_dependent()),
+			_ => false,
+		}
+	}
+
+	/// Appends a key, turning the method into a multi-key one
+	///
+	/// `reversed` is folded into the existing keys, so the result is meant to be used unreversed.
+	pub fn then_by(self, reversed: bool, method: SortMethod, method_reversed: bool) -> SortMethod {
+		let mut keys = match self {
+			SortMethod::MultiKey(keys) => keys.into_iter().map(|(key, key_reversed)| (key, key_reversed != reversed)).collect(),
+			method => vec![(method, reversed)],
+		};
+		keys.push((method, method_reversed));
+		SortMethod::MultiKey(keys)
+	}
+
 	//TODO Unit test sort methods
 	pub fn compare(&self, a: &ArticleWeak, b: &ArticleWeak) -> std::cmp::Ordering {
+		self.compare_at(a, b, js_sys::Date::now())
+	}
+
+	/// Same as `compare`, with `now` captured once by the caller for the whole sort
+	pub fn compare_at(&self, a: &ArticleWeak, b: &ArticleWeak, now: f64) -> std::cmp::Ordering {
 		match self {
 			SortMethod::Id => {
 				let a = a.upgrade().map(|s| s.borrow().sortable_id()).unwrap_or_default();
@@ -61,10 +104,57 @@ impl SortMethod {
 				let b = b.upgrade().map(|s| s.borrow().repost_count()).unwrap_or_default();
 				a.cmp(&b)
 			}
+			SortMethod::EngagementPerHour => {
+				engagement_per_hour(a, now).partial_cmp(&engagement_per_hour(b, now)).unwrap_or(Ordering::Equal)
+			}
+			SortMethod::Author => {
+				let (a, b) = (weak_actual_article(&a), weak_actual_article(&b));
+				let a = a.upgrade().map(|s| s.borrow().author_username().to_lowercase()).unwrap_or_default();
+				let b = b.upgrade().map(|s| s.borrow().author_username().to_lowercase()).unwrap_or_default();
+				a.cmp(&b)
+			}
+			SortMethod::MediaCount => {
+				let (a, b) = (weak_actual_article(&a), weak_actual_article(&b));
+				let a = a.upgrade().map(|s| s.borrow().media().len()).unwrap_or_default();
+				let b = b.upgrade().map(|s| s.borrow().media().len()).unwrap_or_default();
+				a.cmp(&b)
+			}
+			SortMethod::Random(seed) => {
+				let a = a.upgrade().map(|s| random_key(*seed, s.borrow().service(), &s.borrow().id())).unwrap_or_default();
+				let b = b.upgrade().map(|s| random_key(*seed, s.borrow().service(), &s.borrow().id())).unwrap_or_default();
+				a.cmp(&b)
+			}
+			SortMethod::MultiKey(keys) => keys.iter()
+				.map(|(method, reversed)| match reversed {
+					false => method.compare_at(a, b, now),
+					true => method.compare_at(a, b, now).reverse(),
+				})
+				.find(|ordering| *ordering != Ordering::Equal)
+				.unwrap_or(Ordering::Equal),
+		}
+	}
+}
+
+fn engagement_per_hour(article: &ArticleWeak, now: f64) -> f64 {
+	let actual = weak_actual_article(article);
+	match actual.upgrade() {
+		Some(strong) => {
+			let borrow = strong.borrow();
+			let hours = (now - borrow.creation_time().get_time()) / 3_600_000.0;
+			(borrow.like_count() + borrow.repost_count()) as f64 / hours.max(1.0)
 		}
+		None => 0.0,
 	}
 }
 
+/// FNV-1a hash of the seed and article, so the order survives reloads
+pub fn random_key(seed: u64, service: &str, id: &str) -> u64 {
+	seed.to_le_bytes().iter()
+		.chain(service.as_bytes())
+		.chain(id.as_bytes())
+		.fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
 impl Display for SortMethod {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -73,6 +163,14 @@ impl Display for SortMethod {
 			SortMethod::Date => f.write_str("Date"),
 			SortMethod::Likes => f.write_str("Likes"),
 			SortMethod::Reposts => f.write_str("Reposts"),
+			SortMethod::EngagementPerHour => f.write_str("Engagement per hour"),
+			SortMethod::Author => f.write_str("Author"),
+			SortMethod::MediaCount => f.write_str("Media count"),
+			SortMethod::Random(_) => f.write_str("Random"),
+			SortMethod::MultiKey(keys) => {
+				let names: Vec<String> = keys.iter().map(|(method, reversed)| format!("{} ({})", method, method.direction_label(*reversed))).collect();
+				f.write_str(&names.join(", then "))
+			}
 		}
 	}
 }
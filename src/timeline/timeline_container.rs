@@ -15,6 +15,8 @@ use crate::modals::{
 	add_timeline::AddTimelineModal,
 	Modal,
 	batch_action::BatchActionModal,
+	endpoint_dashboard::EndpointDashboardModal,
+	mute_list::MuteListModal,
 };
 use crate::timeline::filters::FilterCollection;
 
@@ -182,6 +184,13 @@ impl Component for TimelineContainer {
 					}
 					true
 				}
+				TimelineResponse::ClearTimelines => {
+					for timeline in self.timelines.drain(..) {
+						self.endpoint_agent.send(EndpointRequest::RemoveTimeline(timeline.id));
+					}
+					self.main_timeline = self.timeline_counter;
+					true
+				}
 				TimelineResponse::AddQuickUserTimeline(service, username) => {
 					let callback = {
 						let username = username.clone();
@@ -241,7 +250,9 @@ impl Component for TimelineContainer {
 		html! {
 			<>
 				<AddTimelineModal {add_timeline_callback}/>
-				<BatchActionModal {timeline_ids}/>
+				<BatchActionModal timeline_ids={timeline_ids.clone()}/>
+				<EndpointDashboardModal {timeline_ids}/>
+				<MuteListModal/>
 				{ self.page_info.as_ref().map(|p| p.view()).unwrap_or_default() }
 
 				<div id="timelineContainer">
@@ -11,12 +11,13 @@ fn main() -> std::io::Result<()> {
         let file_name = path.as_ref().and_then(|p| p.file_name()).and_then(|s| s.to_str());
         let extension = path.as_ref().and_then(|p| p.extension()).and_then(|s| s.to_str());
         if let Some((file_name, extension)) = file_name.zip(extension) {
-            let prefix = match extension {
-                "css" => "partial-index-",
-                _ => "index-",
+            //Trunk names the main bin's output after it once a worker bin is declared
+            let prefixes: &[&str] = match extension {
+                "css" => &["partial-index-"],
+                _ => &["index-", "soshalthing-"],
             };
 
-            if file_name.contains(prefix) {
+            if prefixes.iter().any(|prefix| file_name.starts_with(prefix)) {
                 json[extension] = Value::String(file_name.to_string());
             }
         }